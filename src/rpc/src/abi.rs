@@ -0,0 +1,321 @@
+//! A minimal Solidity ABI encoder and decoder covering the types used by the RPC clients.
+//! https://docs.soliditylang.org/en/latest/abi-spec.html
+
+use primitive_types::U256;
+use std::fmt;
+
+const WORD_SIZE: usize = 32;
+
+pub type Address = [u8; 20];
+pub type Selector = [u8; 4];
+
+/// `balanceOf(address)`
+pub const BALANCE_OF: Selector = [0x70, 0xa0, 0x82, 0x31];
+/// `decimals()`
+pub const DECIMALS: Selector = [0x31, 0x3c, 0xe5, 0x67];
+/// `symbol()`
+pub const SYMBOL: Selector = [0x95, 0xd8, 0x9b, 0x41];
+/// `aggregate3((address,bool,bytes)[])` of Multicall3.
+pub const AGGREGATE3: Selector = [0x82, 0xad, 0x56, 0xcb];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AbiError {
+    InvalidAddress(String),
+    UnexpectedEnd,
+    InvalidOffset,
+    InvalidUtf8,
+}
+
+impl fmt::Display for AbiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiError::InvalidAddress(address) => write!(f, "Invalid address: '{address}'"),
+            AbiError::UnexpectedEnd => write!(f, "Unexpected end of ABI data"),
+            AbiError::InvalidOffset => write!(f, "Invalid offset in ABI data"),
+            AbiError::InvalidUtf8 => write!(f, "Invalid UTF-8 string in ABI data"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    Address(Address),
+    Uint(U256),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Token>),
+    Tuple(Vec<Token>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Uint,
+    Bool,
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    Tuple(Vec<ParamType>),
+}
+
+impl Token {
+    pub fn into_uint(self) -> Option<U256> {
+        match self {
+            Token::Uint(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_bool(self) -> Option<bool> {
+        match self {
+            Token::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            Token::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_string(self) -> Option<String> {
+        match self {
+            Token::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the items of an array or a tuple.
+    pub fn into_tokens(self) -> Option<Vec<Token>> {
+        match self {
+            Token::Array(tokens) | Token::Tuple(tokens) => Some(tokens),
+            _ => None,
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+            Token::Tuple(tokens) => tokens.iter().any(Token::is_dynamic),
+            Token::Address(_) | Token::Uint(_) | Token::Bool(_) => false,
+        }
+    }
+
+    /// The size of the head of a static token.
+    fn head_size(&self) -> usize {
+        match self {
+            Token::Tuple(tokens) if !self.is_dynamic() => tokens.iter().map(Token::head_size).sum(),
+            _ => WORD_SIZE,
+        }
+    }
+}
+
+impl ParamType {
+    fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::Tuple(types) => types.iter().any(ParamType::is_dynamic),
+            ParamType::Address | ParamType::Uint | ParamType::Bool => false,
+        }
+    }
+
+    /// The size of the head of a static type.
+    fn head_size(&self) -> usize {
+        match self {
+            ParamType::Tuple(types) if !self.is_dynamic() => {
+                types.iter().map(ParamType::head_size).sum()
+            }
+            _ => WORD_SIZE,
+        }
+    }
+}
+
+/// Parses a `0x`-prefixed hex address. The checksum isn't validated.
+pub fn parse_address(address: &str) -> Result<Address, AbiError> {
+    let invalid = || AbiError::InvalidAddress(address.to_string());
+    let hex_address = address.strip_prefix("0x").ok_or_else(invalid)?;
+    let bytes = hex::decode(hex_address).map_err(|_| invalid())?;
+    bytes.try_into().map_err(|_| invalid())
+}
+
+/// Encodes a function call: the `selector` followed by the encoded `args`.
+pub fn encode_call(selector: Selector, args: &[Token]) -> Vec<u8> {
+    let mut data = selector.to_vec();
+    data.extend(encode(args));
+    data
+}
+
+/// Encodes the `tokens` as a tuple, e.g. function arguments.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let head_size: usize = tokens.iter().map(Token::head_size).sum();
+
+    let mut heads = Vec::with_capacity(head_size);
+    let mut tails = Vec::new();
+    for token in tokens {
+        if token.is_dynamic() {
+            heads.extend(encode_uint(U256::from(head_size + tails.len())));
+            tails.extend(encode_token(token));
+        } else {
+            heads.extend(encode_token(token));
+        }
+    }
+    heads.extend(tails);
+    heads
+}
+
+/// Decodes the `data` as a tuple of the `types`, e.g. function results.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, AbiError> {
+    let mut head = 0;
+    let mut tokens = Vec::with_capacity(types.len());
+    for param_type in types {
+        let token = if param_type.is_dynamic() {
+            let offset = read_offset(data, head)?;
+            decode_token(
+                param_type,
+                data.get(offset..).ok_or(AbiError::InvalidOffset)?,
+            )?
+        } else {
+            decode_token(param_type, data.get(head..).ok_or(AbiError::UnexpectedEnd)?)?
+        };
+        tokens.push(token);
+        head += param_type.head_size();
+    }
+    Ok(tokens)
+}
+
+fn encode_token(token: &Token) -> Vec<u8> {
+    match token {
+        Token::Address(address) => {
+            let mut word = vec![0; WORD_SIZE - address.len()];
+            word.extend(address);
+            word
+        }
+        Token::Uint(value) => encode_uint(*value),
+        Token::Bool(value) => encode_uint(U256::from(*value as u8)),
+        Token::Bytes(bytes) => encode_bytes(bytes),
+        Token::String(string) => encode_bytes(string.as_bytes()),
+        Token::Array(tokens) => {
+            let mut data = encode_uint(U256::from(tokens.len()));
+            data.extend(encode(tokens));
+            data
+        }
+        Token::Tuple(tokens) => encode(tokens),
+    }
+}
+
+fn encode_uint(value: U256) -> Vec<u8> {
+    let mut word = vec![0; WORD_SIZE];
+    value.to_big_endian(&mut word);
+    word
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut data = encode_uint(U256::from(bytes.len()));
+    data.extend(bytes);
+    let padding = (WORD_SIZE - bytes.len() % WORD_SIZE) % WORD_SIZE;
+    data.extend(vec![0; padding]);
+    data
+}
+
+fn decode_token(param_type: &ParamType, data: &[u8]) -> Result<Token, AbiError> {
+    let token = match param_type {
+        ParamType::Address => {
+            let word = read_word(data, 0)?;
+            Token::Address(word[WORD_SIZE - 20..].try_into().expect("20 bytes"))
+        }
+        ParamType::Uint => Token::Uint(U256::from_big_endian(read_word(data, 0)?)),
+        ParamType::Bool => Token::Bool(!U256::from_big_endian(read_word(data, 0)?).is_zero()),
+        ParamType::Bytes => Token::Bytes(read_bytes(data)?.to_vec()),
+        ParamType::String => {
+            let string =
+                String::from_utf8(read_bytes(data)?.to_vec()).map_err(|_| AbiError::InvalidUtf8)?;
+            Token::String(string)
+        }
+        ParamType::Array(item_type) => {
+            let len = read_offset(data, 0)?;
+            // Every item takes at least a word, so don't trust the length blindly.
+            if len > data.len() / WORD_SIZE {
+                return Err(AbiError::UnexpectedEnd);
+            }
+            let types = vec![item_type.as_ref().clone(); len];
+            Token::Array(decode(&types, &data[WORD_SIZE..])?)
+        }
+        ParamType::Tuple(types) => Token::Tuple(decode(types, data)?),
+    };
+    Ok(token)
+}
+
+fn read_word(data: &[u8], position: usize) -> Result<&[u8], AbiError> {
+    data.get(position..position + WORD_SIZE)
+        .ok_or(AbiError::UnexpectedEnd)
+}
+
+/// Reads a word as an offset or a length.
+fn read_offset(data: &[u8], position: usize) -> Result<usize, AbiError> {
+    let value = U256::from_big_endian(read_word(data, position)?);
+    if value > U256::from(data.len()) {
+        return Err(AbiError::InvalidOffset);
+    }
+    Ok(value.as_usize())
+}
+
+fn read_bytes(data: &[u8]) -> Result<&[u8], AbiError> {
+    let len = read_offset(data, 0)?;
+    data.get(WORD_SIZE..WORD_SIZE + len)
+        .ok_or(AbiError::UnexpectedEnd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_encode_decode() {
+        let owner = parse_address("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap();
+        let call = encode_call(BALANCE_OF, &[Token::Address(owner)]);
+        assert_eq!(
+            hex::encode(call),
+            "70a08231000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"
+        );
+
+        let calls = Token::Array(vec![
+            Token::Tuple(vec![
+                Token::Address(owner),
+                Token::Bool(true),
+                Token::Bytes(vec![1, 2, 3]),
+            ]),
+            Token::Tuple(vec![
+                Token::Address(owner),
+                Token::Bool(false),
+                Token::Bytes(Vec::new()),
+            ]),
+        ]);
+        let data = encode(std::slice::from_ref(&calls));
+        let types = [ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))];
+        assert_eq!(decode(&types, &data).unwrap(), vec![calls]);
+
+        // `symbol()` of USDC.
+        let symbol = hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000004\
+             5553444300000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap();
+        assert_eq!(
+            decode(&[ParamType::String], &symbol).unwrap(),
+            vec![Token::String("USDC".to_string())]
+        );
+        assert_eq!(
+            decode(&[ParamType::String], &symbol[..64]),
+            Err(AbiError::UnexpectedEnd)
+        );
+        assert!(parse_address("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045").is_err());
+    }
+}
@@ -0,0 +1,22 @@
+/// The state of an account on the account-model chains, e.g. XRP Ledger or Stellar.
+///
+/// These chains require a minimum reserve to create an account, so an address that has never been funded
+/// has no account at all, which differs from an existing account with a zero balance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountState<T> {
+    /// The account doesn't exist on the ledger.
+    NotActivated,
+    Active(T),
+}
+
+impl<T> AccountState<T> {
+    pub fn is_activated(&self) -> bool { matches!(self, AccountState::Active(_)) }
+
+    /// Returns the account, or `None` if it isn't activated.
+    pub fn active(self) -> Option<T> {
+        match self {
+            AccountState::NotActivated => None,
+            AccountState::Active(account) => Some(account),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::account::AccountState;
+use crate::amount::Amount;
+use crate::http::HttpError;
+use async_trait::async_trait;
+
+/// The balance and the transaction count of an address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Activity {
+    /// The native token balance.
+    pub balance: Amount,
+    /// `None` if the API doesn't provide a transaction count.
+    pub transaction_count: Option<usize>,
+}
+
+/// A client that reports the activity of the addresses of a single chain.
+#[async_trait]
+pub trait ActivityRpc {
+    /// Requests the balance and the transaction count of the address,
+    /// or [`AccountState::NotActivated`] if the account doesn't exist on chain.
+    async fn activity(&self, address: &str) -> Result<AccountState<Activity>, HttpError>;
+}
@@ -0,0 +1,200 @@
+use crate::account::AccountState;
+use crate::activity::{Activity, ActivityRpc};
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, HttpTransportExt, Request, SharedTransport};
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const ALGORAND_INDEXER_URL: &str = "https://mainnet-idx.algonode.cloud";
+
+/// The maximum `limit` of the indexer search endpoints.
+const PAGE_LIMIT: usize = 1_000;
+
+/// 1 ALGO = 1,000,000 microalgos.
+pub type MicroAlgos = u64;
+
+/// A client of the Algorand indexer API: https://developer.algorand.org/docs/rest-apis/indexer
+#[derive(Clone)]
+pub struct AlgorandRpc {
+    transport: SharedTransport,
+    url: String,
+}
+
+impl AlgorandRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        AlgorandRpc::with_url(transport, ALGORAND_INDEXER_URL.to_string())
+            .expect("'ALGORAND_INDEXER_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(AlgorandRpc { transport, url })
+    }
+
+    /// Requests the account. The indexer responds with `404` if the account has never been funded.
+    pub async fn account(&self, address: &str) -> Result<AccountState<AlgorandAccount>, HttpError> {
+        let res: Option<AccountResponse> =
+            self.get_json(&format!("/v2/accounts/{address}")).await?;
+        match res {
+            Some(res) => Ok(AccountState::Active(res.account)),
+            None => Ok(AccountState::NotActivated),
+        }
+    }
+
+    /// Requests all transactions of the account page by page, newest first.
+    /// Returns no transactions if the account isn't activated.
+    pub async fn transactions(&self, address: &str) -> Result<Vec<AlgorandTransaction>, HttpError> {
+        let mut txs = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let next = next_token
+                .map(|token| format!("&next={token}"))
+                .unwrap_or_default();
+            let path = format!("/v2/accounts/{address}/transactions?limit={PAGE_LIMIT}{next}");
+            let page: TransactionsPage = match self.get_json(&path).await? {
+                Some(page) => page,
+                None => return Ok(txs),
+            };
+
+            let is_last_page = page.transactions.len() < PAGE_LIMIT;
+            txs.extend(page.transactions);
+            // The `next-token` is returned even with the last page.
+            match page.next_token {
+                Some(token) if !is_last_page => next_token = Some(token),
+                _ => return Ok(txs),
+            }
+        }
+    }
+
+    /// Returns `None` if the resource isn't found.
+    async fn get_json<R>(&self, path: &str) -> Result<Option<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let request = Request::get(format!("{}{path}", self.url)).body(Vec::new())?;
+        self.transport.send_json_opt(request).await
+    }
+}
+
+#[async_trait]
+impl ActivityRpc for AlgorandRpc {
+    async fn activity(&self, address: &str) -> Result<AccountState<Activity>, HttpError> {
+        let account = match self.account(address).await? {
+            AccountState::Active(account) => account,
+            AccountState::NotActivated => return Ok(AccountState::NotActivated),
+        };
+        let txs = self.transactions(address).await?;
+        Ok(AccountState::Active(Activity {
+            balance: Amount::from_base_units(account.amount, Coin::ALGO),
+            transaction_count: Some(txs.len()),
+        }))
+    }
+}
+
+/// https://developer.algorand.org/docs/rest-apis/indexer/#account
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AlgorandAccount {
+    pub address: String,
+    pub amount: MicroAlgos,
+    /// The balance that can't be spent, raised by every asset opt-in and application.
+    pub min_balance: MicroAlgos,
+    /// The Algorand Standard Assets the account is opted in to.
+    #[serde(default)]
+    pub assets: Vec<AssetHolding>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AssetHolding {
+    pub asset_id: u64,
+    /// In the asset base units.
+    pub amount: u64,
+    pub is_frozen: bool,
+}
+
+/// https://developer.algorand.org/docs/rest-apis/indexer/#transaction
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AlgorandTransaction {
+    pub id: String,
+    /// E.g. `pay`, `axfer` or `appl`.
+    pub tx_type: String,
+    pub sender: String,
+    pub confirmed_round: u64,
+    pub round_time: u64,
+}
+
+#[derive(Deserialize)]
+struct AccountResponse {
+    account: AlgorandAccount,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TransactionsPage {
+    next_token: Option<String>,
+    transactions: Vec<AlgorandTransaction>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "VCMJKWOY5P5P7SKMZFFOCEROPJCZOTIJMNIYNUCKH7LRO45JMJP6UYBIJA";
+    const UNFUNDED: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAY5HFKQ";
+
+    fn transactions_page(range: std::ops::Range<usize>, next_token: &str) -> String {
+        let txs: Vec<_> = range
+            .map(|i| {
+                format!(
+                    r#"{{"id":"{i}","tx-type":"pay","sender":"{ADDRESS}","confirmed-round":{i},"round-time":{i}}}"#
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"current-round":1,"next-token":"{next_token}","transactions":[{}]}}"#,
+            txs.join(",")
+        )
+    }
+
+    #[tokio::test]
+    async fn test_algorand_rpc_stub() {
+        let transactions_path =
+            |next: &str| format!("/v2/accounts/{ADDRESS}/transactions?limit={PAGE_LIMIT}{next}");
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/v2/accounts/{ADDRESS}"),
+                &format!(
+                    r#"{{"current-round":1,"account":{{"address":"{ADDRESS}","amount":5100000,"min-balance":200000,
+                    "assets":[{{"asset-id":31566704,"amount":1000000,"is-frozen":false}}]}}}}"#
+                ),
+            )
+            .with_response(
+                &transactions_path(""),
+                &transactions_page(0..PAGE_LIMIT, "page2"),
+            )
+            .with_response(
+                &transactions_path("&next=page2"),
+                &transactions_page(PAGE_LIMIT..1_200, "page3"),
+            )
+            .with_status(&format!("/v2/accounts/{UNFUNDED}"), StatusCode::NOT_FOUND);
+        let rpc = AlgorandRpc::with_default_url(Arc::new(transport));
+
+        let account = rpc.account(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(account.min_balance, 200_000);
+        assert_eq!(account.assets[0].asset_id, 31566704);
+
+        let activity = rpc.activity(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(activity.balance.to_string(), "5.1 ALGO");
+        assert_eq!(activity.transaction_count, Some(1_200));
+        assert!(!rpc.activity(UNFUNDED).await.unwrap().is_activated());
+    }
+}
@@ -0,0 +1,634 @@
+//! Amounts of coins and tokens, stored in the base units, e.g. satoshis,
+//! and parsed or formatted in any unit of the coin, e.g. `sat` or `BTC`.
+
+use primitive_types::U256;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+
+/// The units of a coin or a token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Coin {
+    /// The display unit, e.g. `BTC`.
+    pub symbol: Cow<'static, str>,
+    /// The base unit the amounts are stored in, e.g. `sat`. Empty for the tokens that don't name it.
+    pub base_unit: Cow<'static, str>,
+    /// The count of the base unit digits in the display unit, e.g. `8` for BTC.
+    pub decimals: u8,
+    /// The intermediate units with their decimals, e.g. `gwei` with `9` for ETH.
+    pub sub_units: &'static [(&'static str, u8)],
+}
+
+impl Coin {
+    pub const BTC: Coin = Coin::known("BTC", "sat", 8);
+    pub const ETH: Coin = Coin {
+        symbol: Cow::Borrowed("ETH"),
+        base_unit: Cow::Borrowed("wei"),
+        decimals: 18,
+        sub_units: &[("gwei", 9)],
+    };
+    pub const ATOM: Coin = Coin::known("ATOM", "uatom", 6);
+    pub const SOL: Coin = Coin::known("SOL", "lamports", 9);
+    pub const XRP: Coin = Coin::known("XRP", "drops", 6);
+    pub const XLM: Coin = Coin::known("XLM", "stroops", 7);
+    pub const TRX: Coin = Coin::known("TRX", "sun", 6);
+    pub const DOT: Coin = Coin::known("DOT", "planck", 10);
+    pub const KSM: Coin = Coin::known("KSM", "planck", 12);
+    pub const ADA: Coin = Coin::known("ADA", "lovelace", 6);
+    pub const XTZ: Coin = Coin::known("XTZ", "mutez", 6);
+    pub const ALGO: Coin = Coin::known("ALGO", "microalgos", 6);
+    pub const NEAR: Coin = Coin::known("NEAR", "yoctoNEAR", 24);
+    pub const APT: Coin = Coin::known("APT", "octas", 8);
+
+    /// The coins the deserialized amounts take the sub-units from, see [`Amount`].
+    pub const KNOWN: &'static [Coin] = &[
+        Coin::BTC,
+        Coin::ETH,
+        Coin::ATOM,
+        Coin::SOL,
+        Coin::XRP,
+        Coin::XLM,
+        Coin::TRX,
+        Coin::DOT,
+        Coin::KSM,
+        Coin::ADA,
+        Coin::XTZ,
+        Coin::ALGO,
+        Coin::NEAR,
+        Coin::APT,
+    ];
+
+    /// Creates the units of a coin known at runtime only, e.g. a Cosmos-SDK chain from the registry.
+    pub fn new<S, B>(symbol: S, base_unit: B, decimals: u8) -> Coin
+    where
+        S: Into<String>,
+        B: Into<String>,
+    {
+        Coin {
+            symbol: Cow::Owned(symbol.into()),
+            base_unit: Cow::Owned(base_unit.into()),
+            decimals,
+            sub_units: &[],
+        }
+    }
+
+    /// Creates the units of a token that doesn't name its base unit, e.g. an ERC-20 token.
+    pub fn token<S>(symbol: S, decimals: u8) -> Coin
+    where
+        S: Into<String>,
+    {
+        Coin::new(symbol, String::default(), decimals)
+    }
+
+    /// Returns the decimals of the `unit` relative to the base unit, e.g. `9` for `gwei`.
+    /// The units are matched case-insensitively.
+    pub fn unit_decimals(&self, unit: &str) -> Option<u8> {
+        if unit.eq_ignore_ascii_case(&self.symbol) {
+            return Some(self.decimals);
+        }
+        if !self.base_unit.is_empty() && unit.eq_ignore_ascii_case(&self.base_unit) {
+            return Some(0);
+        }
+        self.sub_units
+            .iter()
+            .find(|(name, _)| unit.eq_ignore_ascii_case(name))
+            .map(|(_, decimals)| *decimals)
+    }
+
+    const fn known(symbol: &'static str, base_unit: &'static str, decimals: u8) -> Coin {
+        Coin {
+            symbol: Cow::Borrowed(symbol),
+            base_unit: Cow::Borrowed(base_unit),
+            decimals,
+            sub_units: &[],
+        }
+    }
+}
+
+/// How to round the digits that don't fit into the requested precision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero, i.e. truncate.
+    Down,
+    /// Away from zero.
+    Up,
+    /// To the nearest, ties away from zero.
+    HalfUp,
+    /// To the nearest, ties to the even digit.
+    HalfEven,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AmountError {
+    InvalidAmount(String),
+    UnknownUnit(String),
+    /// The amount has more fraction digits than its unit allows, e.g. `0.000000001 BTC`.
+    TooPrecise(String),
+    Overflow,
+    CoinMismatch(String, String),
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::InvalidAmount(amount) => write!(f, "Invalid amount: '{amount}'"),
+            AmountError::UnknownUnit(unit) => write!(f, "Unknown unit: '{unit}'"),
+            AmountError::TooPrecise(amount) => write!(f, "Too many fraction digits: '{amount}'"),
+            AmountError::Overflow => write!(f, "Amount overflow"),
+            AmountError::CoinMismatch(left, right) => {
+                write!(f, "Amounts of different coins: {left} and {right}")
+            }
+        }
+    }
+}
+
+/// An amount of a coin, e.g. a balance, stored as a signed count of the base units.
+///
+/// Formatted in the display unit, e.g. `1.5 BTC`, with the trailing zeros trimmed.
+/// The precision is rounded half up: `format!("{:.2}", amount)` gives `1.50 BTC`.
+/// Serialized with the units of its coin, e.g. `{"amount":"1.5","symbol":"BTC","base_unit":"sat","decimals":8}`,
+/// so it deserializes to the same coin, with the sub-units of the matching one of [`Coin::KNOWN`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Amount {
+    /// The absolute value in the base units.
+    raw: U256,
+    /// Never set for zero.
+    negative: bool,
+    coin: Coin,
+}
+
+impl Amount {
+    pub fn zero(coin: Coin) -> Amount { Amount::from_base_units(U256::zero(), coin) }
+
+    pub fn from_base_units<R>(raw: R, coin: Coin) -> Amount
+    where
+        R: Into<U256>,
+    {
+        Amount {
+            raw: raw.into(),
+            negative: false,
+            coin,
+        }
+    }
+
+    /// Creates an amount that can be negative, e.g. an unconfirmed balance change.
+    pub fn from_signed_base_units(raw: i128, coin: Coin) -> Amount {
+        let amount = Amount::from_base_units(raw.unsigned_abs(), coin);
+        if raw < 0 {
+            -amount
+        } else {
+            amount
+        }
+    }
+
+    /// Parses an amount with an optional unit, e.g. `1.5 BTC`, `150000000 sat` or `1.5`.
+    /// The amount without a unit is in the display unit.
+    /// Fails with [`AmountError::TooPrecise`] if the amount has more fraction digits than the unit allows.
+    pub fn parse(amount: &str, coin: Coin) -> Result<Amount, AmountError> {
+        let parsed = ParsedAmount::parse(amount, &coin)?;
+        if parsed.fraction.len() > usize::from(parsed.unit_decimals) {
+            return Err(AmountError::TooPrecise(amount.to_string()));
+        }
+        parsed.into_amount(coin, Rounding::Down)
+    }
+
+    /// Parses an amount like [`Amount::parse`], but rounds the fraction digits the unit doesn't allow.
+    pub fn parse_rounded(
+        amount: &str,
+        coin: Coin,
+        rounding: Rounding,
+    ) -> Result<Amount, AmountError> {
+        ParsedAmount::parse(amount, &coin)?.into_amount(coin, rounding)
+    }
+
+    /// Returns the absolute value in the base units.
+    pub fn raw(&self) -> U256 { self.raw }
+
+    pub fn coin(&self) -> &Coin { &self.coin }
+
+    pub fn is_zero(&self) -> bool { self.raw.is_zero() }
+
+    pub fn is_negative(&self) -> bool { self.negative }
+
+    pub fn checked_add(&self, other: &Amount) -> Result<Amount, AmountError> {
+        self.check_same_coin(other)?;
+        let (raw, negative) = if self.negative == other.negative {
+            let raw = self
+                .raw
+                .checked_add(other.raw)
+                .ok_or(AmountError::Overflow)?;
+            (raw, self.negative)
+        } else if self.raw >= other.raw {
+            (self.raw - other.raw, self.negative)
+        } else {
+            (other.raw - self.raw, other.negative)
+        };
+        Ok(Amount::with_sign(raw, negative, self.coin.clone()))
+    }
+
+    pub fn checked_sub(&self, other: &Amount) -> Result<Amount, AmountError> {
+        self.checked_add(&-other.clone())
+    }
+
+    pub fn checked_mul<F>(&self, factor: F) -> Result<Amount, AmountError>
+    where
+        F: Into<U256>,
+    {
+        let raw = self
+            .raw
+            .checked_mul(factor.into())
+            .ok_or(AmountError::Overflow)?;
+        Ok(Amount::with_sign(raw, self.negative, self.coin.clone()))
+    }
+
+    /// Converts the amount with the `price` of one display unit, e.g. `1.5 BTC` at `60000 USD` is `90000 USD`.
+    /// The result is in the units of the `price`, truncated to its decimals.
+    pub fn value_in(&self, price: &Amount) -> Result<Amount, AmountError> {
+        let scale = pow10(usize::from(self.coin.decimals)).ok_or(AmountError::Overflow)?;
+        let raw = self
+            .raw
+            .checked_mul(price.raw)
+            .ok_or(AmountError::Overflow)?
+            / scale;
+        let negative = self.negative != price.negative;
+        Ok(Amount::with_sign(raw, negative, price.coin.clone()))
+    }
+
+    /// Rounds the amount to the given count of the display unit fraction digits.
+    pub fn round(&self, fraction_digits: u8, rounding: Rounding) -> Result<Amount, AmountError> {
+        let dropped = match self.coin.decimals.checked_sub(fraction_digits) {
+            Some(dropped) if dropped > 0 => usize::from(dropped),
+            _ => return Ok(self.clone()),
+        };
+        let digits = self.raw.to_string();
+        let kept = round_digits(&digits, dropped, rounding)?;
+        let raw = if kept.is_zero() {
+            kept
+        } else {
+            pow10(dropped)
+                .and_then(|scale| kept.checked_mul(scale))
+                .ok_or(AmountError::Overflow)?
+        };
+        Ok(Amount::with_sign(raw, self.negative, self.coin.clone()))
+    }
+
+    /// Formats the amount in the given unit without the unit name, e.g. `150000000` in `sat`.
+    pub fn to_unit_string(&self, unit: &str) -> Result<String, AmountError> {
+        let decimals = self
+            .coin
+            .unit_decimals(unit)
+            .ok_or_else(|| AmountError::UnknownUnit(unit.to_string()))?;
+        Ok(self.format(usize::from(decimals), 0))
+    }
+
+    /// Formats the amount in the base unit, e.g. `150000000 sat`.
+    pub fn to_base_string(&self) -> String {
+        let raw = self.format(0, 0);
+        match self.coin.base_unit.as_ref() {
+            "" => raw,
+            base_unit => format!("{raw} {base_unit}"),
+        }
+    }
+
+    fn with_sign(raw: U256, negative: bool, coin: Coin) -> Amount {
+        Amount {
+            raw,
+            negative: negative && !raw.is_zero(),
+            coin,
+        }
+    }
+
+    fn check_same_coin(&self, other: &Amount) -> Result<(), AmountError> {
+        if self.coin != other.coin {
+            return Err(AmountError::CoinMismatch(
+                self.coin.symbol.to_string(),
+                other.coin.symbol.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Formats the raw value with the point `decimals` digits from the right,
+    /// padding the fraction with zeros to at least `min_fraction_digits`.
+    fn format(&self, decimals: usize, min_fraction_digits: usize) -> String {
+        let digits = format!("{:0>width$}", self.raw.to_string(), width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+        let mut fraction = fraction.trim_end_matches('0').to_string();
+        if fraction.len() < min_fraction_digits {
+            fraction.extend(std::iter::repeat_n(
+                '0',
+                min_fraction_digits - fraction.len(),
+            ));
+        }
+
+        let sign = if self.negative { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{sign}{integer}")
+        } else {
+            format!("{sign}{integer}.{fraction}")
+        }
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Self::Output {
+        let negative = !self.negative;
+        Amount::with_sign(self.raw, negative, self.coin)
+    }
+}
+
+/// Only the amounts of the same coin are comparable.
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.coin != other.coin {
+            return None;
+        }
+        let ordering = match (self.negative, other.negative) {
+            (false, false) => self.raw.cmp(&other.raw),
+            (true, true) => other.raw.cmp(&self.raw),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        };
+        Some(ordering)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = usize::from(self.coin.decimals);
+        let formatted = match f.precision() {
+            Some(precision) => {
+                let fraction_digits = u8::try_from(precision).unwrap_or(u8::MAX);
+                let rounded = self
+                    .round(fraction_digits, Rounding::HalfUp)
+                    .map_err(|_| fmt::Error)?;
+                rounded.format(decimals, precision.min(decimals))
+            }
+            None => self.format(decimals, 0),
+        };
+        write!(f, "{formatted} {}", self.coin.symbol)
+    }
+}
+
+/// The serialized form of an [`Amount`].
+#[derive(Deserialize, Serialize)]
+struct SerializedAmount<'a> {
+    /// In the display unit, e.g. `1.5`.
+    amount: String,
+    symbol: Cow<'a, str>,
+    base_unit: Cow<'a, str>,
+    decimals: u8,
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SerializedAmount {
+            amount: self.format(usize::from(self.coin.decimals), 0),
+            symbol: Cow::Borrowed(&self.coin.symbol),
+            base_unit: Cow::Borrowed(&self.coin.base_unit),
+            decimals: self.coin.decimals,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let amount = SerializedAmount::deserialize(deserializer)?;
+        let mut coin = Coin::new(amount.symbol, amount.base_unit, amount.decimals);
+        if let Some(known) = Coin::KNOWN.iter().find(|known| {
+            (&known.symbol, &known.base_unit, known.decimals)
+                == (&coin.symbol, &coin.base_unit, coin.decimals)
+        }) {
+            coin = known.clone();
+        }
+        Amount::parse(&amount.amount, coin).map_err(D::Error::custom)
+    }
+}
+
+/// An amount split into its parts, e.g. `-`, `1`, `5` and `8` decimals of `1.5 BTC`.
+struct ParsedAmount<'a> {
+    negative: bool,
+    integer: &'a str,
+    fraction: &'a str,
+    unit_decimals: u8,
+}
+
+impl<'a> ParsedAmount<'a> {
+    fn parse(amount: &'a str, coin: &Coin) -> Result<ParsedAmount<'a>, AmountError> {
+        let invalid = || AmountError::InvalidAmount(amount.to_string());
+
+        let mut parts = amount.split_whitespace();
+        let number = parts.next().ok_or_else(invalid)?;
+        let unit_decimals = match parts.next() {
+            Some(unit) => coin
+                .unit_decimals(unit)
+                .ok_or_else(|| AmountError::UnknownUnit(unit.to_string()))?,
+            None => coin.decimals,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, number),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return Err(invalid());
+        }
+        Ok(ParsedAmount {
+            negative,
+            integer,
+            fraction,
+            unit_decimals,
+        })
+    }
+
+    fn into_amount(self, coin: Coin, rounding: Rounding) -> Result<Amount, AmountError> {
+        let unit_decimals = usize::from(self.unit_decimals);
+        // Pad the fraction to the unit decimals, so all the digits are in the base units.
+        let digits = format!(
+            "{}{:0<width$}",
+            self.integer,
+            self.fraction,
+            width = unit_decimals
+        );
+        let dropped = self.fraction.len().saturating_sub(unit_decimals);
+        let raw = round_digits(&digits, dropped, rounding)?;
+        Ok(Amount::with_sign(raw, self.negative, coin))
+    }
+}
+
+/// Drops the given count of the least significant decimal `digits`, rounding the rest.
+fn round_digits(digits: &str, dropped: usize, rounding: Rounding) -> Result<U256, AmountError> {
+    let (kept, dropped_digits) = digits.split_at(digits.len().saturating_sub(dropped));
+    let kept = if kept.is_empty() {
+        U256::zero()
+    } else {
+        U256::from_dec_str(kept).map_err(|_| AmountError::Overflow)?
+    };
+
+    // The dropped digits are padded with leading zeros if there are fewer of them than `dropped`.
+    let is_padded = dropped_digits.len() < dropped;
+    let mut dropped_digits = dropped_digits.bytes();
+    let first = match is_padded {
+        true => b'0',
+        false => dropped_digits.next().unwrap_or(b'0'),
+    };
+    let has_rest = dropped_digits.any(|digit| digit != b'0');
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => first != b'0' || has_rest,
+        Rounding::HalfUp => first >= b'5',
+        Rounding::HalfEven => first > b'5' || (first == b'5' && (has_rest || kept.bit(0))),
+    };
+    match round_up {
+        true => kept.checked_add(U256::one()).ok_or(AmountError::Overflow),
+        false => Ok(kept),
+    }
+}
+
+fn pow10(exp: usize) -> Option<U256> { U256::from(10).checked_pow(U256::from(exp)) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_parse_format() {
+        let amount = Amount::parse("1.5 BTC", Coin::BTC).unwrap();
+        assert_eq!(amount.raw(), U256::from(150_000_000));
+        assert_eq!(amount, Amount::parse("150000000 sat", Coin::BTC).unwrap());
+        assert_eq!(amount, Amount::parse("1.50000000", Coin::BTC).unwrap());
+        assert_eq!(amount.to_string(), "1.5 BTC");
+        assert_eq!(format!("{amount:.2}"), "1.50 BTC");
+        assert_eq!(amount.to_base_string(), "150000000 sat");
+
+        let gas_price = Amount::parse("1.5 gwei", Coin::ETH).unwrap();
+        assert_eq!(gas_price.raw(), U256::from(1_500_000_000));
+        assert_eq!(gas_price.to_unit_string("gwei").unwrap(), "1.5");
+        assert_eq!(gas_price.to_string(), "0.0000000015 ETH");
+
+        let lamports = Amount::from_base_units(1u64, Coin::SOL);
+        assert_eq!(lamports.to_string(), "0.000000001 SOL");
+        assert_eq!(
+            Amount::from_signed_base_units(-250, Coin::ATOM).to_string(),
+            "-0.00025 ATOM"
+        );
+
+        assert_eq!(
+            Amount::parse("0.000000001 BTC", Coin::BTC),
+            Err(AmountError::TooPrecise("0.000000001 BTC".to_string()))
+        );
+        assert_eq!(
+            Amount::parse("1 XRP", Coin::BTC),
+            Err(AmountError::UnknownUnit("XRP".to_string()))
+        );
+        assert!(Amount::parse("1e8 sat", Coin::BTC).is_err());
+        assert!(Amount::parse(".5", Coin::BTC).is_err());
+        assert_eq!(
+            Amount::parse(&format!("{} wei", U256::MAX), Coin::ETH)
+                .unwrap()
+                .raw(),
+            U256::MAX
+        );
+        assert_eq!(
+            Amount::parse(&format!("{}0 wei", U256::MAX), Coin::ETH),
+            Err(AmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_amount_rounding() {
+        let drops = |amount: &str, rounding| {
+            let amount = Amount::parse_rounded(amount, Coin::XRP, rounding).unwrap();
+            amount.to_base_string()
+        };
+        assert_eq!(drops("0.0000015", Rounding::Down), "1 drops");
+        assert_eq!(drops("0.0000011", Rounding::Up), "2 drops");
+        assert_eq!(drops("0.0000015", Rounding::HalfUp), "2 drops");
+        assert_eq!(drops("0.0000015", Rounding::HalfEven), "2 drops");
+        assert_eq!(drops("0.0000025", Rounding::HalfEven), "2 drops");
+        assert_eq!(drops("0.00000251", Rounding::HalfEven), "3 drops");
+        assert_eq!(drops("-0.0000015", Rounding::HalfUp), "-2 drops");
+
+        let amount = Amount::parse("0.004 XRP", Coin::XRP).unwrap();
+        assert!(amount.round(2, Rounding::HalfUp).unwrap().is_zero());
+        assert_eq!(
+            amount.round(2, Rounding::Up).unwrap().to_string(),
+            "0.01 XRP"
+        );
+        assert_eq!(format!("{amount:.2}"), "0.00 XRP");
+        assert_eq!(format!("{amount:.8}"), "0.004000 XRP");
+    }
+
+    #[test]
+    fn test_amount_arithmetic() {
+        let btc = |amount: &str| Amount::parse(amount, Coin::BTC).unwrap();
+        assert_eq!(btc("1.5").checked_add(&btc("0.5")).unwrap(), btc("2"));
+        assert_eq!(btc("0.5").checked_sub(&btc("1.5")).unwrap(), btc("-1"));
+        assert_eq!(btc("-1").checked_add(&btc("1")).unwrap(), btc("0"));
+        assert!(!btc("-1").checked_add(&btc("1")).unwrap().is_negative());
+        assert_eq!(btc("0.5").checked_mul(3u64).unwrap(), btc("1.5"));
+        assert!(btc("-1") < btc("0.5"));
+
+        let max = Amount::from_base_units(U256::MAX, Coin::ETH);
+        assert_eq!(
+            max.checked_add(&Amount::parse("1 wei", Coin::ETH).unwrap()),
+            Err(AmountError::Overflow)
+        );
+        assert_eq!(
+            btc("1").checked_add(&Amount::zero(Coin::ETH)),
+            Err(AmountError::CoinMismatch(
+                "BTC".to_string(),
+                "ETH".to_string()
+            ))
+        );
+        assert_eq!(btc("1").partial_cmp(&Amount::zero(Coin::ETH)), None);
+
+        let usd = Coin::token("USD", 4);
+        let price = Amount::parse("60000.1234", usd.clone()).unwrap();
+        let value = btc("-1.5").value_in(&price).unwrap();
+        assert_eq!(value, Amount::parse("-90000.1851", usd).unwrap());
+    }
+
+    #[test]
+    fn test_amount_serde() {
+        let amount = Amount::parse("1.5 gwei", Coin::ETH).unwrap();
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(
+            json,
+            r#"{"amount":"0.0000000015","symbol":"ETH","base_unit":"wei","decimals":18}"#
+        );
+        let deserialized = serde_json::from_str::<Amount>(&json).unwrap();
+        assert_eq!(deserialized, amount);
+        assert_eq!(deserialized.to_unit_string("gwei").unwrap(), "1.5");
+
+        // The coins sharing a base unit, and the coins known at runtime only, round-trip too.
+        for amount in [
+            Amount::from_base_units(100u64, Coin::KSM),
+            Amount::from_signed_base_units(-250, Coin::new("OSMO", "uosmo", 6)),
+            Amount::parse("1.5", Coin::token("USDC", 6)).unwrap(),
+        ] {
+            let json = serde_json::to_string(&amount).unwrap();
+            assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+        }
+
+        let too_precise = r#"{"amount":"0.0000001","symbol":"USDC","base_unit":"","decimals":6}"#;
+        assert!(serde_json::from_str::<Amount>(too_precise).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::account::AccountState;
+use crate::activity::{Activity, ActivityRpc};
+use crate::amount::{self, Amount};
+use crate::http::{HttpError, HttpTransportExt, Request, SharedTransport};
+use crate::serde_str::deserialize_from_str;
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const APTOS_URL: &str = "https://fullnode.mainnet.aptoslabs.com/v1";
+
+/// The APT asset type, the node sums up its coin store and its fungible asset store.
+const APT_ASSET_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+/// 1 APT = 10^8 octas.
+pub type Octas = u64;
+
+/// A client of the Aptos node REST API: https://aptos.dev/en/build/apis/fullnode-rest-api
+#[derive(Clone)]
+pub struct AptosRpc {
+    transport: SharedTransport,
+    url: String,
+}
+
+impl AptosRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        AptosRpc::with_url(transport, APTOS_URL.to_string())
+            .expect("'APTOS_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(AptosRpc { transport, url })
+    }
+
+    /// Requests the account. The node responds with `404` if the account hasn't been created on chain.
+    pub async fn account(&self, address: &str) -> Result<AccountState<AptosAccount>, HttpError> {
+        match self.get_json(&format!("/accounts/{address}")).await? {
+            Some(account) => Ok(AccountState::Active(account)),
+            None => Ok(AccountState::NotActivated),
+        }
+    }
+
+    /// Requests the APT balance of the account, including the APT migrated to the fungible asset store.
+    /// Returns `0` if the account isn't found.
+    pub async fn apt_balance(&self, address: &str) -> Result<Octas, HttpError> {
+        let path = format!("/accounts/{address}/balance/{APT_ASSET_TYPE}");
+        let balance: Option<Balance> = self.get_json(&path).await?;
+        Ok(balance.map(Balance::octas).unwrap_or_default())
+    }
+
+    /// Returns `None` if the resource isn't found.
+    async fn get_json<R>(&self, path: &str) -> Result<Option<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let request = Request::get(format!("{}{path}", self.url)).body(Vec::new())?;
+        self.transport.send_json_opt(request).await
+    }
+}
+
+#[async_trait]
+impl ActivityRpc for AptosRpc {
+    /// The transaction count is the count of the transactions sent by the account.
+    async fn activity(&self, address: &str) -> Result<AccountState<Activity>, HttpError> {
+        let account = match self.account(address).await? {
+            AccountState::Active(account) => account,
+            AccountState::NotActivated => return Ok(AccountState::NotActivated),
+        };
+        let balance = self.apt_balance(address).await?;
+        Ok(AccountState::Active(Activity {
+            balance: Amount::from_base_units(balance, amount::Coin::APT),
+            transaction_count: Some(account.sequence_number as usize),
+        }))
+    }
+}
+
+/// https://aptos.dev/en/build/apis/fullnode-rest-api-reference#tag/accounts/GET/accounts/{address}
+#[derive(Debug, Deserialize)]
+pub struct AptosAccount {
+    /// The count of the transactions sent by the account.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub sequence_number: u64,
+    pub authentication_key: String,
+}
+
+/// https://aptos.dev/en/build/apis/fullnode-rest-api-reference#tag/accounts/GET/accounts/{address}/balance/{asset_type}
+/// The balance is a Json number, unlike the other `u64` values of the API.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Balance {
+    Number(Octas),
+    String(#[serde(deserialize_with = "deserialize_from_str")] Octas),
+}
+
+impl Balance {
+    fn octas(self) -> Octas {
+        match self {
+            Balance::Number(octas) | Balance::String(octas) => octas,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "0x07968dab936c1bad187c60ce4082f307d030d780e91e694ae03aef16aba73f30";
+    const UNFUNDED: &str = "0x0000000000000000000000000000000000000000000000000000000000000bad";
+
+    #[tokio::test]
+    async fn test_aptos_rpc_stub() {
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}"),
+                &format!(r#"{{"sequence_number":"7","authentication_key":"{ADDRESS}"}}"#),
+            )
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}/balance/{APT_ASSET_TYPE}"),
+                "250000000",
+            )
+            .with_status(&format!("/v1/accounts/{UNFUNDED}"), StatusCode::NOT_FOUND)
+            .with_status(
+                &format!("/v1/accounts/{UNFUNDED}/balance/{APT_ASSET_TYPE}"),
+                StatusCode::NOT_FOUND,
+            );
+        let rpc = AptosRpc::with_default_url(Arc::new(transport));
+
+        let activity = rpc.activity(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(
+            activity,
+            Activity {
+                balance: Amount::from_base_units(250_000_000u64, amount::Coin::APT),
+                transaction_count: Some(7)
+            }
+        );
+        assert_eq!(rpc.apt_balance(UNFUNDED).await.unwrap(), 0);
+        assert!(!rpc.activity(UNFUNDED).await.unwrap().is_activated());
+    }
+}
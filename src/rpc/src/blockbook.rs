@@ -0,0 +1,339 @@
+use crate::amount::{Amount, Coin};
+use crate::blockbook_instances::BlockbookInstance;
+use crate::blockstream::Balance;
+use crate::http::{HttpError, SharedTransport};
+use crate::serde_str::{deserialize_from_str, deserialize_optional_from_str};
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// Amounts in the smallest units of the coin, e.g. satoshis or koinu.
+/// `i64` isn't enough for the coins with a large supply, e.g. DOGE.
+pub type BaseUnits = i128;
+
+/// The maximum `pageSize` of the Blockbook API.
+const TXIDS_PAGE_SIZE: usize = 1_000;
+
+/// A Blockbook v2 REST API client.
+/// One client type covers every UTXO coin Blockbook supports, see [`BlockbookInstance`].
+///
+/// https://github.com/trezor/blockbook/blob/master/docs/api.md
+#[derive(Clone)]
+pub struct BlockbookRpc {
+    transport: SharedTransport,
+    url: String,
+}
+
+impl BlockbookRpc {
+    /// Creates a client of the given `instance`, with the URL overridden by `instance.url_env` if set.
+    pub fn with_instance(
+        transport: SharedTransport,
+        instance: &BlockbookInstance,
+    ) -> Result<Self, InvalidUri> {
+        BlockbookRpc::with_url(transport, instance.configured_url())
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(BlockbookRpc { transport, url })
+    }
+
+    /// Requests the count of the address transactions, including the mempool ones.
+    pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
+        let info = self.address_info(address).await?;
+        Ok(info.txs + info.unconfirmed_txs)
+    }
+
+    /// Requests the address balances and transaction counts.
+    pub async fn address_info(&self, address: &str) -> Result<AddressInfo, HttpError> {
+        self.get_json(&format!("/api/v2/address/{address}?details=basic"))
+            .await
+    }
+
+    /// Requests the ids of all address transactions page by page, newest first.
+    pub async fn txids(&self, address: &str) -> Result<Vec<String>, HttpError> {
+        let mut txids = Vec::new();
+        let mut page = 1;
+        loop {
+            let res: TxidsPage = self
+                .get_json(&format!(
+                    "/api/v2/address/{address}?details=txids&page={page}&pageSize={TXIDS_PAGE_SIZE}"
+                ))
+                .await?;
+            txids.extend(res.txids);
+            if res.page >= res.total_pages {
+                return Ok(txids);
+            }
+            page = res.page + 1;
+        }
+    }
+
+    /// Requests the balances of an extended public key and its used addresses.
+    /// Blockbook derives and scans the addresses of both the receive and change chains.
+    pub async fn xpub_info(&self, xpub: &str) -> Result<XpubInfo, HttpError> {
+        self.get_json(&format!(
+            "/api/v2/xpub/{xpub}?details=tokenBalances&tokens=used"
+        ))
+        .await
+    }
+
+    /// Requests the unspent outputs of an address or an extended public key, including the mempool ones.
+    pub async fn utxo(&self, address_or_xpub: &str) -> Result<Vec<Utxo>, HttpError> {
+        self.get_json(&format!("/api/v2/utxo/{address_or_xpub}"))
+            .await
+    }
+
+    /// Requests the transaction by its `txid`.
+    pub async fn transaction(&self, txid: &str) -> Result<Transaction, HttpError> {
+        self.get_json(&format!("/api/v2/tx/{txid}")).await
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let uri = format!("{}{path}", self.url).parse()?;
+        // Check the status before parsing the payload, as Blockbook responds with `{"error": ...}` on errors.
+        let res = self.transport.get(uri).await?;
+        if !res.status().is_success() {
+            let error = serde_json::from_slice::<ErrorResponse>(res.body());
+            return match error {
+                Ok(error) => Err(HttpError::ApiError(error.error)),
+                Err(_) => Err(HttpError::UnexpectedStatus(res.status())),
+            };
+        }
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressInfo {
+    pub address: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub balance: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub total_received: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub total_sent: BaseUnits,
+    /// Can be negative if the mempool transactions spend confirmed outputs.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub unconfirmed_balance: BaseUnits,
+    pub unconfirmed_txs: usize,
+    /// The count of the confirmed transactions.
+    pub txs: usize,
+}
+
+impl AddressInfo {
+    /// Returns the confirmed and unconfirmed balances in the units of the `coin`, see [`BlockbookInstance::coin`].
+    pub fn to_balance(&self, coin: &Coin) -> Balance {
+        Balance {
+            confirmed: Amount::from_signed_base_units(self.balance, coin.clone()),
+            unconfirmed: Amount::from_signed_base_units(self.unconfirmed_balance, coin.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XpubInfo {
+    /// The extended public key.
+    pub address: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub balance: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub total_received: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub total_sent: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub unconfirmed_balance: BaseUnits,
+    pub unconfirmed_txs: usize,
+    pub txs: usize,
+    /// The count of the derived addresses with at least one transaction.
+    #[serde(default)]
+    pub used_tokens: usize,
+    /// The used addresses. Blockbook calls the derived addresses "tokens".
+    #[serde(default, rename = "tokens")]
+    pub addresses: Vec<XpubAddress>,
+}
+
+impl XpubInfo {
+    /// Returns the confirmed and unconfirmed balances in the units of the `coin`, see [`BlockbookInstance::coin`].
+    pub fn to_balance(&self, coin: &Coin) -> Balance {
+        Balance {
+            confirmed: Amount::from_signed_base_units(self.balance, coin.clone()),
+            unconfirmed: Amount::from_signed_base_units(self.unconfirmed_balance, coin.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XpubAddress {
+    #[serde(rename = "name")]
+    pub address: String,
+    /// The derivation path, e.g. `m/84'/2'/0'/0/0`.
+    pub path: String,
+    /// The count of the address transactions.
+    pub transfers: usize,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub balance: BaseUnits,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub value: BaseUnits,
+    /// Absent for the mempool outputs.
+    pub height: Option<u64>,
+    pub confirmations: u64,
+    /// Set for the outputs of an extended public key.
+    pub address: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub txid: String,
+    pub vin: Vec<TxInput>,
+    pub vout: Vec<TxOutput>,
+    /// `-1` for the mempool transactions.
+    pub block_height: i64,
+    pub confirmations: u64,
+    pub block_time: u64,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub value: BaseUnits,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub fees: BaseUnits,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxInput {
+    pub n: u32,
+    /// Absent for coinbase inputs.
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub value: Option<BaseUnits>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxOutput {
+    pub n: u32,
+    /// Empty for the outputs that don't have an address, e.g. `OP_RETURN`.
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub value: BaseUnits,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TxidsPage {
+    page: usize,
+    total_pages: usize,
+    /// Absent if the address has no transactions.
+    #[serde(default)]
+    txids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use http::StatusCode;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh";
+    const XPUB: &str = "zpub6rzzaYE2jKaAiDvkwYkpqBqHHNiwAqSiaXmh5Ny6WVrUSm4c5fR2tAHLVQEhRfDN4wXDy3i5kDHmHkRkLxrqHjAnEk3BvbEpnUZU5xHsCfq";
+
+    #[tokio::test]
+    async fn test_blockbook_rpc_stub() {
+        let txids_path = |page: usize| {
+            format!(
+                "/api/v2/address/{ADDRESS}?details=txids&page={page}&pageSize={TXIDS_PAGE_SIZE}"
+            )
+        };
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/api/v2/address/{ADDRESS}?details=basic"),
+                &format!(
+                    r#"{{"page":1,"totalPages":1,"itemsOnPage":1000,"address":"{ADDRESS}","balance":"25000000000000000000",
+                    "totalReceived":"25000000000000000000","totalSent":"0","unconfirmedBalance":"-1000","unconfirmedTxs":1,"txs":2}}"#
+                ),
+            )
+            .with_response(
+                &txids_path(1),
+                r#"{"page":1,"totalPages":2,"itemsOnPage":1000,"txids":["c","b"]}"#,
+            )
+            .with_response(
+                &txids_path(2),
+                r#"{"page":2,"totalPages":2,"itemsOnPage":1000,"txids":["a"]}"#,
+            )
+            .with_response(
+                &format!("/api/v2/xpub/{XPUB}?details=tokenBalances&tokens=used"),
+                &format!(
+                    r#"{{"address":"{XPUB}","balance":"1500","totalReceived":"2500","totalSent":"1000","unconfirmedBalance":"0",
+                    "unconfirmedTxs":0,"txs":3,"usedTokens":2,"tokens":[
+                        {{"type":"XPUBAddress","name":"{ADDRESS}","path":"m/84'/2'/0'/0/0","transfers":2,"decimals":8,"balance":"0"}},
+                        {{"type":"XPUBAddress","name":"ltc1qchange","path":"m/84'/2'/0'/1/0","transfers":1,"decimals":8,"balance":"1500"}}
+                    ]}}"#
+                ),
+            )
+            .with_response(
+                &format!("/api/v2/utxo/{XPUB}"),
+                r#"[{"txid":"a","vout":1,"value":"1500","height":2500000,"confirmations":10,"address":"ltc1qchange","path":"m/84'/2'/0'/1/0"}]"#,
+            )
+            .with_response(
+                "/api/v2/tx/a",
+                &format!(
+                    r#"{{"txid":"a","vin":[{{"n":0,"addresses":["{ADDRESS}"],"value":"2500"}}],
+                    "vout":[{{"n":0,"addresses":["ltc1qother"],"value":"900"}},{{"n":1,"addresses":["ltc1qchange"],"value":"1500"}}],
+                    "blockHeight":2500000,"confirmations":10,"blockTime":1700000000,"value":"2400","valueIn":"2500","fees":"100"}}"#
+                ),
+            )
+            .with_status("/api/v2/tx/unknown", StatusCode::BAD_REQUEST);
+        let rpc = BlockbookRpc::with_url(Arc::new(transport), "https://ltc1.trezor.io".to_string())
+            .unwrap();
+
+        let info = rpc.address_info(ADDRESS).await.unwrap();
+        // Exceeds `i64::MAX`.
+        assert_eq!(info.balance, 25_000_000_000_000_000_000);
+        assert_eq!(info.unconfirmed_balance, -1000);
+        let litecoin = BlockbookInstance::by_coin_type(2).unwrap().coin();
+        let balance = info.to_balance(&litecoin);
+        assert_eq!(balance.confirmed.to_string(), "250000000000 LTC");
+        assert_eq!(balance.unconfirmed.to_string(), "-0.00001 LTC");
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 3);
+        assert_eq!(rpc.txids(ADDRESS).await.unwrap(), vec!["c", "b", "a"]);
+
+        let xpub = rpc.xpub_info(XPUB).await.unwrap();
+        assert_eq!(xpub.balance, 1500);
+        assert_eq!(xpub.used_tokens, 2);
+        assert_eq!(xpub.addresses[1].path, "m/84'/2'/0'/1/0");
+
+        let utxo = rpc.utxo(XPUB).await.unwrap();
+        assert_eq!(utxo[0].address.as_deref(), Some("ltc1qchange"));
+
+        let tx = rpc.transaction("a").await.unwrap();
+        assert_eq!(tx.fees, 100);
+        assert_eq!(tx.vin[0].value, Some(2500));
+        assert!(matches!(
+            rpc.transaction("unknown").await,
+            Err(HttpError::UnexpectedStatus(StatusCode::BAD_REQUEST))
+        ));
+    }
+}
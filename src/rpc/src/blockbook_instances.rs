@@ -0,0 +1,108 @@
+use crate::amount::Coin;
+use std::borrow::Cow;
+use std::env;
+
+/// All the Blockbook UTXO coins have 8 decimals, e.g. 1 LTC = 10^8 litoshis.
+const UTXO_DECIMALS: u8 = 8;
+
+/// A Blockbook instance of a UTXO coin.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockbookInstance {
+    /// The `trustwallet/wallet-core` coin type.
+    pub coin_type: u32,
+    /// The display symbol of the coin, e.g. `LTC`.
+    pub symbol: &'static str,
+    /// The default instance URL, e.g. `https://ltc1.trezor.io`.
+    pub url: &'static str,
+    /// The environment variable to override the instance URL with, e.g. to use a self-hosted Blockbook.
+    pub url_env: &'static str,
+}
+
+impl BlockbookInstance {
+    /// Returns the instance by the `trustwallet/wallet-core` coin type.
+    pub fn by_coin_type(coin_type: u32) -> Option<&'static BlockbookInstance> {
+        INSTANCES
+            .iter()
+            .find(|instance| instance.coin_type == coin_type)
+    }
+
+    pub fn all() -> &'static [BlockbookInstance] { INSTANCES }
+
+    /// Returns the units of the coin, with the base unit named `sat` for all the coins.
+    pub fn coin(&self) -> Coin {
+        Coin {
+            symbol: Cow::Borrowed(self.symbol),
+            base_unit: Cow::Borrowed("sat"),
+            decimals: UTXO_DECIMALS,
+            sub_units: &[],
+        }
+    }
+
+    /// Returns the URL from the `url_env` environment variable if it's set, otherwise the default `url`.
+    pub fn configured_url(&self) -> String {
+        env::var(self.url_env).unwrap_or_else(|_| self.url.to_string())
+    }
+}
+
+const INSTANCES: &[BlockbookInstance] = &[
+    BlockbookInstance {
+        coin_type: 2,
+        symbol: "LTC",
+        url: "https://ltc1.trezor.io",
+        url_env: "LITECOIN_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 3,
+        symbol: "DOGE",
+        url: "https://doge1.trezor.io",
+        url_env: "DOGECOIN_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 5,
+        symbol: "DASH",
+        url: "https://dash1.trezor.io",
+        url_env: "DASH_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 20,
+        symbol: "DGB",
+        url: "https://dgb1.trezor.io",
+        url_env: "DIGIBYTE_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 145,
+        symbol: "BCH",
+        url: "https://bch1.trezor.io",
+        url_env: "BITCOINCASH_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 133,
+        symbol: "ZEC",
+        url: "https://zec1.trezor.io",
+        url_env: "ZCASH_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 17,
+        symbol: "GRS",
+        url: "https://blockbook.groestlcoin.org",
+        url_env: "GROESTLCOIN_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 175,
+        symbol: "RVN",
+        url: "https://blockbook.ravencoin.org",
+        url_env: "RAVENCOIN_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 136,
+        symbol: "FIRO",
+        url: "https://blockbook.firo.org",
+        url_env: "FIRO_BLOCKBOOK_URL",
+    },
+    BlockbookInstance {
+        coin_type: 14,
+        symbol: "VIA",
+        url: "https://blockbook.viacoin.org",
+        url_env: "VIACOIN_BLOCKBOOK_URL",
+    },
+];
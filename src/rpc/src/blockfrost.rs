@@ -0,0 +1,276 @@
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, HttpTransportExt, Request, SharedTransport};
+use crate::serde_str::deserialize_from_str;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+const BLOCKFROST_URL: &str = "https://cardano-mainnet.blockfrost.io/api/v0";
+
+/// The header to pass the Blockfrost project ID in.
+const PROJECT_ID_HEADER: &str = "project_id";
+/// The maximum `count` of the Blockfrost list endpoints.
+const PAGE_COUNT: usize = 100;
+/// The unit of ADA in the `amount` lists, the other units are native assets.
+const LOVELACE_UNIT: &str = "lovelace";
+
+/// The smallest units of ADA, 1 ADA = 10^6 lovelace.
+pub type Lovelace = u64;
+
+/// A client of a Blockfrost-compatible Cardano API: https://docs.blockfrost.io
+#[derive(Clone)]
+pub struct BlockfrostRpc {
+    transport: SharedTransport,
+    url: String,
+    project_id: String,
+}
+
+impl BlockfrostRpc {
+    pub fn with_default_url(transport: SharedTransport, project_id: String) -> Self {
+        BlockfrostRpc::with_url(transport, project_id, BLOCKFROST_URL.to_string())
+            .expect("'BLOCKFROST_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(
+        transport: SharedTransport,
+        project_id: String,
+        url: String,
+    ) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(BlockfrostRpc {
+            transport,
+            url,
+            project_id,
+        })
+    }
+
+    /// Requests the ADA and native assets of the address.
+    /// Returns `None` if the address has never been used on chain.
+    pub async fn address(&self, address: &str) -> Result<Option<AddressInfo>, HttpError> {
+        self.get_json(&format!("/addresses/{address}")).await
+    }
+
+    /// Requests the stake account of the `stake1...` reward address.
+    /// Returns `None` if the stake key has never been used on chain.
+    pub async fn account(&self, stake_address: &str) -> Result<Option<StakeAccount>, HttpError> {
+        self.get_json(&format!("/accounts/{stake_address}")).await
+    }
+
+    /// Requests all addresses associated with the stake account page by page.
+    pub async fn account_addresses(&self, stake_address: &str) -> Result<Vec<String>, HttpError> {
+        let addresses: Vec<AccountAddress> = self
+            .get_all_pages(&format!("/accounts/{stake_address}/addresses"))
+            .await?;
+        Ok(addresses.into_iter().map(|a| a.address).collect())
+    }
+
+    /// Requests all transactions of the address page by page, oldest first.
+    pub async fn address_transactions(
+        &self,
+        address: &str,
+    ) -> Result<Vec<AddressTransaction>, HttpError> {
+        self.get_all_pages(&format!("/addresses/{address}/transactions"))
+            .await
+    }
+
+    /// Requests the pages until a page has less than `PAGE_COUNT` items.
+    /// Returns no items if the resource isn't found.
+    async fn get_all_pages<R>(&self, path: &str) -> Result<Vec<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let mut items = Vec::new();
+        // Blockfrost pages start from `1`.
+        for page in 1.. {
+            let path = format!("{path}?count={PAGE_COUNT}&page={page}");
+            let page: Vec<R> = match self.get_json(&path).await? {
+                Some(page) => page,
+                None => break,
+            };
+            let is_last_page = page.len() < PAGE_COUNT;
+            items.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Returns `None` if the resource isn't found.
+    async fn get_json<R>(&self, path: &str) -> Result<Option<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let request = Request::get(format!("{}{path}", self.url))
+            .header(PROJECT_ID_HEADER, &self.project_id)
+            .body(Vec::new())?;
+        self.transport.send_json_opt(request).await
+    }
+}
+
+/// https://docs.blockfrost.io/#tag/cardano--addresses/GET/addresses/{address}
+#[derive(Debug, Deserialize)]
+pub struct AddressInfo {
+    pub address: String,
+    /// ADA as `lovelace` and the native assets as `{policy_id}{asset_name_hex}` units.
+    pub amount: Vec<AssetAmount>,
+    /// `None` for the enterprise addresses without a stake part.
+    pub stake_address: Option<String>,
+}
+
+impl AddressInfo {
+    /// Returns the ADA balance.
+    pub fn ada(&self) -> Amount {
+        self.amount
+            .iter()
+            .find(|amount| amount.unit == LOVELACE_UNIT)
+            .map(AssetAmount::to_amount)
+            .unwrap_or_else(|| Amount::zero(Coin::ADA))
+    }
+
+    /// Returns the native assets held by the address.
+    pub fn native_assets(&self) -> impl Iterator<Item = &AssetAmount> {
+        self.amount
+            .iter()
+            .filter(|amount| amount.unit != LOVELACE_UNIT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetAmount {
+    pub unit: String,
+    /// The native assets quantities may exceed `u64`.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub quantity: u128,
+}
+
+impl AssetAmount {
+    /// Returns the quantity in ADA, or in the base units of a native asset, as their decimals are off-chain metadata.
+    pub fn to_amount(&self) -> Amount {
+        let coin = match self.unit.as_str() {
+            LOVELACE_UNIT => Coin::ADA,
+            unit => Coin::token(unit, 0),
+        };
+        Amount::from_base_units(self.quantity, coin)
+    }
+}
+
+/// https://docs.blockfrost.io/#tag/cardano--accounts/GET/accounts/{stake_address}
+#[derive(Debug, Deserialize)]
+pub struct StakeAccount {
+    pub stake_address: String,
+    /// Whether the stake key is registered.
+    pub active: bool,
+    /// The ADA of all addresses of the account, including the rewards.
+    #[serde(deserialize_with = "deserialize_lovelace")]
+    pub controlled_amount: Amount,
+    /// The rewards that are available to withdraw.
+    #[serde(deserialize_with = "deserialize_lovelace")]
+    pub withdrawable_amount: Amount,
+    /// The bech32 ID of the pool the account is delegated to, e.g. `pool1...`.
+    pub pool_id: Option<String>,
+}
+
+impl StakeAccount {
+    /// Returns the pool ID if the stake key is registered and delegated.
+    pub fn delegated_pool(&self) -> Option<&str> { self.pool_id.as_deref().filter(|_| self.active) }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressTransaction {
+    pub tx_hash: String,
+    pub tx_index: u32,
+    pub block_height: u64,
+    pub block_time: u64,
+}
+
+#[derive(Deserialize)]
+struct AccountAddress {
+    address: String,
+}
+
+fn deserialize_lovelace<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let lovelace: Lovelace = deserialize_from_str(deserializer)?;
+    Ok(Amount::from_base_units(lovelace, Coin::ADA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "addr1q8043m5heeaydnvtmmkyuhe6qv5havvhsf0d26q3jygsspxlyfpyk6yqkw0yhtyvtr0flekj84u64az82cufmqn65zdsylzk23";
+    const STAKE_ADDRESS: &str = "stake1u80jysjtdzqt88ztkgk9350lumfr6lx4v8swuyasl2pxcyqn3jf7g";
+    const UNUSED: &str = "addr1qxxj7d5vlpn2ewvuq6lzwvf9xwd0xcvh6vwuqxf0kxjhdg6pjfm6e2r6ugvxms0vrfl7at5txsfhsjc4vz7gxzwt04tsj4m2nk";
+
+    fn transactions_page(range: std::ops::Range<usize>) -> String {
+        let txs: Vec<_> = range
+            .map(|i| {
+                format!(r#"{{"tx_hash":"{i}","tx_index":0,"block_height":{i},"block_time":{i}}}"#)
+            })
+            .collect();
+        format!("[{}]", txs.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_blockfrost_rpc_stub() {
+        let transactions_path = |page: usize| {
+            format!("/api/v0/addresses/{ADDRESS}/transactions?count={PAGE_COUNT}&page={page}")
+        };
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/api/v0/addresses/{ADDRESS}"),
+                &format!(
+                    r#"{{"address":"{ADDRESS}","amount":[{{"unit":"lovelace","quantity":"42000000"}},
+                    {{"unit":"b0d07d45fe9514f80213f4020e5a61241458be626841cde717cb38a74e75747300000000000000000000000000","quantity":"100000000000000000000"}}],
+                    "stake_address":"{STAKE_ADDRESS}","type":"shelley","script":false}}"#
+                ),
+            )
+            .with_response(
+                &format!("/api/v0/accounts/{STAKE_ADDRESS}"),
+                &format!(
+                    r#"{{"stake_address":"{STAKE_ADDRESS}","active":true,"active_epoch":412,
+                    "controlled_amount":"42500000","rewards_sum":"500000","withdrawals_sum":"0",
+                    "reserves_sum":"0","treasury_sum":"0","withdrawable_amount":"500000",
+                    "pool_id":"pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy"}}"#
+                ),
+            )
+            .with_response(
+                &format!("/api/v0/accounts/{STAKE_ADDRESS}/addresses?count={PAGE_COUNT}&page=1"),
+                &format!(r#"[{{"address":"{ADDRESS}"}}]"#),
+            )
+            .with_response(&transactions_path(1), &transactions_page(0..PAGE_COUNT))
+            .with_response(&transactions_path(2), &transactions_page(PAGE_COUNT..120))
+            .with_status(&format!("/api/v0/addresses/{UNUSED}"), StatusCode::NOT_FOUND)
+            .with_status(
+                &format!("/api/v0/addresses/{UNUSED}/transactions?count={PAGE_COUNT}&page=1"),
+                StatusCode::NOT_FOUND,
+            );
+        let rpc = BlockfrostRpc::with_default_url(Arc::new(transport), "mainnet".to_string());
+
+        let info = rpc.address(ADDRESS).await.unwrap().unwrap();
+        assert_eq!(info.ada().to_string(), "42 ADA");
+        assert_eq!(info.native_assets().count(), 1);
+        assert_eq!(info.stake_address.as_deref(), Some(STAKE_ADDRESS));
+
+        let account = rpc.account(STAKE_ADDRESS).await.unwrap().unwrap();
+        assert_eq!(account.withdrawable_amount.to_string(), "0.5 ADA");
+        assert!(account.delegated_pool().is_some());
+        assert_eq!(
+            rpc.account_addresses(STAKE_ADDRESS).await.unwrap(),
+            [ADDRESS]
+        );
+
+        assert_eq!(rpc.address_transactions(ADDRESS).await.unwrap().len(), 120);
+
+        assert!(rpc.address(UNUSED).await.unwrap().is_none());
+        assert!(rpc.address_transactions(UNUSED).await.unwrap().is_empty());
+    }
+}
@@ -1,76 +1,301 @@
-use crate::http::{HttpError, HttpTransport};
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
 use http::uri::InvalidUri;
-use http::Response;
 use hyper::Uri;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
-pub const BLOCKSTREAM_URL: &str = "https://blockstream.info";
+/// The base URLs of the Esplora API instances.
+/// Self-hosted `electrs` usually serves the API at the root, e.g. `http://localhost:3000`.
+pub const BLOCKSTREAM_URL: &str = "https://blockstream.info/api";
+pub const MEMPOOL_SPACE_URL: &str = "https://mempool.space/api";
 
 /// https://github.com/bitcoin/bitcoin/blob/master/src/consensus/amount.h#L11-L12
-#[allow(dead_code)]
 pub type Satoshis = i64;
 
-pub struct BlockstreamRpc<'a, T> {
-    transport: &'a T,
+/// Esplora returns up to 25 confirmed transactions per page.
+/// https://github.com/Blockstream/esplora/blob/master/API.md#get-addressaddresstxschainlast_seen_txid
+const CHAIN_TXS_PAGE_SIZE: usize = 25;
+
+/// An Esplora API client.
+/// Works against `blockstream.info`, `mempool.space` and self-hosted `electrs` instances.
+///
+/// https://github.com/Blockstream/esplora/blob/master/API.md
+#[derive(Clone)]
+pub struct BlockstreamRpc {
+    transport: SharedTransport,
     url: String,
 }
 
-impl<'a, T> BlockstreamRpc<'a, T>
-where
-    T: HttpTransport + Sync,
-{
-    pub fn with_default_url(transport: &'a T) -> Self {
+impl BlockstreamRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
         BlockstreamRpc::with_url(transport, BLOCKSTREAM_URL.to_string())
             .expect("'BLOCKSTREAM_URL' is expected to be a valid URL")
     }
 
-    pub fn with_url(transport: &'a T, url: String) -> Result<Self, InvalidUri> {
+    /// Creates a client with the given Esplora API base `url`, e.g. [`MEMPOOL_SPACE_URL`].
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
         // Check if the given `url` is correct.
         url.parse::<Uri>()?;
         Ok(BlockstreamRpc { transport, url })
     }
 
-    /// Requests the count of the address transactions.
+    /// Requests the count of the address transactions, including the mempool ones.
     pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
         self.address_info(address)
             .await
-            .map(|addr_info| addr_info.chain_stats.tx_count)
+            .map(|addr_info| addr_info.chain_stats.tx_count + addr_info.mempool_stats.tx_count)
+    }
+
+    /// Requests the confirmed and unconfirmed balances of the address.
+    pub async fn balance(&self, address: &str) -> Result<Balance, HttpError> {
+        self.address_info(address).await.map(|addr_info| Balance {
+            confirmed: addr_info.chain_stats.balance(),
+            unconfirmed: addr_info.mempool_stats.balance(),
+        })
     }
 
     /// Requests the the address info.
     pub async fn address_info(&self, address: &str) -> Result<AddressInfo, HttpError> {
-        let uri = format!("{}/api/address/{address}", self.url).parse()?;
-        let res: Response<AddressInfo> = self.transport.get_json(uri).await?;
+        self.get_json(&format!("/address/{address}")).await
+    }
+
+    /// Requests the unspent outputs of the address, including the mempool ones.
+    pub async fn utxo(&self, address: &str) -> Result<Vec<Utxo>, HttpError> {
+        self.get_json(&format!("/address/{address}/utxo")).await
+    }
+
+    /// Requests a page of the confirmed address transactions, newest first.
+    /// Pass the last `txid` of the previous page as `last_seen` to request the next page.
+    pub async fn chain_transactions_page(
+        &self,
+        address: &str,
+        last_seen: Option<&str>,
+    ) -> Result<Vec<Transaction>, HttpError> {
+        let path = match last_seen {
+            Some(last_seen) => format!("/address/{address}/txs/chain/{last_seen}"),
+            None => format!("/address/{address}/txs/chain"),
+        };
+        self.get_json(&path).await
+    }
+
+    /// Requests all confirmed address transactions page by page, newest first.
+    pub async fn chain_transactions(&self, address: &str) -> Result<Vec<Transaction>, HttpError> {
+        let mut txs: Vec<Transaction> = Vec::new();
+        loop {
+            let last_seen = txs.last().map(|tx| tx.txid.as_str());
+            let page = self.chain_transactions_page(address, last_seen).await?;
+
+            let is_last_page = page.len() < CHAIN_TXS_PAGE_SIZE;
+            txs.extend(page);
+            if is_last_page {
+                return Ok(txs);
+            }
+        }
+    }
+
+    /// Requests the transaction by its `txid`.
+    pub async fn transaction(&self, txid: &str) -> Result<Transaction, HttpError> {
+        self.get_json(&format!("/tx/{txid}")).await
+    }
 
-        // TODO check if the response is OK(200).
-        let (_parts, address_info) = res.into_parts();
-        Ok(address_info)
+    /// Requests the fee estimates: a confirmation target (in blocks) to a fee rate (in sat/vB).
+    pub async fn fee_estimates(&self) -> Result<BTreeMap<u16, f64>, HttpError> {
+        self.get_json("/fee-estimates").await
+    }
+
+    /// Requests the height of the last block.
+    pub async fn tip_height(&self) -> Result<u64, HttpError> {
+        let uri = format!("{}/blocks/tip/height", self.url).parse()?;
+        let res = self.transport.get(uri).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        String::from_utf8_lossy(&body)
+            .trim()
+            .parse()
+            .map_err(|e| HttpError::InvalidPayload(format!("Invalid block height: {e}")))
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let uri = format!("{}{path}", self.url).parse()?;
+        // Check the status before parsing the payload, as Esplora responds with a plain text on errors.
+        let res = self.transport.get(uri).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct AddressInfo {
-    #[allow(dead_code)]
-    address: String,
-    chain_stats: ChainStats,
+    pub address: String,
+    pub chain_stats: AddressStats,
+    pub mempool_stats: AddressStats,
+}
+
+/// Either the confirmed (`chain_stats`) or the unconfirmed (`mempool_stats`) address statistics.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct AddressStats {
+    pub funded_txo_count: usize,
+    pub funded_txo_sum: Satoshis,
+    pub spent_txo_count: usize,
+    pub spent_txo_sum: Satoshis,
+    pub tx_count: usize,
+}
+
+impl AddressStats {
+    /// Returns `funded - spent`.
+    /// Note the unconfirmed balance can be negative if the mempool transactions spend confirmed outputs.
+    pub fn balance(&self) -> Amount {
+        let balance = self.funded_txo_sum - self.spent_txo_sum;
+        Amount::from_signed_base_units(i128::from(balance), Coin::BTC)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Balance {
+    pub confirmed: Amount,
+    pub unconfirmed: Amount,
+}
+
+#[derive(Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    pub value: Satoshis,
+    pub status: TxStatus,
+}
+
+#[derive(Deserialize)]
+pub struct Transaction {
+    pub txid: String,
+    pub version: u32,
+    pub locktime: u32,
+    pub vin: Vec<TxInput>,
+    pub vout: Vec<TxOutput>,
+    pub size: usize,
+    pub weight: usize,
+    pub fee: Satoshis,
+    pub status: TxStatus,
+}
+
+#[derive(Deserialize)]
+pub struct TxInput {
+    pub txid: String,
+    pub vout: u32,
+    /// The output being spent. Absent for coinbase inputs.
+    pub prevout: Option<TxOutput>,
+    pub is_coinbase: bool,
+    pub sequence: u32,
+}
+
+#[derive(Deserialize)]
+pub struct TxOutput {
+    pub scriptpubkey: String,
+    pub scriptpubkey_type: String,
+    /// Absent for the outputs that don't have an address, e.g. `OP_RETURN`.
+    pub scriptpubkey_address: Option<String>,
+    pub value: Satoshis,
 }
 
-/// Currently, we're interested in the address's balance and TX count only.
 #[derive(Deserialize)]
-pub struct ChainStats {
-    #[allow(dead_code)]
-    funded_txo_sum: Satoshis,
-    tx_count: usize,
+pub struct TxStatus {
+    pub confirmed: bool,
+    pub block_height: Option<u64>,
+    pub block_hash: Option<String>,
+    pub block_time: Option<u64>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::StubTransport;
+    use http::StatusCode;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "bc1qpjult34k9spjfym8hss2jrwjgf0xjf40ze0pp8";
+
+    fn stub_tx(txid: &str) -> String {
+        format!(
+            r#"{{"txid":"{txid}","version":2,"locktime":0,"size":222,"weight":561,"fee":1410,
+            "vin":[{{"txid":"aa","vout":1,"is_coinbase":false,"sequence":4294967293,
+                "prevout":{{"scriptpubkey":"0014","scriptpubkey_type":"v0_p2wpkh","scriptpubkey_address":"{ADDRESS}","value":20000}}}}],
+            "vout":[{{"scriptpubkey":"6a","scriptpubkey_type":"op_return","value":0}}],
+            "status":{{"confirmed":true,"block_height":768000,"block_hash":"00","block_time":1671000000}}}}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_blockstream_rpc_stub() {
+        let full_page: Vec<_> = (0..CHAIN_TXS_PAGE_SIZE)
+            .map(|i| stub_tx(&format!("tx{i}")))
+            .collect();
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/api/address/{ADDRESS}"),
+                &format!(
+                    r#"{{"address":"{ADDRESS}",
+                    "chain_stats":{{"funded_txo_count":2,"funded_txo_sum":30000,"spent_txo_count":1,"spent_txo_sum":20000,"tx_count":2}},
+                    "mempool_stats":{{"funded_txo_count":0,"funded_txo_sum":0,"spent_txo_count":1,"spent_txo_sum":10000,"tx_count":1}}}}"#
+                ),
+            )
+            .with_response(
+                &format!("/api/address/{ADDRESS}/txs/chain"),
+                &format!("[{}]", full_page.join(",")),
+            )
+            .with_response(
+                &format!("/api/address/{ADDRESS}/txs/chain/tx24"),
+                &format!("[{}]", stub_tx("tx25")),
+            )
+            .with_response("/api/fee-estimates", r#"{"1":87.882,"144":1.027}"#)
+            .with_response("/api/blocks/tip/height", "768012")
+            .with_status("/api/tx/unknown", StatusCode::NOT_FOUND);
+        let rpc = BlockstreamRpc::with_default_url(Arc::new(transport));
+
+        let balance = rpc.balance(ADDRESS).await.unwrap();
+        assert_eq!(
+            balance,
+            Balance {
+                confirmed: Amount::from_signed_base_units(10000, Coin::BTC),
+                unconfirmed: Amount::from_signed_base_units(-10000, Coin::BTC),
+            }
+        );
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 3);
+
+        // The client owns the shared transport, so its clone can be moved into another task.
+        let spawned_rpc = rpc.clone();
+        let tx_count = tokio::spawn(async move { spawned_rpc.transaction_count(ADDRESS).await })
+            .await
+            .unwrap();
+        assert_eq!(tx_count.unwrap(), 3);
+
+        let txs = rpc.chain_transactions(ADDRESS).await.unwrap();
+        assert_eq!(txs.len(), CHAIN_TXS_PAGE_SIZE + 1);
+        assert_eq!(txs[CHAIN_TXS_PAGE_SIZE].txid, "tx25");
+        assert_eq!(txs[0].vin[0].prevout.as_ref().unwrap().value, 20000);
+        assert_eq!(txs[0].vout[0].scriptpubkey_address, None);
+
+        let fee_estimates = rpc.fee_estimates().await.unwrap();
+        assert_eq!(fee_estimates[&144], 1.027);
+        assert_eq!(rpc.tip_height().await.unwrap(), 768012);
+
+        let err = rpc.transaction("unknown").await.err().unwrap();
+        assert!(matches!(
+            err,
+            HttpError::UnexpectedStatus(StatusCode::NOT_FOUND)
+        ));
+    }
 
     #[tokio::test]
     async fn test_blockstream_rpc() {
-        let transport = crate::http::HttpBuilder::build();
-        let rpc = BlockstreamRpc::with_default_url(&transport);
+        let transport = crate::http::HttpBuilder::build(Default::default());
+        let rpc = BlockstreamRpc::with_default_url(transport);
 
         let res = rpc
             .address_info("bc1qpjult34k9spjfym8hss2jrwjgf0xjf40ze0pp8")
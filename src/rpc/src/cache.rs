@@ -0,0 +1,385 @@
+//! Caches the `GET` responses on disk, so that a re-run doesn't send the same requests again,
+//! and can be run offline from the cache only.
+
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::http::{
+    HttpError, HttpTransport, Method, Request, Response, SharedTransport, StatusCode, Uri,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+/// The time a response is fresh for, unless its `Cache-Control` sets another one.
+const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+/// Tells the temporary files of the entries being written apart.
+static TMP_FILE_ID: AtomicU64 = AtomicU64::new(0);
+/// The query parameters the API keys are passed in, stripped from the cache keys.
+const API_KEY_PARAMS: [&str; 6] = [
+    "apikey",
+    "api_key",
+    "api-key",
+    "key",
+    "token",
+    "access_token",
+];
+
+/// An `HttpTransport` that stores the successful `GET` responses under the `dir`,
+/// and answers with the stored ones while they are fresh. The other requests are sent as they are.
+///
+/// A response is fresh for the TTL of its endpoint if set by [`CachingTransport::with_ttl`],
+/// otherwise for the `Cache-Control` `max-age`, otherwise for the default TTL.
+/// The `no-store` responses are not stored, and the `no-cache` ones are revalidated each time.
+/// Neither are the responses their [`CacheCheck`] rejects, e.g. the API errors answered with `200 OK`.
+/// A stale response with an `ETag` is revalidated with an `If-None-Match` request.
+///
+/// The responses are stored by the URI with the API keys stripped, so the keys don't end up on disk.
+pub struct CachingTransport {
+    inner: SharedTransport,
+    dir: PathBuf,
+    default_ttl: Duration,
+    /// The TTLs by the URI prefix, overriding the `Cache-Control` of the responses.
+    ttls: Vec<(String, Duration)>,
+    /// Whether to answer from the cache only, with the stale responses too.
+    offline: bool,
+}
+
+/// A request extension that tells the [`CachingTransport`] whether a `200 OK` response `body` may be stored,
+/// set by the clients of the APIs that answer the errors with `200 OK`, e.g. the Etherscan rate limits.
+/// The responses of the requests without it are stored.
+#[derive(Clone, Copy)]
+pub struct CacheCheck(pub fn(&[u8]) -> bool);
+
+/// A stored response.
+#[derive(Deserialize, Serialize)]
+struct CacheEntry {
+    /// The cache key, to tell the entries apart when looking into the directory.
+    uri: String,
+    etag: Option<String>,
+    /// The Unix time in seconds the response is fresh until.
+    fresh_until: u64,
+    /// The base64-encoded response body.
+    body: String,
+}
+
+impl CachingTransport {
+    pub fn new(inner: SharedTransport, dir: PathBuf) -> Self {
+        CachingTransport {
+            inner,
+            dir,
+            default_ttl: DEFAULT_TTL,
+            ttls: Vec::new(),
+            offline: false,
+        }
+    }
+
+    /// Sets the time the responses without a `Cache-Control` `max-age` are fresh for.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Sets the time the responses of the URIs starting with the `prefix` are fresh for,
+    /// e.g. `with_ttl("https://blockstream.info/api", Duration::from_secs(3600))`.
+    pub fn with_ttl(mut self, prefix: &str, ttl: Duration) -> Self {
+        self.ttls.push((prefix.to_string(), ttl));
+        self
+    }
+
+    /// Answers from the cache only, failing with [`HttpError::NotCached`] on the other requests.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(key.as_bytes()));
+        self.dir.join(name).with_extension("json")
+    }
+
+    /// Returns the time the response of the `key` is fresh for, or `None` if it shouldn't be stored.
+    fn ttl(&self, key: &str, headers: &HeaderMap) -> Option<Duration> {
+        if let Some((_, ttl)) = self.ttls.iter().find(|(prefix, _)| key.starts_with(prefix)) {
+            return Some(*ttl);
+        }
+        let cache_control = headers
+            .get(header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let mut ttl = self.default_ttl;
+        for directive in cache_control
+            .split(',')
+            .map(|d| d.trim().to_ascii_lowercase())
+        {
+            match directive.as_str() {
+                "no-store" => return None,
+                "no-cache" => return Some(Duration::ZERO),
+                _ => {
+                    if let Some(Ok(max_age)) = directive.strip_prefix("max-age=").map(str::parse) {
+                        ttl = Duration::from_secs(max_age);
+                    }
+                }
+            }
+        }
+        Some(ttl)
+    }
+
+    fn read(&self, key: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let entry: CacheEntry =
+            serde_json::from_slice(&fs::read(self.entry_path(key)).ok()?).ok()?;
+        // A corrupted entry is a miss.
+        let body = base64::decode(&entry.body).ok()?;
+        Some((entry, body))
+    }
+
+    fn write(&self, key: &str, entry: &CacheEntry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(key);
+        // Write to a temporary file first, so that a concurrent read doesn't see a partial entry.
+        // The name is unique, so that the concurrent writers of the same entry don't mix their writes.
+        let id = TMP_FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = path.with_extension(format!("{}.{id}.tmp", std::process::id()));
+        fs::write(&tmp_path, serde_json::to_vec(entry)?)?;
+        fs::rename(tmp_path, path)
+    }
+
+    /// Stores the response `body`, if it should be stored. The cache is best-effort,
+    /// so a failure to store doesn't fail the request.
+    fn store(&self, key: &str, headers: &HeaderMap, etag: Option<String>, body: &[u8]) {
+        if let Some(ttl) = self.ttl(key, headers) {
+            let entry = CacheEntry {
+                uri: key.to_string(),
+                etag,
+                fresh_until: unix_time() + ttl.as_secs(),
+                body: base64::encode(body),
+            };
+            let _ = self.write(key, &entry);
+        }
+    }
+}
+
+/// Returns the `uri` without the query parameters of the API keys, e.g. `/api?module=account&apikey=KEY`
+/// is `/api?module=account`.
+fn cache_key(uri: &Uri) -> String {
+    let uri = uri.to_string();
+    let Some((base, query)) = uri.split_once('?') else {
+        return uri;
+    };
+    let params: Vec<_> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !API_KEY_PARAMS.contains(&name.to_ascii_lowercase().as_str())
+        })
+        .collect();
+    if params.is_empty() {
+        base.to_string()
+    } else {
+        format!("{base}?{}", params.join("&"))
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+fn cached_response(etag: Option<String>, body: Vec<u8>) -> Result<Response<Vec<u8>>, HttpError> {
+    let mut response = Response::builder().status(StatusCode::OK);
+    if let Some(etag) = etag {
+        response = response.header(header::ETAG, etag);
+    }
+    Ok(response.body(body)?)
+}
+
+#[async_trait]
+impl HttpTransport for CachingTransport {
+    async fn send(&self, mut request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        let key = cache_key(request.uri());
+        if request.method() != Method::GET {
+            if self.offline {
+                return Err(HttpError::NotCached(key));
+            }
+            return self.inner.send(request).await;
+        }
+
+        let check = request.extensions().get::<CacheCheck>().copied();
+        let cached = self.read(&key);
+        match cached {
+            Some((entry, body)) if self.offline || entry.fresh_until > unix_time() => {
+                return cached_response(entry.etag, body);
+            }
+            None if self.offline => return Err(HttpError::NotCached(key)),
+            _ => (),
+        }
+
+        let etag = cached.as_ref().and_then(|(entry, _)| entry.etag.as_deref());
+        if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(etag).ok()) {
+            request.headers_mut().insert(header::IF_NONE_MATCH, etag);
+        }
+        let response = self.inner.send(request).await?;
+        match (response.status(), cached) {
+            // The stale response is still valid, so it's fresh again.
+            (StatusCode::NOT_MODIFIED, Some((entry, body))) => {
+                self.store(&key, response.headers(), entry.etag.clone(), &body);
+                cached_response(entry.etag, body)
+            }
+            (StatusCode::OK, _) if check.is_none_or(|CacheCheck(check)| check(response.body())) => {
+                let etag = response
+                    .headers()
+                    .get(header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(str::to_string);
+                self.store(&key, response.headers(), etag, response.body());
+                Ok(response)
+            }
+            _ => Ok(response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    /// Answers with the `Cache-Control` and the `ETag` headers, and with `304` if the `ETag` matches.
+    struct EtagTransport {
+        cache_control: &'static str,
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpTransport for EtagTransport {
+        async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let status = match request.headers().get(header::IF_NONE_MATCH) {
+                Some(etag) if etag == "\"v1\"" => StatusCode::NOT_MODIFIED,
+                _ => StatusCode::OK,
+            };
+            let response = Response::builder()
+                .status(status)
+                .header(header::CACHE_CONTROL, self.cache_control)
+                .header(header::ETAG, "\"v1\"")
+                .body(request.uri().path().as_bytes().to_vec())?;
+            Ok(response)
+        }
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rpc-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let uri = Uri::from_static("https://api.example/api?module=account&apikey=KEY&address=0x1");
+        assert_eq!(
+            cache_key(&uri),
+            "https://api.example/api?module=account&address=0x1"
+        );
+        let uri = Uri::from_static("https://api.example/api?API_KEY=KEY");
+        assert_eq!(cache_key(&uri), "https://api.example/api");
+        let uri = Uri::from_static("https://api.example/address/bc1q");
+        assert_eq!(cache_key(&uri), "https://api.example/address/bc1q");
+    }
+
+    #[tokio::test]
+    async fn test_caching_transport() {
+        let inner = Arc::new(EtagTransport {
+            cache_control: "public, max-age=60",
+            requests: AtomicUsize::default(),
+        });
+        let dir = cache_dir("fresh");
+        let uri = Uri::from_static("https://api.example/height?apikey=KEY");
+        let requests = || inner.requests.load(Ordering::SeqCst);
+
+        // The stale response is revalidated by its `ETag`.
+        let transport = CachingTransport::new(inner.clone(), dir.clone())
+            .with_ttl("https://api.example", Duration::ZERO);
+        assert_eq!(
+            transport.get(uri.clone()).await.unwrap().into_body(),
+            b"/height"
+        );
+        assert_eq!(
+            transport.get(uri.clone()).await.unwrap().into_body(),
+            b"/height"
+        );
+        assert_eq!(requests(), 2);
+
+        // The revalidated response is fresh for the `max-age`.
+        let transport = CachingTransport::new(inner.clone(), dir.clone());
+        assert_eq!(
+            transport.get(uri.clone()).await.unwrap().into_body(),
+            b"/height"
+        );
+        assert_eq!(
+            transport.get(uri.clone()).await.unwrap().into_body(),
+            b"/height"
+        );
+        assert_eq!(requests(), 3);
+
+        // The API key isn't stored.
+        let entry = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        let entry = fs::read_to_string(entry).unwrap();
+        assert!(entry.contains("https://api.example/height") && !entry.contains("KEY"));
+
+        // The offline transport answers with the stale responses too, and fails on the others.
+        let transport = CachingTransport::new(inner.clone(), dir.clone())
+            .with_ttl("https://api.example", Duration::ZERO)
+            .offline();
+        assert_eq!(transport.get(uri).await.unwrap().into_body(), b"/height");
+        let uri = Uri::from_static("https://api.example/tx");
+        assert!(matches!(
+            transport.get(uri).await,
+            Err(HttpError::NotCached(_))
+        ));
+        assert_eq!(requests(), 3);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cache_check() {
+        let inner = Arc::new(EtagTransport {
+            cache_control: "public, max-age=60",
+            requests: AtomicUsize::default(),
+        });
+        let dir = cache_dir("check");
+        let transport = CachingTransport::new(inner.clone(), dir.clone());
+        let request = |check: fn(&[u8]) -> bool| {
+            let mut request = Request::get("https://api.example/height")
+                .body(Vec::new())
+                .unwrap();
+            request.extensions_mut().insert(CacheCheck(check));
+            request
+        };
+        transport.send(request(|_| false)).await.unwrap();
+        assert!(!dir.exists());
+        transport.send(request(|_| true)).await.unwrap();
+        transport.send(request(|_| true)).await.unwrap();
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_no_store() {
+        let inner = Arc::new(EtagTransport {
+            cache_control: "no-store",
+            requests: AtomicUsize::default(),
+        });
+        let dir = cache_dir("no-store");
+        let transport = CachingTransport::new(inner.clone(), dir.clone());
+        let uri = Uri::from_static("https://api.example/height");
+        transport.get(uri.clone()).await.unwrap();
+        transport.get(uri).await.unwrap();
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+        assert!(!dir.exists());
+    }
+}
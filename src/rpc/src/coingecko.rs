@@ -0,0 +1,212 @@
+use crate::amount::Amount;
+use crate::http::{HttpError, HttpTransportExt, Request, SharedTransport};
+use crate::price::{self, PriceDate, PriceSource, PricesJson, TokenContract, TokenInfo};
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+const COINGECKO_URL: &str = "https://api.coingecko.com/api/v3";
+/// The header to pass the CoinGecko Demo API key in. Requests without a key are rate-limited harder.
+const API_KEY_HEADER: &str = "x-cg-demo-api-key";
+
+/// A client of the CoinGecko-compatible prices API: https://docs.coingecko.com/v3.0.1/reference/introduction
+#[derive(Clone)]
+pub struct CoinGeckoRpc {
+    transport: SharedTransport,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl CoinGeckoRpc {
+    pub fn with_default_url(transport: SharedTransport, api_key: Option<String>) -> Self {
+        CoinGeckoRpc::with_url(transport, api_key, COINGECKO_URL.to_string())
+            .expect("'COINGECKO_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(
+        transport: SharedTransport,
+        api_key: Option<String>,
+        url: String,
+    ) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(CoinGeckoRpc {
+            transport,
+            url,
+            api_key,
+        })
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let res = self.transport.send(self.get_request(path)?).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+
+    fn get_request(&self, path: &str) -> Result<Request<Vec<u8>>, HttpError> {
+        let mut request = Request::get(format!("{}{path}", self.url));
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        Ok(request.body(Vec::new())?)
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoRpc {
+    /// https://docs.coingecko.com/v3.0.1/reference/simple-price
+    async fn prices(
+        &self,
+        ids: &[&str],
+        currency: &str,
+    ) -> Result<BTreeMap<String, Amount>, HttpError> {
+        let currency = currency.to_lowercase();
+        let res: PricesJson = self
+            .get_json(&format!(
+                "/simple/price?ids={}&vs_currencies={currency}",
+                ids.join(",")
+            ))
+            .await?;
+        let prices = res
+            .into_prices()
+            .map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        Ok(prices
+            .into_iter()
+            .filter_map(|(id, mut prices)| Some((id, prices.remove(&currency)?)))
+            .collect())
+    }
+
+    /// https://docs.coingecko.com/v3.0.1/reference/coins-id-history
+    async fn historical_price(
+        &self,
+        id: &str,
+        currency: &str,
+        date: PriceDate,
+    ) -> Result<Option<Amount>, HttpError> {
+        let currency = currency.to_lowercase();
+        // The history endpoint expects the `DD-MM-YYYY` date format.
+        let PriceDate { year, month, day } = date;
+        let res: CoinHistory = self
+            .get_json(&format!(
+                "/coins/{id}/history?date={day:02}-{month:02}-{year:04}&localization=false"
+            ))
+            .await?;
+        let price = res
+            .market_data
+            .and_then(|data| data.current_price.get(&currency).copied());
+        price
+            .map(|price| price::parse_price(price, &currency))
+            .transpose()
+            .map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+
+    /// https://docs.coingecko.com/v3.0.1/reference/coins-contract-address
+    /// Responds with `404` if the contract isn't listed on the platform.
+    async fn token_info(&self, token: &TokenContract) -> Result<Option<TokenInfo>, HttpError> {
+        let TokenContract { platform, address } = token;
+        let request = self.get_request(&format!("/coins/{platform}/contract/{address}"))?;
+        let res: Option<ContractCoin> = self.transport.send_json_opt(request).await?;
+        Ok(res.map(|mut coin| TokenInfo {
+            decimals: coin
+                .detail_platforms
+                .remove(platform)
+                .and_then(|detail| detail.decimal_place),
+            id: coin.id,
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+struct ContractCoin {
+    id: String,
+    #[serde(default)]
+    detail_platforms: BTreeMap<String, PlatformDetail>,
+}
+
+#[derive(Deserialize)]
+struct PlatformDetail {
+    /// Absent for some tokens.
+    decimal_place: Option<u8>,
+}
+
+#[derive(Deserialize)]
+struct CoinHistory {
+    /// Absent if there is no market data of the coin on the date.
+    market_data: Option<MarketData>,
+}
+
+#[derive(Deserialize)]
+struct MarketData {
+    current_price: BTreeMap<String, f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_coingecko_rpc_stub() {
+        let transport = StubTransport::default()
+            .with_response(
+                "/api/v3/simple/price?ids=bitcoin,ethereum,unknown&vs_currencies=eur",
+                r#"{"bitcoin":{"eur":60123.45},"ethereum":{"eur":3000}}"#,
+            )
+            .with_response(
+                "/api/v3/coins/bitcoin/history?date=01-01-2024&localization=false",
+                r#"{"id":"bitcoin","symbol":"btc","market_data":{"current_price":{"eur":38271.35,"usd":42280.23}}}"#,
+            )
+            .with_response(
+                "/api/v3/coins/bitcoin/history?date=01-01-2009&localization=false",
+                r#"{"id":"bitcoin","symbol":"btc"}"#,
+            )
+            .with_response(
+                "/api/v3/coins/ethereum/contract/0xdac17f958d2ee523a2206206994597c13d831ec7",
+                r#"{"id":"tether","symbol":"usdt","detail_platforms":{"ethereum":
+                {"decimal_place":6,"contract_address":"0xdac17f958d2ee523a2206206994597c13d831ec7"}}}"#,
+            )
+            .with_status(
+                "/api/v3/coins/ethereum/contract/0x0000000000000000000000000000000000000bad",
+                StatusCode::NOT_FOUND,
+            );
+        let rpc = CoinGeckoRpc::with_default_url(Arc::new(transport), None);
+
+        let prices = rpc
+            .prices(&["bitcoin", "ethereum", "unknown"], "EUR")
+            .await
+            .unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices["bitcoin"].to_string(), "60123.45 EUR");
+
+        let date = "2024-01-01".parse().unwrap();
+        let price = rpc.historical_price("bitcoin", "usd", date).await.unwrap();
+        assert_eq!(price.unwrap().to_string(), "42280.23 USD");
+        let date = "2009-01-01".parse().unwrap();
+        assert!(rpc
+            .historical_price("bitcoin", "usd", date)
+            .await
+            .unwrap()
+            .is_none());
+
+        let usdt = TokenContract::new("ethereum", "0xdAC17F958D2ee523a2206206994597C13D831ec7");
+        assert_eq!(
+            rpc.token_info(&usdt).await.unwrap(),
+            Some(TokenInfo {
+                id: "tether".to_string(),
+                decimals: Some(6)
+            })
+        );
+        let unlisted = TokenContract::new("ethereum", "0x0000000000000000000000000000000000000bad");
+        assert!(rpc.token_info(&unlisted).await.unwrap().is_none());
+    }
+}
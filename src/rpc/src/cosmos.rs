@@ -1,75 +1,282 @@
-use crate::http::{HttpError, HttpTransport};
+use crate::amount::{self, Amount};
+use crate::cosmos_registry::CosmosChain;
+use crate::failover::FailoverTransport;
+use crate::http::{HttpError, SharedTransport};
 use http::uri::InvalidUri;
 use http::{Response, Uri};
-use serde::Deserialize;
-use std::collections::HashSet;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 const COSMOS_URL: &str = "https://api.cosmos.network";
 
-pub struct CosmosRpc<'a, T> {
-    transport: &'a T,
+/// The maximum page size allowed by the Cosmos SDK REST API.
+const TXS_PAGE_LIMIT: usize = 100;
+
+#[derive(Clone)]
+pub struct CosmosRpc {
+    transport: SharedTransport,
     url: String,
+    /// Detected on the first transaction search.
+    tx_search_api: OnceCell<TxSearchApi>,
 }
 
-impl<'a, T> CosmosRpc<'a, T>
-where
-    T: HttpTransport + Sync,
-{
-    pub fn with_default_url(transport: &'a T) -> Self {
+impl CosmosRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
         CosmosRpc::with_url(transport, COSMOS_URL.to_string())
-            .expect("'ETHERSCAN_URL' is expected to be a valid URL")
+            .expect("'COSMOS_URL' is expected to be a valid URL")
+    }
+
+    /// Creates a client with the preferred REST endpoint of the given `chain`,
+    /// failing over to its other REST endpoints.
+    pub fn with_chain(transport: SharedTransport, chain: &CosmosChain) -> Result<Self, InvalidUri> {
+        match FailoverTransport::from_urls(transport.clone(), &chain.rest_urls)? {
+            Some(failover) => {
+                let url = failover.url().to_string();
+                CosmosRpc::with_url(Arc::new(failover), url)
+            }
+            // Fails on the empty URL.
+            None => CosmosRpc::with_url(transport, String::new()),
+        }
     }
 
-    pub fn with_url(transport: &'a T, url: String) -> Result<Self, InvalidUri> {
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
         // Check if the given `url` is correct.
         url.parse::<Uri>()?;
-        Ok(CosmosRpc { transport, url })
+        Ok(CosmosRpc {
+            transport,
+            url,
+            tx_search_api: OnceCell::new(),
+        })
     }
 
     /// Requests the count of the address transactions.
-    /// TODO this is suboptimal to fetch all transactions, but I couldn't find another quick solution.
+    ///
+    /// The tx search doesn't support `OR` conditions, so every [`EventFilter`] is searched separately.
+    /// If only one of the filters matches any transactions, its reported total is the count, as there is
+    /// nothing for its transactions to overlap with. Otherwise the transactions of the matching filters
+    /// are requested page by page and deduplicated by their hashes.
     pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
-        let spender_txs = self.request_txs(&EventFilter::spender(address)).await?;
-        let receiver_txs = self.request_txs(&EventFilter::receiver(address)).await?;
+        let mut matching = Vec::new();
+        for event in EventFilter::all(address) {
+            let first_page = self.txs_page(&event, 0).await?;
+            if !first_page.tx_responses.is_empty() {
+                matching.push((event, first_page));
+            }
+        }
+        if let [(_, first_page)] = matching.as_slice() {
+            if let Some(total) = first_page.total() {
+                return Ok(total);
+            }
+        }
+
+        let mut unique_txs = HashSet::new();
+        for (event, first_page) in matching {
+            unique_txs.extend(self.search_txs(&event, first_page).await?);
+        }
+        Ok(unique_txs.len())
+    }
 
-        // Determine unique transaction hashes to get a total number of address transactions.
-        let unique_txs: HashSet<_> = spender_txs
+    /// Requests the spendable balances of the address.
+    pub async fn balances(&self, address: &str) -> Result<Vec<Coin>, HttpError> {
+        let res: BalancesResult = self
+            .get_json(&format!("/cosmos/bank/v1beta1/balances/{address}"))
+            .await?;
+        Ok(res.balances)
+    }
+
+    /// Requests the active delegations of the address.
+    pub async fn delegations(&self, address: &str) -> Result<Vec<Delegation>, HttpError> {
+        let res: DelegationsResult = self
+            .get_json(&format!("/cosmos/staking/v1beta1/delegations/{address}"))
+            .await?;
+        Ok(res
+            .delegation_responses
             .into_iter()
-            .chain(receiver_txs.into_iter())
-            .collect();
+            .map(|res| Delegation {
+                validator_address: res.delegation.validator_address,
+                balance: res.balance,
+            })
+            .collect())
+    }
 
-        Ok(unique_txs.len())
+    /// Requests the delegations of the address that are being unbonded.
+    pub async fn unbonding_delegations(
+        &self,
+        address: &str,
+    ) -> Result<Vec<UnbondingDelegation>, HttpError> {
+        let res: UnbondingDelegationsResult = self
+            .get_json(&format!(
+                "/cosmos/staking/v1beta1/delegators/{address}/unbonding_delegations"
+            ))
+            .await?;
+        Ok(res.unbonding_responses)
     }
 
-    /// Requests the the address info.
-    async fn request_txs(&self, events: &str) -> Result<Vec<TxInfo>, HttpError> {
-        /// Set the limit to `0` to fetch all transactions.
-        const LIMIT: usize = 0;
+    /// Requests the pending staking rewards of the address.
+    pub async fn rewards(&self, address: &str) -> Result<Rewards, HttpError> {
+        self.get_json(&format!(
+            "/cosmos/distribution/v1beta1/delegators/{address}/rewards"
+        ))
+        .await
+    }
 
-        let url = &self.url;
-        let uri = format!("{url}/cosmos/tx/v1beta1/txs?events={events}&pagination.limit={LIMIT}")
-            .parse()?;
+    /// Requests the Cosmos SDK version of the node, e.g. `v0.47.5`.
+    pub async fn sdk_version(&self) -> Result<String, HttpError> {
+        let node_info: NodeInfo = self
+            .get_json("/cosmos/base/tendermint/v1beta1/node_info")
+            .await?;
+        Ok(node_info.application_version.cosmos_sdk_version)
+    }
+
+    /// Requests the zero-based `page` of the transactions matching the `event`.
+    async fn txs_page(&self, event: &str, page: usize) -> Result<TxsResult, HttpError> {
+        // The nodes that don't serve `node_info` are searched with the oldest API,
+        // as the nodes of an unknown version are.
+        let api = self
+            .tx_search_api
+            .get_or_init(|| async {
+                match self.sdk_version().await {
+                    Ok(version) => TxSearchApi::from_sdk_version(&version),
+                    Err(_) => TxSearchApi::Legacy,
+                }
+            })
+            .await;
+        self.get_json(&api.page_path(event, page)).await
+    }
+
+    /// Requests the hashes of all transactions matching the `event` page by page,
+    /// continuing from the `first_page`.
+    async fn search_txs(
+        &self,
+        event: &str,
+        first_page: TxsResult,
+    ) -> Result<Vec<TxInfo>, HttpError> {
+        let mut txs = Vec::new();
+        let mut res = first_page;
+        for page in 1.. {
+            let page_len = res.tx_responses.len();
+            let total = res.total();
+            txs.extend(res.tx_responses);
+
+            let is_last_page = match total {
+                Some(total) => txs.len() >= total,
+                None => page_len < TXS_PAGE_LIMIT,
+            };
+            if is_last_page || page_len == 0 {
+                break;
+            }
+            res = self.txs_page(event, page).await?;
+        }
+        Ok(txs)
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let uri = format!("{}{path}", self.url).parse()?;
+        let res: Response<Vec<u8>> = self.transport.get(uri).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+}
+
+/// The flavours of `GET /cosmos/tx/v1beta1/txs` across the Cosmos SDK versions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TxSearchApi {
+    /// SDK < 0.46: `events=` with `pagination.offset`/`pagination.limit`/`pagination.count_total`.
+    Legacy,
+    /// SDK 0.46 and 0.47: `events=` with `page`/`limit`, the response has a top-level `total`.
+    Paged,
+    /// SDK >= 0.50: `query=` with `page`/`limit`.
+    Query,
+}
+
+impl TxSearchApi {
+    /// Parses a version like `v0.47.5`. Falls back to the oldest API if the version is unknown.
+    fn from_sdk_version(version: &str) -> TxSearchApi {
+        let mut numbers = version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|n| n.parse::<u32>().unwrap_or_default());
+        let major = numbers.next().unwrap_or_default();
+        let minor = numbers.next().unwrap_or_default();
+
+        match (major, minor) {
+            (0, 0..=45) => TxSearchApi::Legacy,
+            (0, 46..=47) => TxSearchApi::Paged,
+            _ => TxSearchApi::Query,
+        }
+    }
 
-        let res: Response<TxsResult> = self.transport.get_json(uri).await?;
-        // TODO check if the response is OK(200) and if the `EtherscanResponse::message` is "OK".
-        let (_parts, res) = res.into_parts();
+    /// Returns a request path of the zero-based `page` of the transactions matching the `event`.
+    fn page_path(&self, event: &str, page: usize) -> String {
+        const TXS_PATH: &str = "/cosmos/tx/v1beta1/txs";
 
-        Ok(res.tx_responses)
+        match self {
+            TxSearchApi::Legacy => {
+                let offset = page * TXS_PAGE_LIMIT;
+                format!("{TXS_PATH}?events={event}&pagination.offset={offset}&pagination.limit={TXS_PAGE_LIMIT}&pagination.count_total=true")
+            }
+            TxSearchApi::Paged => {
+                let page = page + 1;
+                format!("{TXS_PATH}?events={event}&page={page}&limit={TXS_PAGE_LIMIT}")
+            }
+            TxSearchApi::Query => {
+                let page = page + 1;
+                format!("{TXS_PATH}?query={event}&page={page}&limit={TXS_PAGE_LIMIT}")
+            }
+        }
     }
 }
 
 struct EventFilter;
 
 impl EventFilter {
-    fn spender(address: &str) -> String { format!("coin_spent.spender='{address}'") }
-
-    fn receiver(address: &str) -> String { format!("coin_received.receiver='{address}'") }
+    /// Returns the events that cover every transaction the address has taken part in:
+    /// signed messages (including delegations and IBC transfers), fee payments (including failed txs),
+    /// and incoming transfers.
+    fn all(address: &str) -> [String; 4] {
+        [
+            format!("message.sender='{address}'"),
+            format!("tx.fee_payer='{address}'"),
+            format!("coin_spent.spender='{address}'"),
+            format!("coin_received.receiver='{address}'"),
+        ]
+    }
 }
 
-/// We're currently interested in the `tx_responses` field, so ignore other ones.
+/// We're currently interested in the `tx_responses` and totals fields, so ignore other ones.
 #[derive(Deserialize)]
 struct TxsResult {
     tx_responses: Vec<TxInfo>,
+    /// Present in SDK < 0.46 if `pagination.count_total=true`.
+    pagination: Option<PageResponse>,
+    /// Present in SDK >= 0.46.
+    total: Option<String>,
+}
+
+impl TxsResult {
+    /// Returns the total number of the matching transactions if the node reported it.
+    fn total(&self) -> Option<usize> {
+        let pagination_total = self.pagination.as_ref().and_then(|p| p.total.as_ref());
+        self.total
+            .as_ref()
+            .or(pagination_total)
+            .and_then(|total| total.parse().ok())
+            // Some nodes report `0` instead of omitting the total.
+            .filter(|total| *total > 0)
+    }
+}
+
+#[derive(Deserialize)]
+struct PageResponse {
+    total: Option<String>,
 }
 
 /// We're currently interested in the `txhash` field, so ignore other ones.
@@ -78,14 +285,349 @@ struct TxInfo {
     txhash: String,
 }
 
+/// A coin amount in the base units of the `denom`, e.g. `uatom`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Coin {
+    pub denom: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub amount: u128,
+}
+
+impl Coin {
+    /// Returns the amount in the units of the chain staking token if the `denom` is the staking one.
+    /// Other denoms, e.g. IBC tokens, are returned in their base units, as their decimals aren't known.
+    pub fn to_amount(&self, chain: &CosmosChain) -> Amount {
+        let coin = if self.denom == chain.staking_denom {
+            chain.staking_coin()
+        } else {
+            amount::Coin::new(self.denom.clone(), self.denom.clone(), 0)
+        };
+        Amount::from_base_units(self.amount, coin)
+    }
+
+    /// Sums up the `coins` amounts grouped by their denoms.
+    pub fn sum_by_denom<'a, I>(coins: I) -> BTreeMap<String, u128>
+    where
+        I: IntoIterator<Item = &'a Coin>,
+    {
+        let mut sums = BTreeMap::new();
+        for coin in coins {
+            *sums.entry(coin.denom.clone()).or_default() += coin.amount;
+        }
+        sums
+    }
+}
+
+/// Parses an integer amount (`"1000"`) or a decimal amount (`"1000.500000000000000000"`).
+/// Decimal amounts are truncated to integers, as only whole base units can be withdrawn.
+fn deserialize_amount<'de, D>(deserializer: D) -> Result<u128, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let amount = String::deserialize(deserializer)?;
+    let integer = amount.split('.').next().unwrap_or_default();
+    integer
+        .parse()
+        .map_err(|e| D::Error::custom(format!("Invalid amount '{amount}': {e}")))
+}
+
+#[derive(Debug)]
+pub struct Delegation {
+    pub validator_address: String,
+    pub balance: Coin,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnbondingDelegation {
+    pub validator_address: String,
+    pub entries: Vec<UnbondingEntry>,
+}
+
+/// The `balance` is in the staking denom of the chain.
+#[derive(Debug, Deserialize)]
+pub struct UnbondingEntry {
+    pub completion_time: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub balance: u128,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Rewards {
+    pub rewards: Vec<ValidatorRewards>,
+    pub total: Vec<Coin>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatorRewards {
+    pub validator_address: String,
+    pub reward: Vec<Coin>,
+}
+
+#[derive(Deserialize)]
+struct BalancesResult {
+    balances: Vec<Coin>,
+}
+
+#[derive(Deserialize)]
+struct DelegationsResult {
+    delegation_responses: Vec<DelegationResponse>,
+}
+
+#[derive(Deserialize)]
+struct DelegationResponse {
+    delegation: DelegationInfo,
+    balance: Coin,
+}
+
+/// We're currently interested in the `validator_address` field, so ignore other ones.
+#[derive(Deserialize)]
+struct DelegationInfo {
+    validator_address: String,
+}
+
+#[derive(Deserialize)]
+struct UnbondingDelegationsResult {
+    unbonding_responses: Vec<UnbondingDelegation>,
+}
+
+#[derive(Deserialize)]
+struct NodeInfo {
+    application_version: ApplicationVersion,
+}
+
+#[derive(Deserialize)]
+struct ApplicationVersion {
+    cosmos_sdk_version: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cosmos_registry::CosmosChainRegistry;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "cosmos1mzfn3lk6f6vu6hnazc5fazxn9eme5acmay4p6m";
+
+    fn txs_page(hashes: &[&str], total: usize, api: TxSearchApi) -> String {
+        let txs: Vec<_> = hashes
+            .iter()
+            .map(|hash| format!(r#"{{"txhash":"{hash}"}}"#))
+            .collect();
+        let txs = txs.join(",");
+        match api {
+            TxSearchApi::Legacy => format!(
+                r#"{{"txs":[],"tx_responses":[{txs}],"pagination":{{"next_key":null,"total":"{total}"}}}}"#
+            ),
+            TxSearchApi::Paged | TxSearchApi::Query => {
+                format!(
+                    r#"{{"txs":[],"tx_responses":[{txs}],"pagination":null,"total":"{total}"}}"#
+                )
+            }
+        }
+    }
+
+    /// Stubs a node where the address sent `A` and `B` (with `B` paying the fee only),
+    /// delegated in `C`, and received in `B` and `D`.
+    fn stub_transport(sdk_version: &str, api: TxSearchApi) -> StubTransport {
+        let [sender, fee_payer, spender, receiver] = EventFilter::all(ADDRESS);
+        let stubs = [
+            (sender, vec!["A", "C"]),
+            (fee_payer, vec!["A", "B", "C"]),
+            (spender, vec!["A", "B", "C"]),
+            (receiver, vec!["B", "D"]),
+        ];
+
+        let node_info = format!(
+            r#"{{"application_version":{{"name":"gaia","cosmos_sdk_version":"{sdk_version}"}}}}"#
+        );
+        let mut transport = StubTransport::default()
+            .with_response("/cosmos/base/tendermint/v1beta1/node_info", &node_info);
+        for (event, hashes) in stubs {
+            let total = hashes.len();
+            transport =
+                transport.with_response(&api.page_path(&event, 0), &txs_page(&hashes, total, api));
+        }
+        transport
+    }
+
+    #[test]
+    fn test_tx_search_api_from_sdk_version() {
+        assert_eq!(
+            TxSearchApi::from_sdk_version("v0.45.16"),
+            TxSearchApi::Legacy
+        );
+        assert_eq!(
+            TxSearchApi::from_sdk_version("v0.46.13"),
+            TxSearchApi::Paged
+        );
+        assert_eq!(TxSearchApi::from_sdk_version("v0.47.5"), TxSearchApi::Paged);
+        assert_eq!(TxSearchApi::from_sdk_version("v0.50.1"), TxSearchApi::Query);
+        assert_eq!(
+            TxSearchApi::from_sdk_version("unknown"),
+            TxSearchApi::Legacy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cosmos_transaction_count_stub() {
+        for (sdk_version, api) in [
+            ("v0.45.16", TxSearchApi::Legacy),
+            ("v0.47.5", TxSearchApi::Paged),
+            ("v0.50.1", TxSearchApi::Query),
+        ] {
+            let transport = stub_transport(sdk_version, api);
+            let rpc = CosmosRpc::with_default_url(Arc::new(transport));
+            let actual = rpc.transaction_count(ADDRESS).await.unwrap();
+            assert_eq!(actual, 4, "sdk_version={sdk_version}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cosmos_transaction_count_pages() {
+        let api = TxSearchApi::Paged;
+        let hashes: Vec<_> = (0..TXS_PAGE_LIMIT + 1).map(|i| i.to_string()).collect();
+        let hashes: Vec<_> = hashes.iter().map(String::as_str).collect();
+        let total = hashes.len();
+
+        let [sender, fee_payer, spender, receiver] = EventFilter::all(ADDRESS);
+        let transport = StubTransport::default()
+            .with_response(
+                "/cosmos/base/tendermint/v1beta1/node_info",
+                r#"{"application_version":{"cosmos_sdk_version":"v0.47.5"}}"#,
+            )
+            .with_response(
+                &api.page_path(&sender, 0),
+                &txs_page(&hashes[..TXS_PAGE_LIMIT], total, api),
+            )
+            .with_response(
+                &api.page_path(&sender, 1),
+                &txs_page(&hashes[TXS_PAGE_LIMIT..], total, api),
+            )
+            .with_response(&api.page_path(&fee_payer, 0), &txs_page(&[], 0, api))
+            .with_response(&api.page_path(&spender, 0), &txs_page(&[], 0, api))
+            // Overlaps with the last sent transaction.
+            .with_response(&api.page_path(&receiver, 0), &txs_page(&["100"], 1, api));
+        let rpc = CosmosRpc::with_default_url(Arc::new(transport));
+
+        let actual = rpc.transaction_count(ADDRESS).await.unwrap();
+        assert_eq!(actual, TXS_PAGE_LIMIT + 1);
+    }
+
+    #[tokio::test]
+    async fn test_cosmos_transaction_count_total() {
+        let api = TxSearchApi::Legacy;
+        let hashes: Vec<_> = (0..TXS_PAGE_LIMIT).map(|i| i.to_string()).collect();
+        let hashes: Vec<_> = hashes.iter().map(String::as_str).collect();
+
+        // The node doesn't serve `node_info`, and the address has only received the transactions,
+        // so the reported total is the count without requesting the other pages.
+        let [sender, fee_payer, spender, receiver] = EventFilter::all(ADDRESS);
+        let transport = StubTransport::default()
+            .with_status(
+                "/cosmos/base/tendermint/v1beta1/node_info",
+                StatusCode::NOT_IMPLEMENTED,
+            )
+            .with_response(&api.page_path(&sender, 0), &txs_page(&[], 0, api))
+            .with_response(&api.page_path(&fee_payer, 0), &txs_page(&[], 0, api))
+            .with_response(&api.page_path(&spender, 0), &txs_page(&[], 0, api))
+            .with_response(&api.page_path(&receiver, 0), &txs_page(&hashes, 250, api));
+        let rpc = CosmosRpc::with_default_url(Arc::new(transport));
+
+        let actual = rpc.transaction_count(ADDRESS).await.unwrap();
+        assert_eq!(actual, 250);
+    }
+
+    #[tokio::test]
+    async fn test_cosmos_failover_stub() {
+        let registry = CosmosChainRegistry::bundled();
+        let chain = CosmosChain {
+            rest_urls: vec![
+                "https://a.example/a".to_string(),
+                "https://b.example/b".to_string(),
+            ],
+            ..registry.by_name("cosmoshub").unwrap().clone()
+        };
+        let path = format!("/cosmos/bank/v1beta1/balances/{ADDRESS}");
+        let transport = StubTransport::default()
+            .with_status(&format!("/a{path}"), StatusCode::SERVICE_UNAVAILABLE)
+            .with_response(
+                &format!("/b{path}"),
+                r#"{"balances":[{"denom":"uatom","amount":"1500000"}],"pagination":{"next_key":null,"total":"1"}}"#,
+            );
+        let rpc = CosmosRpc::with_chain(Arc::new(transport), &chain).unwrap();
+
+        let balances = rpc.balances(ADDRESS).await.unwrap();
+        assert_eq!(balances[0].amount, 1_500_000);
+    }
+
+    #[tokio::test]
+    async fn test_cosmos_holdings_stub() {
+        const VALIDATOR: &str = "cosmosvaloper1sjllsnramtg3ewxqwwrwjxfgc4n4ef9u2lcnj0";
+
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/cosmos/bank/v1beta1/balances/{ADDRESS}"),
+                r#"{"balances":[{"denom":"ibc/27394FB0","amount":"5"},{"denom":"uatom","amount":"1500000"}],
+                "pagination":{"next_key":null,"total":"2"}}"#,
+            )
+            .with_response(
+                &format!("/cosmos/staking/v1beta1/delegations/{ADDRESS}"),
+                &format!(
+                    r#"{{"delegation_responses":[{{
+                    "delegation":{{"delegator_address":"{ADDRESS}","validator_address":"{VALIDATOR}","shares":"25000000.000000000000000000"}},
+                    "balance":{{"denom":"uatom","amount":"25000000"}}}}],"pagination":{{"next_key":null,"total":"1"}}}}"#
+                ),
+            )
+            .with_response(
+                &format!("/cosmos/staking/v1beta1/delegators/{ADDRESS}/unbonding_delegations"),
+                &format!(
+                    r#"{{"unbonding_responses":[{{"delegator_address":"{ADDRESS}","validator_address":"{VALIDATOR}",
+                    "entries":[{{"creation_height":"13000000","completion_time":"2022-12-30T10:00:00Z","initial_balance":"700000","balance":"700000"}}]}}],
+                    "pagination":{{"next_key":null,"total":"1"}}}}"#
+                ),
+            )
+            .with_response(
+                &format!("/cosmos/distribution/v1beta1/delegators/{ADDRESS}/rewards"),
+                &format!(
+                    r#"{{"rewards":[{{"validator_address":"{VALIDATOR}","reward":[{{"denom":"uatom","amount":"12345.678900000000000000"}}]}}],
+                    "total":[{{"denom":"uatom","amount":"12345.678900000000000000"}}]}}"#
+                ),
+            );
+        let rpc = CosmosRpc::with_default_url(Arc::new(transport));
+
+        let balances = rpc.balances(ADDRESS).await.unwrap();
+        assert_eq!(Coin::sum_by_denom(&balances)["uatom"], 1_500_000);
+        let cosmoshub = CosmosChainRegistry::bundled();
+        let cosmoshub = cosmoshub.by_name("cosmoshub").unwrap();
+        assert_eq!(
+            balances[0].to_amount(cosmoshub).to_string(),
+            "5 ibc/27394FB0"
+        );
+        assert_eq!(balances[1].to_amount(cosmoshub).to_string(), "1.5 ATOM");
+
+        let delegations = rpc.delegations(ADDRESS).await.unwrap();
+        assert_eq!(delegations[0].validator_address, VALIDATOR);
+        assert_eq!(delegations[0].balance.amount, 25_000_000);
+
+        let unbonding = rpc.unbonding_delegations(ADDRESS).await.unwrap();
+        assert_eq!(unbonding[0].entries[0].balance, 700_000);
+
+        let rewards = rpc.rewards(ADDRESS).await.unwrap();
+        assert_eq!(
+            rewards.total,
+            vec![Coin {
+                denom: "uatom".to_string(),
+                amount: 12345
+            }]
+        );
+    }
 
     #[tokio::test]
     async fn test_cosmos_transaction_count() {
-        let transport = crate::http::HttpBuilder::build();
-        let rpc = CosmosRpc::with_default_url(&transport);
+        let transport = crate::http::HttpBuilder::build(Default::default());
+        let rpc = CosmosRpc::with_default_url(transport);
 
         let actual = rpc
             .transaction_count("cosmos1mzfn3lk6f6vu6hnazc5fazxn9eme5acmay4p6m")
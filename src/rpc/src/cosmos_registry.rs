@@ -0,0 +1,316 @@
+use crate::amount::Coin;
+use serde::Deserialize;
+use std::{fmt, fs, io};
+
+/// The chains bundled with the app.
+const BUNDLED_CHAINS: &str = include_str!("../res/cosmos_chains.json");
+
+/// The `trustwallet/wallet-core` coin types by the chain-registry names, as the registry has the SLIP-44
+/// coin types only, which many chains share, e.g. `118`.
+const COIN_TYPES: &[(&str, u32)] = &[
+    ("cosmoshub", 118),
+    ("osmosis", 10000118),
+    ("kava", 459),
+    ("terra", 330),
+    ("terra2", 10000330),
+    ("secretnetwork", 529),
+    ("cryptoorgchain", 394),
+    ("bandchain", 494),
+    ("thorchain", 931),
+    ("bluzelle", 483),
+    ("evmos", 20009001),
+];
+
+#[derive(Debug)]
+pub enum RegistryError {
+    Io(String),
+    InvalidFormat(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Io(e) => write!(f, "Error on reading the chain registry: {e}"),
+            RegistryError::InvalidFormat(e) => write!(f, "Invalid chain registry format: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(e: io::Error) -> Self { RegistryError::Io(e.to_string()) }
+}
+
+impl From<serde_json::Error> for RegistryError {
+    fn from(e: serde_json::Error) -> Self { RegistryError::InvalidFormat(e.to_string()) }
+}
+
+#[derive(Clone, Debug)]
+pub struct CosmosChain {
+    /// The chain name as in https://github.com/cosmos/chain-registry, e.g. `cosmoshub`.
+    pub chain_name: String,
+    /// The `trustwallet/wallet-core` coin type, if the chain is supported by it.
+    pub coin_type: Option<u32>,
+    /// The display symbol of the staking token, e.g. `ATOM`.
+    pub symbol: String,
+    /// The human-readable part of the account addresses, e.g. `cosmos`.
+    pub bech32_prefix: String,
+    /// The base denom of the staking token, e.g. `uatom`.
+    pub staking_denom: String,
+    /// The number of decimals between the `staking_denom` and the display `symbol`.
+    pub decimals: u8,
+    /// The LCD (REST) endpoints, the first one is preferred.
+    pub rest_urls: Vec<String>,
+}
+
+impl CosmosChain {
+    /// Returns the units of the staking token, e.g. `uatom` and `ATOM`.
+    pub fn staking_coin(&self) -> Coin {
+        Coin::new(
+            self.symbol.clone(),
+            self.staking_denom.clone(),
+            self.decimals,
+        )
+    }
+
+    /// Whether the bech32 `address` is of the chain, e.g. `cosmos1...` of `cosmoshub`.
+    /// The rest of the address isn't checked.
+    pub fn is_chain_address(&self, address: &str) -> bool {
+        // The human-readable part is separated by the last `1`.
+        address
+            .rsplit_once('1')
+            .is_some_and(|(prefix, _)| prefix == self.bech32_prefix)
+    }
+}
+
+/// A registry of the Cosmos-SDK chains.
+pub struct CosmosChainRegistry {
+    chains: Vec<CosmosChain>,
+}
+
+impl CosmosChainRegistry {
+    /// Returns the registry of the chains bundled with the app.
+    pub fn bundled() -> CosmosChainRegistry {
+        CosmosChainRegistry::from_json(BUNDLED_CHAINS)
+            .expect("Bundled Cosmos chain registry is expected to be valid")
+    }
+
+    /// Loads the registry from a file. See [`CosmosChainRegistry::from_json`].
+    pub fn from_file(path: &str) -> Result<CosmosChainRegistry, RegistryError> {
+        CosmosChainRegistry::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses either a list of chains or a single chain in the format of the chain-registry `chain.json`:
+    /// https://github.com/cosmos/chain-registry/blob/master/chain.schema.json
+    ///
+    /// Only `chain_name`, `bech32_prefix`, `staking` and `apis.rest` are required.
+    /// `coin_type`, `symbol` and `decimals` are the extensions of the format. Without them, the coin type
+    /// is looked up by the `chain_name`, and the symbol and decimals are taken from the `assets`
+    /// of the chain-registry `assetlist.json`, which may be added to the chain. A chain without decimals is rejected:
+    /// https://github.com/cosmos/chain-registry/blob/master/assetlist.schema.json
+    pub fn from_json(json: &str) -> Result<CosmosChainRegistry, RegistryError> {
+        let chains = match serde_json::from_str(json)? {
+            ChainsJson::List(chains) => chains,
+            ChainsJson::Single(chain) => vec![chain],
+        };
+
+        let chains = chains
+            .into_iter()
+            .map(CosmosChain::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(CosmosChainRegistry { chains })
+    }
+
+    /// Returns the chain by the `trustwallet/wallet-core` coin type.
+    pub fn by_coin_type(&self, coin_type: u32) -> Option<&CosmosChain> {
+        self.chains
+            .iter()
+            .find(|chain| chain.coin_type == Some(coin_type))
+    }
+
+    /// Returns the chain by the chain-registry name, e.g. `osmosis`.
+    pub fn by_name(&self, chain_name: &str) -> Option<&CosmosChain> {
+        self.chains
+            .iter()
+            .find(|chain| chain.chain_name == chain_name)
+    }
+
+    pub fn chains(&self) -> impl Iterator<Item = &CosmosChain> { self.chains.iter() }
+}
+
+impl TryFrom<ChainJson> for CosmosChain {
+    type Error = RegistryError;
+
+    fn try_from(chain: ChainJson) -> Result<Self, Self::Error> {
+        let chain_name = chain.chain_name;
+        let staking_denom = chain
+            .staking
+            .staking_tokens
+            .into_iter()
+            .next()
+            .map(|token| token.denom)
+            .ok_or_else(|| {
+                RegistryError::InvalidFormat(format!("'{chain_name}' has no staking tokens"))
+            })?;
+        let rest_urls: Vec<_> = chain.apis.rest.into_iter().map(|api| api.address).collect();
+        if rest_urls.is_empty() {
+            return Err(RegistryError::InvalidFormat(format!(
+                "'{chain_name}' has no REST endpoints"
+            )));
+        }
+
+        let asset = chain
+            .assets
+            .iter()
+            .find(|asset| asset.base == staking_denom);
+        let decimals = chain
+            .decimals
+            .or_else(|| asset.and_then(AssetJson::display_exponent))
+            .ok_or_else(|| {
+                RegistryError::InvalidFormat(format!(
+                    "'{chain_name}' has no decimals of '{staking_denom}' in 'decimals' or 'assets'"
+                ))
+            })?;
+        let symbol = chain
+            .symbol
+            .or_else(|| asset.and_then(|asset| asset.symbol.clone()))
+            .unwrap_or_else(|| chain_name.to_uppercase());
+        let coin_type = chain.coin_type.or_else(|| {
+            COIN_TYPES
+                .iter()
+                .find(|(name, _)| *name == chain_name)
+                .map(|(_, coin_type)| *coin_type)
+        });
+
+        Ok(CosmosChain {
+            symbol,
+            chain_name,
+            coin_type,
+            bech32_prefix: chain.bech32_prefix,
+            staking_denom,
+            decimals,
+            rest_urls,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChainsJson {
+    List(Vec<ChainJson>),
+    Single(ChainJson),
+}
+
+/// We're currently interested in the following `chain.json` fields only, so ignore other ones.
+#[derive(Deserialize)]
+struct ChainJson {
+    chain_name: String,
+    coin_type: Option<u32>,
+    symbol: Option<String>,
+    bech32_prefix: String,
+    decimals: Option<u8>,
+    staking: StakingJson,
+    apis: ApisJson,
+    /// The `assets` of the chain `assetlist.json`.
+    #[serde(default)]
+    assets: Vec<AssetJson>,
+}
+
+#[derive(Deserialize)]
+struct StakingJson {
+    staking_tokens: Vec<StakingTokenJson>,
+}
+
+#[derive(Deserialize)]
+struct StakingTokenJson {
+    denom: String,
+}
+
+#[derive(Deserialize)]
+struct ApisJson {
+    #[serde(default)]
+    rest: Vec<ApiJson>,
+}
+
+#[derive(Deserialize)]
+struct ApiJson {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct AssetJson {
+    base: String,
+    display: String,
+    symbol: Option<String>,
+    denom_units: Vec<DenomUnitJson>,
+}
+
+impl AssetJson {
+    /// Returns the exponent of the `display` denom, e.g. `6` of `atom` to `uatom`.
+    fn display_exponent(&self) -> Option<u8> {
+        self.denom_units
+            .iter()
+            .find(|unit| unit.denom == self.display)
+            .map(|unit| unit.exponent)
+    }
+}
+
+#[derive(Deserialize)]
+struct DenomUnitJson {
+    denom: String,
+    exponent: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosmos_chain_registry() {
+        let registry = CosmosChainRegistry::bundled();
+        let osmosis = registry.by_coin_type(10000118).unwrap();
+        assert_eq!(osmosis.chain_name, "osmosis");
+        assert_eq!(osmosis.bech32_prefix, "osmo");
+        assert_eq!(osmosis.staking_denom, "uosmo");
+        assert_eq!(registry.by_name("evmos").unwrap().decimals, 18);
+        let terra = registry.by_coin_type(330).unwrap();
+        assert_eq!(terra.symbol, "LUNC");
+        assert_eq!(registry.by_coin_type(10000330).unwrap().symbol, "LUNA");
+
+        assert!(terra.is_chain_address("terra1x46rqay4d3cssq8gxxvqz8xt6nwlz4td20k38v"));
+        assert!(!osmosis.is_chain_address("terra1x46rqay4d3cssq8gxxvqz8xt6nwlz4td20k38v"));
+        assert!(!osmosis.is_chain_address("osmo"));
+
+        // A trimmed `chain.json` from https://github.com/cosmos/chain-registry, with the `assetlist.json` assets.
+        let chain_json = r#"{
+            "$schema": "../chain.schema.json",
+            "chain_name": "evmos",
+            "bech32_prefix": "evmos",
+            "slip44": 60,
+            "staking": { "staking_tokens": [{ "denom": "aevmos" }] },
+            "apis": {
+                "rpc": [{ "address": "https://rpc-evmos.ecostake.com", "provider": "ecostake" }],
+                "rest": [{ "address": "https://rest.bd.evmos.org:1317", "provider": "Blockdaemon" }]
+            },
+            "assets": [{
+                "base": "aevmos",
+                "display": "evmos",
+                "symbol": "EVMOS",
+                "denom_units": [{ "denom": "aevmos", "exponent": 0 }, { "denom": "evmos", "exponent": 18 }]
+            }]
+        }"#;
+        let registry = CosmosChainRegistry::from_json(chain_json).unwrap();
+        let evmos = registry.by_coin_type(20009001).unwrap();
+        assert_eq!(evmos.symbol, "EVMOS");
+        assert_eq!(evmos.decimals, 18);
+        assert_eq!(evmos.rest_urls, vec!["https://rest.bd.evmos.org:1317"]);
+
+        // The decimals aren't guessed.
+        let chain_json = r#"{
+            "chain_name": "juno",
+            "bech32_prefix": "juno",
+            "staking": { "staking_tokens": [{ "denom": "ujuno" }] },
+            "apis": { "rest": [{ "address": "https://lcd-juno.itastakers.com" }] }
+        }"#;
+        assert!(CosmosChainRegistry::from_json(chain_json).is_err());
+    }
+}
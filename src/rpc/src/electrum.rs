@@ -0,0 +1,481 @@
+use crate::amount::{Amount, Coin};
+use crate::blockstream::Satoshis;
+use crate::tls;
+use crate::transport_config::TransportConfig;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::{fmt, io};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_rustls::rustls::ServerName;
+use tokio_rustls::TlsConnector;
+
+pub const ELECTRUM_URL: &str = "ssl://electrum.blockstream.info:50002";
+
+/// The client name reported to the server on `server.version` negotiation.
+const CLIENT_NAME: &str = "hd-wallet";
+/// https://electrumx.readthedocs.io/en/latest/protocol-methods.html#server-version
+const PROTOCOL_VERSION: &str = "1.4";
+/// The number of consecutive unused addresses that ends a chain of an account, as BIP44 suggests.
+pub const GAP_LIMIT: u32 = 20;
+
+#[derive(Debug)]
+pub enum ElectrumError {
+    InvalidUrl(String),
+    InvalidPayload(String),
+    ConnectionError(String),
+    ServerError(String),
+}
+
+impl fmt::Display for ElectrumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElectrumError::InvalidUrl(e) => write!(f, "Invalid URL: {e}"),
+            ElectrumError::InvalidPayload(e) => write!(f, "Invalid response payload: {e}"),
+            ElectrumError::ConnectionError(e) => write!(f, "Connection error: {e}"),
+            ElectrumError::ServerError(e) => write!(f, "Server error: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for ElectrumError {
+    fn from(e: io::Error) -> Self { ElectrumError::ConnectionError(e.to_string()) }
+}
+
+impl From<serde_json::Error> for ElectrumError {
+    fn from(e: serde_json::Error) -> Self { ElectrumError::InvalidPayload(e.to_string()) }
+}
+
+/// Computes an Electrum script hash of the given `script_pubkey`:
+/// a SHA256 hash of the script, with the bytes reversed and hex-encoded.
+///
+/// https://electrumx.readthedocs.io/en/latest/protocol-basics.html#script-hashes
+pub fn script_hash(script_pubkey: &[u8]) -> String {
+    let mut hash = Sha256::digest(script_pubkey).to_vec();
+    hash.reverse();
+    hex::encode(hash)
+}
+
+/// Either a plain TCP or a TLS stream.
+trait ElectrumStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S> ElectrumStream for S where S: AsyncRead + AsyncWrite + Unpin + Send {}
+
+struct Connection {
+    stream: BufReader<Box<dyn ElectrumStream>>,
+    next_id: u64,
+}
+
+/// An Electrum protocol client that speaks newline-delimited JSON-RPC over one TCP or TLS connection.
+///
+/// https://electrumx.readthedocs.io/en/latest/protocol.html
+pub struct ElectrumRpc {
+    connection: Mutex<Connection>,
+    server_version: ServerVersion,
+}
+
+impl ElectrumRpc {
+    pub async fn connect_default_url() -> Result<Self, ElectrumError> {
+        ElectrumRpc::connect(ELECTRUM_URL).await
+    }
+
+    /// Connects to the server at the given `url` and negotiates the protocol version.
+    /// The `url` is expected to be either `tcp://host:port` or `ssl://host:port`.
+    pub async fn connect(url: &str) -> Result<Self, ElectrumError> {
+        ElectrumRpc::connect_with_config(url, &TransportConfig::default()).await
+    }
+
+    /// Connects as [`connect`](ElectrumRpc::connect) does, verifying the `ssl` server with the TLS settings
+    /// of the `config`: its root certificates and public key pins. The other settings don't apply.
+    pub async fn connect_with_config(
+        url: &str,
+        config: &TransportConfig,
+    ) -> Result<Self, ElectrumError> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|e| ElectrumError::InvalidUrl(format!("{url}: {e}")))?;
+        let host = uri
+            .host()
+            .ok_or_else(|| ElectrumError::InvalidUrl(format!("{url}: expected a host")))?;
+        let port = uri
+            .port_u16()
+            .ok_or_else(|| ElectrumError::InvalidUrl(format!("{url}: expected a port")))?;
+
+        let stream: Box<dyn ElectrumStream> = match uri.scheme_str() {
+            Some("tcp") => Box::new(TcpStream::connect((host, port)).await?),
+            Some("ssl") => {
+                let tcp = TcpStream::connect((host, port)).await?;
+                Box::new(
+                    tls_connector(config)
+                        .connect(server_name(host)?, tcp)
+                        .await?,
+                )
+            }
+            _ => {
+                return Err(ElectrumError::InvalidUrl(format!(
+                    "{url}: expected either 'tcp' or 'ssl' scheme"
+                )))
+            }
+        };
+
+        ElectrumRpc::with_stream(stream).await
+    }
+
+    async fn with_stream(stream: Box<dyn ElectrumStream>) -> Result<Self, ElectrumError> {
+        let connection = Mutex::new(Connection {
+            stream: BufReader::new(stream),
+            next_id: 0,
+        });
+        let mut rpc = ElectrumRpc {
+            connection,
+            server_version: ServerVersion::default(),
+        };
+
+        // `server.version` must be the first message sent on the connection.
+        rpc.server_version = rpc
+            .call("server.version", json!([CLIENT_NAME, PROTOCOL_VERSION]))
+            .await?;
+        Ok(rpc)
+    }
+
+    /// Returns the server software and the protocol version negotiated on connection.
+    pub fn server_version(&self) -> &ServerVersion { &self.server_version }
+
+    /// Requests the count of the script hash transactions, including the mempool ones.
+    pub async fn transaction_count(&self, script_hash: &str) -> Result<usize, ElectrumError> {
+        self.get_history(script_hash)
+            .await
+            .map(|history| history.len())
+    }
+
+    /// Requests the confirmed and unconfirmed history of the script hash.
+    pub async fn get_history(&self, script_hash: &str) -> Result<Vec<HistoryItem>, ElectrumError> {
+        self.call("blockchain.scripthash.get_history", json!([script_hash]))
+            .await
+    }
+
+    /// Requests the histories of the given script hashes pipelined over the connection.
+    pub async fn get_history_batch(
+        &self,
+        script_hashes: &[String],
+    ) -> Result<Vec<Vec<HistoryItem>>, ElectrumError> {
+        self.call_for_each("blockchain.scripthash.get_history", script_hashes)
+            .await
+    }
+
+    /// Scans a chain of the account addresses, e.g. the receive or the change one, until [`GAP_LIMIT`]
+    /// consecutive unused ones. The histories are requested in batches of [`GAP_LIMIT`] script hashes.
+    /// Returns the script hashes of the used addresses, as returned by `script_hash_at` the address index,
+    /// with their histories.
+    pub async fn scan_chain<F>(
+        &self,
+        script_hash_at: F,
+    ) -> Result<Vec<(String, Vec<HistoryItem>)>, ElectrumError>
+    where
+        F: Fn(u32) -> String,
+    {
+        let mut used = Vec::new();
+        let mut unused = 0;
+        let mut next_index = 0;
+        while unused < GAP_LIMIT {
+            let script_hashes: Vec<_> = (next_index..next_index + GAP_LIMIT)
+                .map(&script_hash_at)
+                .collect();
+            next_index += GAP_LIMIT;
+
+            let histories = self.get_history_batch(&script_hashes).await?;
+            for (script_hash, history) in script_hashes.into_iter().zip(histories) {
+                if history.is_empty() {
+                    unused += 1;
+                    if unused == GAP_LIMIT {
+                        break;
+                    }
+                } else {
+                    unused = 0;
+                    used.push((script_hash, history));
+                }
+            }
+        }
+        Ok(used)
+    }
+
+    /// Requests the confirmed and unconfirmed balances of the script hash.
+    pub async fn get_balance(&self, script_hash: &str) -> Result<Balance, ElectrumError> {
+        self.call("blockchain.scripthash.get_balance", json!([script_hash]))
+            .await
+    }
+
+    /// Requests the balances of the given script hashes pipelined over the connection.
+    pub async fn get_balance_batch(
+        &self,
+        script_hashes: &[String],
+    ) -> Result<Vec<Balance>, ElectrumError> {
+        self.call_for_each("blockchain.scripthash.get_balance", script_hashes)
+            .await
+    }
+
+    /// Requests the unspent outputs of the script hash.
+    pub async fn listunspent(&self, script_hash: &str) -> Result<Vec<Unspent>, ElectrumError> {
+        self.call("blockchain.scripthash.listunspent", json!([script_hash]))
+            .await
+    }
+
+    /// Requests the unspent outputs of the given script hashes pipelined over the connection.
+    pub async fn listunspent_batch(
+        &self,
+        script_hashes: &[String],
+    ) -> Result<Vec<Vec<Unspent>>, ElectrumError> {
+        self.call_for_each("blockchain.scripthash.listunspent", script_hashes)
+            .await
+    }
+
+    async fn call<R>(&self, method: &str, params: Value) -> Result<R, ElectrumError>
+    where
+        R: DeserializeOwned,
+    {
+        let mut results = self.call_pipelined(&[(method, params)]).await?;
+        // `call_pipelined` returns exactly one result per request.
+        Ok(serde_json::from_value(results.remove(0))?)
+    }
+
+    async fn call_for_each<R>(
+        &self,
+        method: &str,
+        script_hashes: &[String],
+    ) -> Result<Vec<R>, ElectrumError>
+    where
+        R: DeserializeOwned,
+    {
+        let requests: Vec<_> = script_hashes
+            .iter()
+            .map(|script_hash| (method, json!([script_hash])))
+            .collect();
+        self.call_pipelined(&requests)
+            .await?
+            .into_iter()
+            .map(|result| serde_json::from_value(result).map_err(ElectrumError::from))
+            .collect()
+    }
+
+    /// Writes all `requests` at once, then reads the responses matching them by `id`.
+    /// Returns the results in the order of `requests`.
+    async fn call_pipelined(
+        &self,
+        requests: &[(&str, Value)],
+    ) -> Result<Vec<Value>, ElectrumError> {
+        let mut connection = self.connection.lock().await;
+
+        let first_id = connection.next_id;
+        connection.next_id += requests.len() as u64;
+
+        let mut payload = String::new();
+        for (id, (method, params)) in (first_id..).zip(requests) {
+            let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+            payload.push_str(&request.to_string());
+            payload.push('\n');
+        }
+        connection.stream.write_all(payload.as_bytes()).await?;
+        connection.stream.flush().await?;
+
+        let mut results: HashMap<u64, Value> = HashMap::with_capacity(requests.len());
+        let mut line = String::new();
+        while results.len() < requests.len() {
+            line.clear();
+            if connection.stream.read_line(&mut line).await? == 0 {
+                return Err(ElectrumError::ConnectionError(
+                    "Connection closed by the server".to_string(),
+                ));
+            }
+
+            let response: JsonRpcResponse = serde_json::from_str(&line)?;
+            let id = match response.id {
+                Some(id) if (first_id..connection.next_id).contains(&id) => id,
+                // Skip subscription notifications and stale responses of a previously failed batch.
+                _ => continue,
+            };
+            if let Some(error) = response.error {
+                return Err(ElectrumError::ServerError(error.to_string()));
+            }
+            results.insert(id, response.result.unwrap_or(Value::Null));
+        }
+
+        Ok((first_id..connection.next_id)
+            .map(|id| results.remove(&id).unwrap_or(Value::Null))
+            .collect())
+    }
+}
+
+fn tls_connector(config: &TransportConfig) -> TlsConnector {
+    let tls_config =
+        tls::client_config(config.native_roots, &config.root_certificates, &config.pins);
+    TlsConnector::from(Arc::new(tls_config))
+}
+
+fn server_name(host: &str) -> Result<ServerName, ElectrumError> {
+    ServerName::try_from(host).map_err(|e| ElectrumError::InvalidUrl(format!("{host}: {e}")))
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    id: Option<u64>,
+    result: Option<Value>,
+    error: Option<Value>,
+}
+
+/// The `server.version` response, e.g. `["ElectrumX 1.16.0", "1.4"]`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ServerVersion {
+    pub server_software: String,
+    pub protocol_version: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryItem {
+    pub tx_hash: String,
+    /// `0` or `-1` for the mempool transactions.
+    pub height: i64,
+    /// Present for the mempool transactions only.
+    pub fee: Option<Satoshis>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Balance {
+    #[serde(deserialize_with = "deserialize_satoshis")]
+    pub confirmed: Amount,
+    /// Negative if the mempool transactions spend confirmed outputs.
+    #[serde(deserialize_with = "deserialize_satoshis")]
+    pub unconfirmed: Amount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Unspent {
+    pub tx_hash: String,
+    pub tx_pos: u32,
+    /// `0` for the mempool outputs.
+    pub height: i64,
+    pub value: Satoshis,
+}
+
+fn deserialize_satoshis<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let satoshis = Satoshis::deserialize(deserializer)?;
+    Ok(Amount::from_signed_base_units(
+        i128::from(satoshis),
+        Coin::BTC,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Spawns a one-connection Electrum server stub that answers the requests in the reversed order.
+    async fn spawn_stub_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+
+            let mut pending = Vec::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let result = match request["method"].as_str().unwrap() {
+                    "server.version" => json!(["ElectrumX 1.16.0", "1.4"]),
+                    "blockchain.scripthash.get_history"
+                        if request["params"][0].as_str().unwrap().starts_with("unused") =>
+                    {
+                        json!([])
+                    }
+                    "blockchain.scripthash.get_history" => json!([
+                        { "tx_hash": "f3e1bf48975b8d6060a9de8884296abb80be618dc00ae3cb2f6cee3085e09403", "height": 200004 },
+                        { "tx_hash": "9fbed79a1e970343fcd39f4a2d830a6bde6de0754ed2da70f489d0303ed558ec", "height": 0, "fee": 20000 },
+                    ]),
+                    "blockchain.scripthash.get_balance" => {
+                        json!({ "confirmed": 103873966, "unconfirmed": 23684400 })
+                    }
+                    method => panic!("Unexpected method: {method}"),
+                };
+                pending.push(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }));
+
+                // Wait until the whole pipelined batch is received, then answer in the reversed order.
+                if lines.get_ref().buffer().is_empty() {
+                    // Send a notification first, the client is expected to skip it.
+                    let mut payload = "{\"jsonrpc\":\"2.0\",\"method\":\"blockchain.headers.subscribe\",\"params\":[]}\n".to_string();
+                    for response in pending.drain(..).rev() {
+                        payload.push_str(&format!("{response}\n"));
+                    }
+                    writer.write_all(payload.as_bytes()).await.unwrap();
+                }
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn test_script_hash() {
+        // The P2PKH script of `1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa`.
+        let script = hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert_eq!(
+            script_hash(&script),
+            "8b01df4e368ea28f8dc0423bcf7a4923e3a12d307c875e47a0cfbf90b5c39161"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_electrum_rpc() {
+        let url = spawn_stub_server().await;
+        let rpc = ElectrumRpc::connect(&url).await.unwrap();
+        assert_eq!(rpc.server_version().server_software, "ElectrumX 1.16.0");
+        assert_eq!(rpc.server_version().protocol_version, "1.4");
+
+        let script_hashes = vec![
+            "8b01df4e368ea28f8dc0423bcf7a4923e3a12d307c875e47a0cfbf90b5c39161".to_string(),
+            "4fa2b9cbd4a0a0d3c1c1e9bcf5b0ee1ba5e7a1d4e50f20b1c6b4c1b1f2e5a8d3".to_string(),
+        ];
+        let histories = rpc.get_history_batch(&script_hashes).await.unwrap();
+        assert_eq!(histories.len(), 2);
+        assert_eq!(histories[0].len(), 2);
+        assert_eq!(histories[0][1].fee, Some(20000));
+
+        let balance = rpc.get_balance(&script_hashes[0]).await.unwrap();
+        assert_eq!(balance.confirmed.to_base_string(), "103873966 sat");
+        assert_eq!(balance.unconfirmed.to_string(), "0.236844 BTC");
+    }
+
+    #[tokio::test]
+    async fn test_electrum_scan_chain() {
+        let url = spawn_stub_server().await;
+        let rpc = ElectrumRpc::connect(&url).await.unwrap();
+
+        // The address 22 is found in the second batch, after 18 unused ones, and the third batch ends the chain.
+        let script_hash_at = |index: u32| match index {
+            0 | 3 | 22 => format!("used{index}"),
+            _ => format!("unused{index}"),
+        };
+        let used = rpc.scan_chain(script_hash_at).await.unwrap();
+        let used: Vec<_> = used
+            .iter()
+            .map(|(script_hash, history)| (script_hash.as_str(), history.len()))
+            .collect();
+        assert_eq!(used, [("used0", 2), ("used3", 2), ("used22", 2)]);
+
+        let used = rpc
+            .scan_chain(|index| format!("unused{index}"))
+            .await
+            .unwrap();
+        assert!(used.is_empty());
+    }
+}
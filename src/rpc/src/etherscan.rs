@@ -1,85 +1,982 @@
-use crate::http::{HttpError, HttpTransport};
+use crate::amount::{Amount, Coin};
+use crate::cache::CacheCheck;
+use crate::etherscan_explorers::EtherscanExplorer;
+use crate::failover::FailoverTransport;
+use crate::http::{HttpError, Request, SharedTransport};
+use crate::serde_str::{deserialize_from_str, deserialize_optional_from_str};
+use futures::stream::{self, Stream, TryStreamExt};
 use http::uri::InvalidUri;
-use http::{Response, Uri};
-use serde::Deserialize;
+use http::Uri;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+pub use primitive_types::U256;
 
 const ETHERSCAN_URL: &str = "https://api.etherscan.io";
 
-pub struct EtherscanRpc<'a, T> {
-    transport: &'a T,
+/// Etherscan returns at most `page * offset <= 10000` records for one `startblock`.
+/// https://docs.etherscan.io/api-endpoints/accounts#get-a-list-of-normal-transactions-by-address
+const MAX_RESULT_WINDOW: usize = 10_000;
+const TXS_PAGE_SIZE: usize = 1_000;
+/// Etherscan responds with `status: "0"` and this message if there are no records.
+const NO_RECORDS_MESSAGE: &str = "No transactions found";
+/// `balancemulti` accepts up to 20 addresses per request.
+const BALANCEMULTI_LIMIT: usize = 20;
+
+#[derive(Clone)]
+pub struct EtherscanRpc {
+    transport: SharedTransport,
     api_key: String,
     url: String,
+    /// The chain id of the Etherscan V2 multichain API, if used.
+    chain_id: Option<u64>,
+    /// The native coin of the chain.
+    coin: Coin,
 }
 
-impl<'a, T> EtherscanRpc<'a, T>
-where
-    T: HttpTransport + Sync,
-{
-    pub fn with_default_url(transport: &'a T, api_key: String) -> Self {
+impl EtherscanRpc {
+    pub fn with_default_url(transport: SharedTransport, api_key: String) -> Self {
         EtherscanRpc::with_url(transport, api_key, ETHERSCAN_URL.to_string())
             .expect("'ETHERSCAN_URL' is expected to be a valid URL")
     }
 
-    pub fn with_url(transport: &'a T, api_key: String, url: String) -> Result<Self, InvalidUri> {
+    pub fn with_url(
+        transport: SharedTransport,
+        api_key: String,
+        url: String,
+    ) -> Result<Self, InvalidUri> {
         // Check if the given `url` is correct.
         url.parse::<Uri>()?;
         Ok(EtherscanRpc {
             transport,
             api_key,
             url,
+            chain_id: None,
+            coin: Coin::ETH,
         })
     }
 
-    /// Requests the count of the address transactions.
+    /// Creates a client of the given Etherscan-compatible `explorer`.
+    pub fn with_explorer(
+        transport: SharedTransport,
+        api_key: String,
+        explorer: &EtherscanExplorer,
+    ) -> Self {
+        let rpc = EtherscanRpc::with_url(transport, api_key, explorer.url.to_string())
+            .expect("'EtherscanExplorer::url' is expected to be a valid URL");
+        EtherscanRpc {
+            chain_id: explorer.chain_id,
+            coin: explorer.coin(),
+            ..rpc
+        }
+    }
+
+    /// Creates a client of the given `explorer` with the Etherscan-compatible API `urls` instead of its own one,
+    /// failing over from the first one to the others.
+    pub fn with_explorer_urls(
+        transport: SharedTransport,
+        api_key: String,
+        explorer: &EtherscanExplorer,
+        urls: &[String],
+    ) -> Result<Self, InvalidUri> {
+        let (transport, url) = match FailoverTransport::from_urls(transport.clone(), urls)? {
+            Some(failover) => {
+                let failover = failover.with_failure_check(|res| is_rate_limited(res.body()));
+                let url = failover.url().to_string();
+                (Arc::new(failover) as SharedTransport, url)
+            }
+            // Fails on the empty URL.
+            None => (transport, String::new()),
+        };
+        let rpc = EtherscanRpc::with_url(transport, api_key, url)?;
+        Ok(EtherscanRpc {
+            chain_id: explorer.chain_id,
+            coin: explorer.coin(),
+            ..rpc
+        })
+    }
+
+    /// Requests the count of the transactions the address has taken part in:
+    /// normal and internal transactions, and ERC-20, ERC-721, ERC-1155 transfers.
     pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
-        self.txlist(address).await.map(|txs| txs.len())
+        let transfers = self.all_token_transfers(address).await?;
+        self.transaction_count_with_transfers(address, &transfers)
+            .await
     }
 
-    /// Requests the the address info.
+    /// Like [`EtherscanRpc::transaction_count`], but counts the given token `transfers` of the address
+    /// instead of requesting them, e.g. the ones requested for [`TokenBalance::from_transfers`].
+    pub async fn transaction_count_with_transfers(
+        &self,
+        address: &str,
+        transfers: &[TokenTransfer],
+    ) -> Result<usize, HttpError> {
+        let mut hashes: HashSet<String> = self
+            .txlist_stream(address)
+            .map_ok(|tx| tx.hash)
+            .try_collect()
+            .await?;
+        for internal_tx in self.txlistinternal(address).await? {
+            hashes.insert(internal_tx.hash);
+        }
+        hashes.extend(transfers.iter().map(|transfer| transfer.hash.clone()));
+        Ok(hashes.len())
+    }
+
+    /// Requests the native coin balance of the address.
+    pub async fn balance(&self, address: &str) -> Result<Amount, HttpError> {
+        let uri = self.address_request_uri("balance", address, "tag=latest")?;
+        let balance: String = self.get_result(uri).await?;
+        Ok(Amount::from_base_units(
+            parse_u256(&balance)?,
+            self.coin.clone(),
+        ))
+    }
+
+    /// Requests the native coin balances of the given addresses.
+    pub async fn balancemulti(&self, addresses: &[&str]) -> Result<Vec<AccountBalance>, HttpError> {
+        let mut balances = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(BALANCEMULTI_LIMIT) {
+            let uri = self.address_request_uri("balancemulti", &chunk.join(","), "tag=latest")?;
+            let chunk_balances: Vec<BalancemultiItem> = self.get_result(uri).await?;
+            balances.extend(chunk_balances.into_iter().map(|item| AccountBalance {
+                account: item.account,
+                balance: Amount::from_base_units(item.balance, self.coin.clone()),
+            }));
+        }
+        Ok(balances)
+    }
+
+    /// Requests all address transactions, oldest first.
     pub async fn txlist(&self, address: &str) -> Result<Vec<TxListItem>, HttpError> {
-        let action = "txlist";
-        let uri = self.address_request_uri(action, address)?;
-        let res: Response<EtherscanResponse<Vec<TxListItem>>> =
-            self.transport.get_json(uri).await?;
+        self.txlist_stream(address).try_collect().await
+    }
+
+    /// Streams all address transactions, oldest first, requesting them page by page.
+    pub fn txlist_stream<'b>(
+        &'b self,
+        address: &'b str,
+    ) -> impl Stream<Item = Result<TxListItem, HttpError>> + 'b {
+        self.records_stream("txlist", address)
+    }
 
-        // TODO check if the response is OK(200) and if the `EtherscanResponse::message` is "OK".
-        let (_parts, ether_res) = res.into_parts();
-        Ok(ether_res.result)
+    /// Requests all internal transactions of the address, oldest first.
+    pub async fn txlistinternal(&self, address: &str) -> Result<Vec<InternalTx>, HttpError> {
+        self.records_stream("txlistinternal", address)
+            .try_collect()
+            .await
+    }
+
+    /// Requests all ERC-20 token transfers of the address, oldest first.
+    pub async fn tokentx(&self, address: &str) -> Result<Vec<TokenTransfer>, HttpError> {
+        self.token_transfers(TokenStandard::Erc20, address).await
+    }
+
+    /// Requests all ERC-721 token transfers of the address, oldest first.
+    pub async fn tokennfttx(&self, address: &str) -> Result<Vec<TokenTransfer>, HttpError> {
+        self.token_transfers(TokenStandard::Erc721, address).await
+    }
+
+    /// Requests all ERC-1155 token transfers of the address, oldest first.
+    pub async fn token1155tx(&self, address: &str) -> Result<Vec<TokenTransfer>, HttpError> {
+        self.token_transfers(TokenStandard::Erc1155, address).await
+    }
+
+    /// Requests all token transfers of the given `standard`, oldest first.
+    pub async fn token_transfers(
+        &self,
+        standard: TokenStandard,
+        address: &str,
+    ) -> Result<Vec<TokenTransfer>, HttpError> {
+        self.token_transfers_stream(standard, address)
+            .try_collect()
+            .await
+    }
+
+    /// Streams all token transfers of the given `standard`, oldest first.
+    pub fn token_transfers_stream<'b>(
+        &'b self,
+        standard: TokenStandard,
+        address: &'b str,
+    ) -> impl Stream<Item = Result<TokenTransfer, HttpError>> + 'b {
+        self.records_stream(standard.action(), address)
+            .map_ok(move |transfer: TokenTransfer| TokenTransfer {
+                standard,
+                ..transfer
+            })
+    }
+
+    /// Requests the ERC-20, ERC-721 and ERC-1155 transfers of the address, oldest first per standard.
+    pub async fn all_token_transfers(
+        &self,
+        address: &str,
+    ) -> Result<Vec<TokenTransfer>, HttpError> {
+        let mut transfers = Vec::new();
+        for standard in TokenStandard::ALL {
+            transfers.extend(self.token_transfers(standard, address).await?);
+        }
+        Ok(transfers)
+    }
+
+    /// Requests the ERC-20, ERC-721 and ERC-1155 transfers of the address,
+    /// and sums them up into the token balances.
+    pub async fn token_balances(&self, address: &str) -> Result<Vec<TokenBalance>, HttpError> {
+        let transfers = self.all_token_transfers(address).await?;
+        Ok(TokenBalance::from_transfers(address, &transfers))
+    }
+
+    /// Streams all records of the `action`, oldest first, requesting them page by page.
+    ///
+    /// Once the result window of `startblock` is exhausted, the next requests start from the block
+    /// of the last received record, skipping the records of that block received already.
+    fn records_stream<'b, R>(
+        &'b self,
+        action: &'static str,
+        address: &'b str,
+    ) -> impl Stream<Item = Result<R, HttpError>> + 'b
+    where
+        R: EtherscanRecord + DeserializeOwned + 'b,
+    {
+        let first_page = Some(PageQuery {
+            startblock: 0,
+            page: 1,
+        });
+        let state = (first_page, LastBlock::default());
+
+        stream::try_unfold(state, move |(query, mut last_block)| async move {
+            let query = match query {
+                Some(query) => query,
+                None => return Ok::<_, HttpError>(None),
+            };
+
+            let page: Vec<R> = self.records_page(action, address, &query).await?;
+            let next_query = query.next(&page)?;
+
+            let records: Vec<_> = page
+                .into_iter()
+                .filter(|record| last_block.insert(record))
+                .collect();
+
+            let records = stream::iter(records.into_iter().map(Ok));
+            Ok(Some((records, (next_query, last_block))))
+        })
+        .try_flatten()
     }
 
-    fn address_request_uri(&self, action: &str, address: &str) -> Result<Uri, InvalidUri> {
+    /// Requests one page of the `action` records, oldest first.
+    async fn records_page<R>(
+        &self,
+        action: &str,
+        address: &str,
+        query: &PageQuery,
+    ) -> Result<Vec<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let PageQuery { startblock, page } = query;
+        let params = format!("startblock={startblock}&page={page}&offset={TXS_PAGE_SIZE}&sort=asc");
+        let uri = self.address_request_uri(action, address, &params)?;
+        self.get_result(uri).await
+    }
+
+    async fn get_result<R>(&self, uri: Uri) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned + Default,
+    {
+        let mut request = Request::get(uri).body(Vec::new())?;
+        // The API errors, e.g. the rate limits, are answered with `200 OK`, and shouldn't be cached.
+        request
+            .extensions_mut()
+            .insert(CacheCheck(is_result_response));
+        let res = self.transport.send(request).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        let ether_res: EtherscanResponse<Value> =
+            serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        match (ether_res.status.as_str(), ether_res.message.as_str()) {
+            ("1", _) => serde_json::from_value(ether_res.result)
+                .map_err(|e| HttpError::InvalidPayload(e.to_string())),
+            (_, NO_RECORDS_MESSAGE) => Ok(R::default()),
+            // `result` contains an error description, e.g. "Invalid API Key".
+            (_, message) => Err(HttpError::ApiError(format!(
+                "{message}: {}",
+                ether_res.result
+            ))),
+        }
+    }
+
+    fn address_request_uri(
+        &self,
+        action: &str,
+        address: &str,
+        params: &str,
+    ) -> Result<Uri, InvalidUri> {
         let url = &self.url;
         let apikey = &self.api_key;
-        format!("{url}/api?module=account&action={action}&apikey={apikey}&address={address}")
+        let api = match self.chain_id {
+            Some(chain_id) => format!("{url}/v2/api?chainid={chain_id}&"),
+            None => format!("{url}/api?"),
+        };
+        format!("{api}module=account&action={action}&apikey={apikey}&address={address}&{params}")
             .parse()
     }
 }
 
+/// A page request of the list actions, e.g. `txlist`.
+#[derive(Clone, Copy, Debug)]
+struct PageQuery {
+    startblock: u64,
+    /// Starts from `1`.
+    page: usize,
+}
+
+impl PageQuery {
+    /// Returns the query of the page that follows the given one, or `None` if it was the last page.
+    /// A result window of one block is an error, as the next window would start from the same block.
+    fn next<R: EtherscanRecord>(&self, page: &[R]) -> Result<Option<PageQuery>, HttpError> {
+        if page.len() < TXS_PAGE_SIZE {
+            return Ok(None);
+        }
+        if (self.page + 1) * TXS_PAGE_SIZE <= MAX_RESULT_WINDOW {
+            return Ok(Some(PageQuery {
+                startblock: self.startblock,
+                page: self.page + 1,
+            }));
+        }
+        // The result window is exhausted, start a new one from the last block.
+        let last_block = match page.last() {
+            Some(record) => record.block_number(),
+            None => return Ok(None),
+        };
+        if last_block == self.startblock {
+            return Err(HttpError::ApiError(format!(
+                "Block {last_block} has more than {MAX_RESULT_WINDOW} records"
+            )));
+        }
+        Ok(Some(PageQuery {
+            startblock: last_block,
+            page: 1,
+        }))
+    }
+}
+
+/// The ids of the records received of the last block, across the pages and the result windows.
+#[derive(Default)]
+struct LastBlock {
+    number: u64,
+    ids: HashSet<String>,
+}
+
+impl LastBlock {
+    /// Remembers the `record`, the records being ordered by block. Returns `false` if it was received already.
+    fn insert<R: EtherscanRecord>(&mut self, record: &R) -> bool {
+        let number = record.block_number();
+        if number != self.number {
+            self.number = number;
+            self.ids.clear();
+        }
+        self.ids.insert(record.id())
+    }
+}
+
+/// A record of the list actions.
+trait EtherscanRecord {
+    fn block_number(&self) -> u64;
+
+    /// Identifies the record within its block.
+    fn id(&self) -> String;
+}
+
 #[derive(Deserialize)]
 struct EtherscanResponse<T> {
-    #[allow(dead_code)]
     status: String,
-    #[allow(dead_code)]
     message: String,
     result: T,
 }
 
-/// We're currently interested in the count of transactions, so leave the structure fields empty.
+#[derive(Clone, Debug)]
+pub struct AccountBalance {
+    pub account: String,
+    pub balance: Amount,
+}
+
 #[derive(Deserialize)]
-pub struct TxListItem {}
+struct BalancemultiItem {
+    account: String,
+    #[serde(deserialize_with = "deserialize_u256")]
+    balance: U256,
+}
+
+/// https://docs.etherscan.io/api-endpoints/accounts#get-a-list-of-normal-transactions-by-address
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxListItem {
+    pub hash: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub block_number: u64,
+    #[serde(rename = "timeStamp", deserialize_with = "deserialize_from_str")]
+    pub timestamp: u64,
+    pub from: String,
+    /// `None` for contract creation transactions.
+    #[serde(deserialize_with = "deserialize_optional_address")]
+    pub to: Option<String>,
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub value: U256,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub gas: u64,
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub gas_price: U256,
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub is_error: bool,
+    /// E.g. `0xa9059cbb`, or `0x` for plain transfers.
+    #[serde(default)]
+    pub method_id: String,
+}
+
+impl EtherscanRecord for TxListItem {
+    fn block_number(&self) -> u64 { self.block_number }
+
+    fn id(&self) -> String { self.hash.clone() }
+}
+
+/// https://docs.etherscan.io/api-endpoints/accounts#get-a-list-of-internal-transactions-by-address
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalTx {
+    /// The hash of the parent transaction.
+    pub hash: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub block_number: u64,
+    #[serde(rename = "timeStamp", deserialize_with = "deserialize_from_str")]
+    pub timestamp: u64,
+    pub from: String,
+    /// `None` for contract creation calls.
+    #[serde(deserialize_with = "deserialize_optional_address")]
+    pub to: Option<String>,
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub value: U256,
+    /// E.g. `call`, `create`.
+    #[serde(rename = "type")]
+    pub call_type: String,
+    #[serde(default)]
+    pub trace_id: String,
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub is_error: bool,
+}
+
+impl EtherscanRecord for InternalTx {
+    fn block_number(&self) -> u64 { self.block_number }
+
+    fn id(&self) -> String { format!("{}/{}", self.hash, self.trace_id) }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TokenStandard {
+    #[default]
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+impl TokenStandard {
+    pub const ALL: [TokenStandard; 3] = [
+        TokenStandard::Erc20,
+        TokenStandard::Erc721,
+        TokenStandard::Erc1155,
+    ];
+
+    fn action(&self) -> &'static str {
+        match self {
+            TokenStandard::Erc20 => "tokentx",
+            TokenStandard::Erc721 => "tokennfttx",
+            TokenStandard::Erc1155 => "token1155tx",
+        }
+    }
+}
+
+/// An ERC-20 (`tokentx`), ERC-721 (`tokennfttx`) or ERC-1155 (`token1155tx`) transfer.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransfer {
+    #[serde(skip)]
+    pub standard: TokenStandard,
+    pub hash: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub block_number: u64,
+    #[serde(rename = "timeStamp", deserialize_with = "deserialize_from_str")]
+    pub timestamp: u64,
+    pub from: String,
+    pub to: String,
+    pub contract_address: String,
+    /// The transferred amount of an ERC-20 token.
+    #[serde(default, deserialize_with = "deserialize_optional_u256")]
+    pub value: Option<U256>,
+    /// The id of an ERC-721 or ERC-1155 token.
+    #[serde(
+        rename = "tokenID",
+        default,
+        deserialize_with = "deserialize_optional_u256"
+    )]
+    pub token_id: Option<U256>,
+    /// The transferred amount of an ERC-1155 token.
+    #[serde(default, deserialize_with = "deserialize_optional_u256")]
+    pub token_value: Option<U256>,
+    pub token_name: String,
+    pub token_symbol: String,
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub token_decimal: Option<u8>,
+    /// The index of the `Transfer` event in the block, if the explorer returns it.
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub log_index: Option<u64>,
+}
+
+impl TokenTransfer {
+    /// Returns the transferred amount. An ERC-721 transfer always moves one token.
+    pub fn amount(&self) -> U256 { self.token_value.or(self.value).unwrap_or_else(U256::one) }
+}
+
+impl EtherscanRecord for TokenTransfer {
+    fn block_number(&self) -> u64 { self.block_number }
+
+    /// One transaction may contain several transfers, told apart by their `Transfer` events.
+    /// An ERC-1155 `TransferBatch` event moves several token ids.
+    fn id(&self) -> String {
+        let token_id = self.token_id.unwrap_or_default();
+        match self.log_index {
+            Some(log_index) => format!("{}/{log_index}/{token_id}", self.hash),
+            // The explorers that don't return the log index.
+            None => format!(
+                "{}/{}/{token_id}/{}/{}/{}",
+                self.hash,
+                self.contract_address,
+                self.from,
+                self.to,
+                self.amount()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TokenBalance {
+    pub standard: TokenStandard,
+    /// Lowercase contract address.
+    pub contract_address: String,
+    /// The id of an ERC-721 or ERC-1155 token.
+    pub token_id: Option<U256>,
+    pub token_symbol: String,
+    pub token_decimal: Option<u8>,
+    /// In the units of the token, without decimals for the NFTs.
+    pub balance: Amount,
+}
+
+impl TokenBalance {
+    /// Sums up the incoming and outgoing `transfers` of the `address` per token.
+    /// Returns the non-zero balances only.
+    ///
+    /// Note the balances may differ from the actual ones for tokens that change balances
+    /// without emitting `Transfer` events, e.g. rebasing tokens.
+    pub fn from_transfers(address: &str, transfers: &[TokenTransfer]) -> Vec<TokenBalance> {
+        let address = address.to_lowercase();
+
+        // (contract, token id) => (incoming, outgoing, the last transfer)
+        let mut sums: BTreeMap<(String, Option<U256>), (U256, U256, &TokenTransfer)> =
+            BTreeMap::new();
+        for transfer in transfers {
+            let key = (transfer.contract_address.to_lowercase(), transfer.token_id);
+            let (incoming, outgoing, _) =
+                sums.entry(key)
+                    .or_insert((U256::zero(), U256::zero(), transfer));
+
+            if transfer.to.to_lowercase() == address {
+                *incoming = incoming.saturating_add(transfer.amount());
+            }
+            if transfer.from.to_lowercase() == address {
+                *outgoing = outgoing.saturating_add(transfer.amount());
+            }
+        }
+
+        sums.into_iter()
+            .map(
+                |((contract_address, token_id), (incoming, outgoing, transfer))| {
+                    let token = Coin::token(
+                        transfer.token_symbol.clone(),
+                        transfer.token_decimal.unwrap_or_default(),
+                    );
+                    TokenBalance {
+                        standard: transfer.standard,
+                        contract_address,
+                        token_id,
+                        token_symbol: transfer.token_symbol.clone(),
+                        token_decimal: transfer.token_decimal,
+                        balance: Amount::from_base_units(incoming.saturating_sub(outgoing), token),
+                    }
+                },
+            )
+            .filter(|balance| !balance.balance.is_zero())
+            .collect()
+    }
+}
+
+/// Whether the response `body` has the `result` of the request, or there are no records, not an API error.
+fn is_result_response(body: &[u8]) -> bool {
+    match serde_json::from_slice::<EtherscanResponse<Value>>(body) {
+        Ok(res) => res.status == "1" || res.message == NO_RECORDS_MESSAGE,
+        Err(_) => false,
+    }
+}
+
+/// Whether the response `body` is a rate limit error, which Etherscan answers with `200 OK`,
+/// e.g. "Max rate limit reached" or "Max calls per sec rate limit reached (5/sec)".
+fn is_rate_limited(body: &[u8]) -> bool {
+    match serde_json::from_slice::<EtherscanResponse<Value>>(body) {
+        Ok(res) if res.status != "1" => {
+            let result = res.result.as_str().unwrap_or_default();
+            [res.message.as_str(), result]
+                .iter()
+                .any(|text| text.to_lowercase().contains("rate limit"))
+        }
+        _ => false,
+    }
+}
+
+fn parse_u256(s: &str) -> Result<U256, HttpError> {
+    // `U256::from_str` expects a hex string.
+    U256::from_dec_str(s)
+        .map_err(|e| HttpError::InvalidPayload(format!("Invalid number '{s}': {e:?}")))
+}
+
+fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_u256(&s).map_err(D::Error::custom)
+}
+
+/// Parses an optional number, treating an empty string as `None`.
+fn deserialize_optional_u256<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => parse_u256(&s).map(Some).map_err(D::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(String::deserialize(deserializer)? == "1")
+}
+
+fn deserialize_optional_address<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let address = String::deserialize(deserializer)?;
+    Ok(Some(address).filter(|address| !address.is_empty()))
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::CachingTransport;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
     use std::env;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "0x60c2A43Cc69658eC4b02a65A07623D7192166F4e";
+
+    fn stub_tx(hash: usize, block_number: u64) -> String {
+        format!(
+            r#"{{"blockNumber":"{block_number}","timeStamp":"1671000000","hash":"0x{hash:x}","nonce":"1","blockHash":"0x00",
+            "transactionIndex":"0","from":"{ADDRESS}","to":"","value":"1000000000000000000000","gas":"21000",
+            "gasPrice":"14000000000","isError":"0","txreceipt_status":"1","input":"0x","contractAddress":"",
+            "cumulativeGasUsed":"21000","gasUsed":"21000","confirmations":"100","methodId":"0x","functionName":""}}"#
+        )
+    }
+
+    fn stub_page(txs: &[String]) -> String {
+        format!(
+            r#"{{"status":"1","message":"OK","result":[{}]}}"#,
+            txs.join(",")
+        )
+    }
+
+    fn page_path(action: &str, query: PageQuery) -> String {
+        let PageQuery { startblock, page } = query;
+        format!("/api?module=account&action={action}&apikey=KEY&address={ADDRESS}&startblock={startblock}&page={page}&offset={TXS_PAGE_SIZE}&sort=asc")
+    }
+
+    const FIRST_PAGE: PageQuery = PageQuery {
+        startblock: 0,
+        page: 1,
+    };
+
+    #[tokio::test]
+    async fn test_etherscan_txlist_stub() {
+        // One transaction per block, so the last block of the first window is `MAX_RESULT_WINDOW - 1`.
+        let txs: Vec<_> = (0..MAX_RESULT_WINDOW + 2)
+            .map(|i| stub_tx(i, i as u64))
+            .collect();
+
+        let mut transport = StubTransport::default();
+        for (i, page) in txs[..MAX_RESULT_WINDOW].chunks(TXS_PAGE_SIZE).enumerate() {
+            let query = PageQuery {
+                startblock: 0,
+                page: i + 1,
+            };
+            transport = transport.with_response(&page_path("txlist", query), &stub_page(page));
+        }
+        // The second window starts from the last block of the first one.
+        let query = PageQuery {
+            startblock: MAX_RESULT_WINDOW as u64 - 1,
+            page: 1,
+        };
+        transport = transport.with_response(
+            &page_path("txlist", query),
+            &stub_page(&txs[MAX_RESULT_WINDOW - 1..]),
+        );
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        let actual = rpc.txlist(ADDRESS).await.unwrap();
+        assert_eq!(actual.len(), MAX_RESULT_WINDOW + 2);
+        assert_eq!(actual[MAX_RESULT_WINDOW + 1].hash, "0x2711");
+        assert_eq!(actual[0].value, U256::exp10(21));
+        assert_eq!(actual[0].to, None);
+        assert!(!actual[0].is_error);
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_block_across_pages_stub() {
+        // The last block of the first window spans its last two pages.
+        let last_block = (MAX_RESULT_WINDOW - TXS_PAGE_SIZE - TXS_PAGE_SIZE / 2) as u64;
+        let txs: Vec<_> = (0..MAX_RESULT_WINDOW + 2)
+            .map(|i| stub_tx(i, (i as u64).min(last_block)))
+            .collect();
+
+        let mut transport = StubTransport::default();
+        for (i, page) in txs[..MAX_RESULT_WINDOW].chunks(TXS_PAGE_SIZE).enumerate() {
+            let query = PageQuery {
+                startblock: 0,
+                page: i + 1,
+            };
+            transport = transport.with_response(&page_path("txlist", query), &stub_page(page));
+        }
+        let window = &txs[last_block as usize..];
+        for (i, page) in window.chunks(TXS_PAGE_SIZE).enumerate() {
+            let query = PageQuery {
+                startblock: last_block,
+                page: i + 1,
+            };
+            transport = transport.with_response(&page_path("txlist", query), &stub_page(page));
+        }
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        let actual = rpc.txlist(ADDRESS).await.unwrap();
+        assert_eq!(actual.len(), MAX_RESULT_WINDOW + 2);
+        assert_eq!(actual[MAX_RESULT_WINDOW + 1].hash, "0x2711");
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_one_block_window_stub() {
+        let txs: Vec<_> = (0..MAX_RESULT_WINDOW).map(|i| stub_tx(i, 7)).collect();
+
+        let mut transport = StubTransport::default();
+        for startblock in [0, 7] {
+            for (i, page) in txs.chunks(TXS_PAGE_SIZE).enumerate() {
+                let query = PageQuery {
+                    startblock,
+                    page: i + 1,
+                };
+                transport = transport.with_response(&page_path("txlist", query), &stub_page(page));
+            }
+        }
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        // The second window starts from block 7 again, instead of being requested over and over.
+        let err = rpc.txlist(ADDRESS).await.err().unwrap();
+        assert!(matches!(err, HttpError::ApiError(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_tokens_stub() {
+        const TOKEN: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        const NFT: &str = "0x06012c8cf97BEaD5deAe237070F9587f8E7A266d";
+        const OTHER: &str = "0x4e83362442b8d1bec281594cea3050c8eb01311c";
+
+        let erc20 = format!(
+            r#"{{"blockNumber":"1","timeStamp":"1","hash":"0x01","from":"{OTHER}","to":"{}","contractAddress":"{TOKEN}",
+            "value":"5000000","tokenName":"Tether USD","tokenSymbol":"USDT","tokenDecimal":"6","logIndex":"3"}},
+            {{"blockNumber":"2","timeStamp":"2","hash":"0x02","from":"{}","to":"{OTHER}","contractAddress":"{TOKEN}",
+            "value":"1500000","tokenName":"Tether USD","tokenSymbol":"USDT","tokenDecimal":"6","logIndex":"7"}},
+            {{"blockNumber":"2","timeStamp":"2","hash":"0x02","from":"{}","to":"{OTHER}","contractAddress":"{TOKEN}",
+            "value":"1500000","tokenName":"Tether USD","tokenSymbol":"USDT","tokenDecimal":"6","logIndex":"8"}}"#,
+            ADDRESS.to_lowercase(),
+            ADDRESS.to_lowercase(),
+            ADDRESS.to_lowercase(),
+        );
+        let erc721 = format!(
+            r#"{{"blockNumber":"3","timeStamp":"3","hash":"0x03","from":"{OTHER}","to":"{ADDRESS}","contractAddress":"{NFT}",
+            "tokenID":"1337","tokenName":"CryptoKitties","tokenSymbol":"CK","tokenDecimal":"0"}}"#
+        );
+        let empty = r#"{"status":"0","message":"No transactions found","result":[]}"#;
+
+        let transport = StubTransport::default()
+            .with_response(&page_path("txlist", FIRST_PAGE), empty)
+            .with_response(&page_path("txlistinternal", FIRST_PAGE), empty)
+            .with_response(&page_path("tokentx", FIRST_PAGE), &stub_page(&[erc20]))
+            .with_response(&page_path("tokennfttx", FIRST_PAGE), &stub_page(&[erc721]))
+            .with_response(&page_path("token1155tx", FIRST_PAGE), empty)
+            .with_response(
+                &format!(
+                    "/api?module=account&action=balance&apikey=KEY&address={ADDRESS}&tag=latest"
+                ),
+                r#"{"status":"1","message":"OK","result":"40891626854930000000000"}"#,
+            );
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        // The address has no normal transactions, but token transfers only.
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 3);
+        let transfers = rpc.all_token_transfers(ADDRESS).await.unwrap();
+        assert_eq!(transfers.len(), 4);
+        assert_eq!(
+            rpc.transaction_count_with_transfers(ADDRESS, &transfers)
+                .await
+                .unwrap(),
+            3
+        );
+        // The equal transfers of one transaction are different events.
+        assert_ne!(transfers[1].id(), transfers[2].id());
+        assert_eq!(
+            rpc.balance(ADDRESS).await.unwrap().to_string(),
+            "40891.62685493 ETH"
+        );
+
+        let balances = rpc.token_balances(ADDRESS).await.unwrap();
+        assert_eq!(balances.len(), 2);
+        let usdt = balances
+            .iter()
+            .find(|balance| balance.token_symbol == "USDT")
+            .unwrap();
+        assert_eq!(usdt.balance.to_string(), "2 USDT");
+        assert_eq!(usdt.token_decimal, Some(6));
+        let kitty = balances
+            .iter()
+            .find(|balance| balance.standard == TokenStandard::Erc721)
+            .unwrap();
+        assert_eq!(kitty.token_id, Some(U256::from(1337)));
+        assert_eq!(kitty.balance.raw(), U256::one());
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_v2_stub() {
+        let avalanche = EtherscanExplorer::by_coin_type(10009000).unwrap();
+        let transport = StubTransport::default().with_response(
+            &format!("/v2/api?chainid=43114&module=account&action=balance&apikey=KEY&address={ADDRESS}&tag=latest"),
+            r#"{"status":"1","message":"OK","result":"1000"}"#,
+        );
+        let rpc = EtherscanRpc::with_explorer(Arc::new(transport), "KEY".to_string(), avalanche);
+
+        let balance = rpc.balance(ADDRESS).await.unwrap();
+        assert_eq!(balance.to_unit_string("wei").unwrap(), "1000");
+        assert_eq!(balance.coin().symbol, "AVAX");
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_failover_stub() {
+        let avalanche = EtherscanExplorer::by_coin_type(10009000).unwrap();
+        let query = format!(
+            "/v2/api?chainid=43114&module=account&action=balance&apikey=KEY&address={ADDRESS}&tag=latest"
+        );
+        let transport = StubTransport::default()
+            .with_status(&format!("/a{query}"), StatusCode::SERVICE_UNAVAILABLE)
+            // The rate limits are answered with `200 OK`.
+            .with_response(
+                &format!("/b{query}"),
+                r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#,
+            )
+            .with_response(
+                &format!("/c{query}"),
+                r#"{"status":"1","message":"OK","result":"1000"}"#,
+            );
+        let urls = [
+            "https://a.example/a",
+            "https://b.example/b",
+            "https://c.example/c",
+        ]
+        .map(String::from);
+        let rpc = EtherscanRpc::with_explorer_urls(
+            Arc::new(transport),
+            "KEY".to_string(),
+            avalanche,
+            &urls,
+        )
+        .unwrap();
+
+        let balance = rpc.balance(ADDRESS).await.unwrap();
+        assert_eq!(balance.to_unit_string("wei").unwrap(), "1000");
+        assert!(EtherscanRpc::with_explorer_urls(
+            Arc::new(StubTransport::default()),
+            "KEY".to_string(),
+            avalanche,
+            &[]
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_api_error_stub() {
+        let transport = StubTransport::default().with_response(
+            &page_path("tokentx", FIRST_PAGE),
+            r#"{"status":"0","message":"NOTOK","result":"Invalid API Key"}"#,
+        );
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        let err = rpc.transaction_count(ADDRESS).await.err().unwrap();
+        assert!(matches!(err, HttpError::ApiError(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_etherscan_rate_limit_not_cached() {
+        let balance_path =
+            format!("/api?module=account&action=balance&apikey=KEY&address={ADDRESS}&tag=latest");
+        let transport = StubTransport::default()
+            .with_response(
+                &balance_path,
+                r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#,
+            )
+            .with_response(&page_path("txlist", FIRST_PAGE), &stub_page(&[]));
+        let dir = env::temp_dir().join(format!("etherscan-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let transport = CachingTransport::new(Arc::new(transport), dir.clone());
+        let rpc = EtherscanRpc::with_default_url(Arc::new(transport), "KEY".to_string());
+
+        let err = rpc.balance(ADDRESS).await.err().unwrap();
+        assert!(matches!(err, HttpError::ApiError(_)), "{err}");
+        assert!(!dir.exists());
+
+        // The results are cached.
+        assert!(rpc.txlist(ADDRESS).await.unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 
     #[tokio::test]
     async fn test_etherscan_rpc() {
         let api_key = env::var("ETHERSCAN_APIKEY")
             .expect("'ETHERSCAN_APIKEY' environment variable is not set");
 
-        let transport = crate::http::HttpBuilder::build();
-        let rpc = EtherscanRpc::with_default_url(&transport, api_key);
+        let transport = crate::http::HttpBuilder::build(Default::default());
+        let rpc = EtherscanRpc::with_default_url(transport, api_key);
 
         let actual = rpc
             .transaction_count("0x60c2A43Cc69658eC4b02a65A07623D7192166F4e")
@@ -0,0 +1,157 @@
+use crate::amount::Coin;
+use std::borrow::Cow;
+
+/// An Etherscan-compatible block explorer of an EVM chain.
+#[derive(Clone, Copy, Debug)]
+pub struct EtherscanExplorer {
+    /// The `trustwallet/wallet-core` coin type.
+    pub coin_type: u32,
+    /// The display symbol of the native coin, e.g. `MATIC`.
+    pub symbol: &'static str,
+    /// The API base URL, e.g. `https://api.polygonscan.com`.
+    pub url: &'static str,
+    /// If set, the explorer is accessed through the Etherscan V2 multichain API with the `chainid` parameter.
+    /// https://docs.etherscan.io/etherscan-v2
+    pub chain_id: Option<u64>,
+    /// The environment variable to read the explorer API key from.
+    pub api_key_env: &'static str,
+    /// The environment variable to read the comma-separated Etherscan-compatible API URLs from,
+    /// to use instead of the `url`, the first one is preferred.
+    pub urls_env: &'static str,
+}
+
+impl EtherscanExplorer {
+    /// Returns the explorer by the `trustwallet/wallet-core` coin type.
+    pub fn by_coin_type(coin_type: u32) -> Option<&'static EtherscanExplorer> {
+        EXPLORERS
+            .iter()
+            .find(|explorer| explorer.coin_type == coin_type)
+    }
+
+    pub fn all() -> &'static [EtherscanExplorer] { EXPLORERS }
+
+    /// Returns the units of the native coin, which has 18 decimals on all EVM chains.
+    pub fn coin(&self) -> Coin {
+        Coin {
+            symbol: Cow::Borrowed(self.symbol),
+            ..Coin::ETH
+        }
+    }
+}
+
+const ETHERSCAN_V2_URL: &str = "https://api.etherscan.io";
+
+const EXPLORERS: &[EtherscanExplorer] = &[
+    EtherscanExplorer {
+        coin_type: 60,
+        symbol: "ETH",
+        url: "https://api.etherscan.io",
+        chain_id: None,
+        api_key_env: "ETHERSCAN_APIKEY",
+        urls_env: "ETHERSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 966,
+        symbol: "MATIC",
+        url: "https://api.polygonscan.com",
+        chain_id: None,
+        api_key_env: "POLYGONSCAN_APIKEY",
+        urls_env: "POLYGONSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 20000714,
+        symbol: "BNB",
+        url: "https://api.bscscan.com",
+        chain_id: None,
+        api_key_env: "BSCSCAN_APIKEY",
+        urls_env: "BSCSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10042221,
+        symbol: "ETH (Arbitrum)",
+        url: "https://api.arbiscan.io",
+        chain_id: None,
+        api_key_env: "ARBISCAN_APIKEY",
+        urls_env: "ARBISCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000070,
+        symbol: "ETH (Optimism)",
+        url: "https://api-optimistic.etherscan.io",
+        chain_id: None,
+        api_key_env: "OPTIMISTIC_ETHERSCAN_APIKEY",
+        urls_env: "OPTIMISTIC_ETHERSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10009000,
+        symbol: "AVAX",
+        url: ETHERSCAN_V2_URL,
+        chain_id: Some(43114),
+        api_key_env: "ETHERSCAN_APIKEY",
+        urls_env: "SNOWTRACE_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000250,
+        symbol: "FTM",
+        url: "https://api.ftmscan.com",
+        chain_id: None,
+        api_key_env: "FTMSCAN_APIKEY",
+        urls_env: "FTMSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 52752,
+        symbol: "CELO",
+        url: "https://api.celoscan.io",
+        chain_id: None,
+        api_key_env: "CELOSCAN_APIKEY",
+        urls_env: "CELOSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000100,
+        symbol: "xDAI",
+        url: "https://api.gnosisscan.io",
+        chain_id: None,
+        api_key_env: "GNOSISSCAN_APIKEY",
+        urls_env: "GNOSISSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000025,
+        symbol: "CRO (Cronos)",
+        url: "https://api.cronoscan.com",
+        chain_id: None,
+        api_key_env: "CRONOSCAN_APIKEY",
+        urls_env: "CRONOSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10001284,
+        symbol: "GLMR",
+        url: "https://api-moonbeam.moonscan.io",
+        chain_id: None,
+        api_key_env: "MOONSCAN_APIKEY",
+        urls_env: "MOONBEAM_MOONSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10001285,
+        symbol: "MOVR",
+        url: "https://api-moonriver.moonscan.io",
+        chain_id: None,
+        api_key_env: "MOONSCAN_APIKEY",
+        urls_env: "MOONRIVER_MOONSCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000288,
+        symbol: "ETH (Boba)",
+        url: "https://api.bobascan.com",
+        chain_id: None,
+        api_key_env: "BOBASCAN_APIKEY",
+        urls_env: "BOBASCAN_URLS",
+    },
+    EtherscanExplorer {
+        coin_type: 10000280,
+        symbol: "ETH (zkSync)",
+        url: ETHERSCAN_V2_URL,
+        chain_id: Some(324),
+        api_key_env: "ETHERSCAN_APIKEY",
+        urls_env: "ZKSYNC_EXPLORER_URLS",
+    },
+];
@@ -0,0 +1,359 @@
+use crate::abi::{self, AbiError, Address, ParamType, Token};
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
+use crate::json_rpc;
+use crate::token_list::TokenInfo;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+pub use primitive_types::U256;
+
+/// Multicall3 is deployed at the same address on most EVM chains: https://www.multicall3.com
+const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+/// The number of calls aggregated into one `eth_call`, to keep the request within the gas limit.
+const MULTICALL_BATCH_SIZE: usize = 100;
+
+impl From<AbiError> for HttpError {
+    fn from(e: AbiError) -> Self { HttpError::InvalidPayload(e.to_string()) }
+}
+
+/// A client of an Ethereum JSON-RPC endpoint of any EVM chain.
+/// https://ethereum.org/en/developers/docs/apis/json-rpc
+#[derive(Clone)]
+pub struct EvmRpc {
+    transport: SharedTransport,
+    uri: Uri,
+    multicall: Address,
+}
+
+impl EvmRpc {
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        Ok(EvmRpc {
+            transport,
+            uri: url.parse()?,
+            multicall: abi::parse_address(MULTICALL3_ADDRESS)
+                .expect("'MULTICALL3_ADDRESS' is expected to be a valid address"),
+        })
+    }
+
+    /// Requests the chain id, see https://chainlist.org
+    pub async fn chain_id(&self) -> Result<u64, HttpError> {
+        let chain_id: String = self.request("eth_chainId", json!([])).await?;
+        let chain_id = parse_quantity(&chain_id)?;
+        if chain_id > U256::from(u64::MAX) {
+            return Err(HttpError::InvalidPayload(format!(
+                "Chain id is out of range: {chain_id}"
+            )));
+        }
+        Ok(chain_id.as_u64())
+    }
+
+    /// Requests the native coin balance of the address.
+    /// The `coin` is the native coin of the chain, e.g. [`Coin::ETH`].
+    pub async fn balance(&self, address: &str, coin: Coin) -> Result<Amount, HttpError> {
+        let balance: String = self
+            .request("eth_getBalance", json!([address, "latest"]))
+            .await?;
+        Ok(Amount::from_base_units(parse_quantity(&balance)?, coin))
+    }
+
+    /// Executes a read-only call of the contract at the latest block, returns the call result.
+    pub async fn eth_call(&self, to: &Address, data: &[u8]) -> Result<Vec<u8>, HttpError> {
+        let call = json!({
+            "to": format!("0x{}", hex::encode(to)),
+            "data": format!("0x{}", hex::encode(data)),
+        });
+        let result: String = self.request("eth_call", json!([call, "latest"])).await?;
+        parse_data(&result)
+    }
+
+    /// Executes the read-only `calls` through Multicall3 `aggregate3`, `MULTICALL_BATCH_SIZE` calls per request.
+    /// Returns the result of every call in the same order, or `None` if the call has reverted.
+    pub async fn multicall(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+    ) -> Result<Vec<Option<Vec<u8>>>, HttpError> {
+        let result_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])));
+
+        let mut results = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(MULTICALL_BATCH_SIZE) {
+            let calls = chunk
+                .iter()
+                .map(|(target, data)| {
+                    // `allowFailure` is set so that one broken token doesn't revert the whole batch.
+                    Token::Tuple(vec![
+                        Token::Address(*target),
+                        Token::Bool(true),
+                        Token::Bytes(data.clone()),
+                    ])
+                })
+                .collect();
+            let data = abi::encode_call(abi::AGGREGATE3, &[Token::Array(calls)]);
+            let output = self.eth_call(&self.multicall, &data).await?;
+
+            let chunk_results = abi::decode(std::slice::from_ref(&result_type), &output)?
+                .into_iter()
+                .next()
+                .and_then(Token::into_tokens)
+                .ok_or(AbiError::UnexpectedEnd)?;
+            if chunk_results.len() != chunk.len() {
+                return Err(HttpError::InvalidPayload(format!(
+                    "Expected {} multicall results, found {}",
+                    chunk.len(),
+                    chunk_results.len()
+                )));
+            }
+            for result in chunk_results {
+                results.push(decode_call_result(result)?);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Requests `balanceOf` of the given ERC-20 `tokens`, and returns the non-zero holdings of the address.
+    /// The `decimals` and `symbol` of the held tokens are read from the contracts,
+    /// falling back to the token list values if the contract doesn't implement them.
+    pub async fn token_balances(
+        &self,
+        address: &str,
+        tokens: &[TokenInfo],
+    ) -> Result<Vec<Erc20Balance>, HttpError> {
+        let owner = abi::parse_address(address)?;
+        let contracts = tokens
+            .iter()
+            .map(|token| abi::parse_address(&token.address))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let balance_calls: Vec<_> = contracts
+            .iter()
+            .map(|contract| {
+                let data = abi::encode_call(abi::BALANCE_OF, &[Token::Address(owner)]);
+                (*contract, data)
+            })
+            .collect();
+        let held: Vec<_> = self
+            .multicall(&balance_calls)
+            .await?
+            .into_iter()
+            .zip(tokens.iter().zip(contracts))
+            .filter_map(|(result, (token, contract))| {
+                let balance = decode_single(ParamType::Uint, &result?)?.into_uint()?;
+                (!balance.is_zero()).then_some((token, contract, balance))
+            })
+            .collect();
+
+        let metadata_calls: Vec<_> = held
+            .iter()
+            .flat_map(|(_, contract, _)| {
+                [
+                    (*contract, abi::encode_call(abi::DECIMALS, &[])),
+                    (*contract, abi::encode_call(abi::SYMBOL, &[])),
+                ]
+            })
+            .collect();
+        let metadata = self.multicall(&metadata_calls).await?;
+
+        let balances = held
+            .into_iter()
+            .zip(metadata.chunks(2))
+            .map(|((token, _, balance), metadata)| {
+                let decimals = metadata[0]
+                    .as_deref()
+                    .and_then(|data| decode_single(ParamType::Uint, data))
+                    .and_then(Token::into_uint)
+                    .filter(|decimals| *decimals <= U256::from(u8::MAX))
+                    .map(|decimals| decimals.as_u32() as u8)
+                    .unwrap_or(token.decimals);
+                let symbol = metadata[1]
+                    .as_deref()
+                    .and_then(decode_symbol)
+                    .unwrap_or_else(|| token.symbol.clone());
+                Erc20Balance {
+                    contract_address: token.address.clone(),
+                    balance: Amount::from_base_units(balance, Coin::token(symbol, decimals)),
+                }
+            })
+            .collect();
+        Ok(balances)
+    }
+
+    async fn request<R>(&self, method: &str, params: Value) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        json_rpc::request(&self.transport, &self.uri, method, params).await
+    }
+}
+
+/// A non-zero ERC-20 token holding.
+#[derive(Clone, Debug)]
+pub struct Erc20Balance {
+    pub contract_address: String,
+    /// In the units of the token `symbol` and `decimals`.
+    pub balance: Amount,
+}
+
+/// Decodes a `(bool success, bytes returnData)` result of `aggregate3`.
+fn decode_call_result(result: Token) -> Result<Option<Vec<u8>>, AbiError> {
+    let mut fields = result
+        .into_tokens()
+        .ok_or(AbiError::UnexpectedEnd)?
+        .into_iter();
+    let success = fields
+        .next()
+        .and_then(Token::into_bool)
+        .ok_or(AbiError::UnexpectedEnd)?;
+    let data = fields
+        .next()
+        .and_then(Token::into_bytes)
+        .ok_or(AbiError::UnexpectedEnd)?;
+    // A call of an address without a contract succeeds with no data.
+    Ok((success && !data.is_empty()).then_some(data))
+}
+
+fn decode_single(param_type: ParamType, data: &[u8]) -> Option<Token> {
+    abi::decode(&[param_type], data).ok()?.into_iter().next()
+}
+
+/// Decodes a `symbol()` result: a `string`, or a `bytes32` for the older tokens like MKR.
+fn decode_symbol(data: &[u8]) -> Option<String> {
+    if let Some(symbol) = decode_single(ParamType::String, data).and_then(Token::into_string) {
+        return Some(symbol);
+    }
+    let word = data.get(..32)?;
+    let len = word
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(word.len());
+    String::from_utf8(word[..len].to_vec())
+        .ok()
+        .filter(|symbol| !symbol.is_empty())
+}
+
+/// Parses a `0x`-prefixed hex quantity, e.g. `0x1b4`.
+fn parse_quantity(quantity: &str) -> Result<U256, HttpError> {
+    let invalid = || HttpError::InvalidPayload(format!("Invalid quantity: '{quantity}'"));
+    let hex_quantity = quantity.strip_prefix("0x").ok_or_else(invalid)?;
+    U256::from_str_radix(hex_quantity, 16).map_err(|_| invalid())
+}
+
+/// Parses `0x`-prefixed hex data, e.g. `0x0a1b`.
+fn parse_data(data: &str) -> Result<Vec<u8>, HttpError> {
+    let invalid = || HttpError::InvalidPayload(format!("Invalid data: '{data}'"));
+    let hex_data = data.strip_prefix("0x").ok_or_else(invalid)?;
+    hex::decode(hex_data).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use crate::token_list::TokenList;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+    const USDT: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+    const MKR: &str = "0x9f8F72aA9304c8B593d555F12eF6589cC3A579A2";
+
+    /// Answers the `aggregate3` calls as if the address held 5 USDT and 2 MKR,
+    /// where MKR returns its symbol as `bytes32`, and all other calls reverted.
+    fn stub_node(body: &[u8]) -> String {
+        let req: Value = serde_json::from_slice(body).unwrap();
+        let data = req["params"][0]["data"].as_str().unwrap();
+        assert_eq!(req["method"], "eth_call");
+        assert_eq!(
+            req["params"][0]["to"],
+            MULTICALL3_ADDRESS.to_lowercase().as_str()
+        );
+
+        let data = parse_data(data).unwrap();
+        assert_eq!(data[..4], abi::AGGREGATE3);
+        let call_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])));
+        let calls = abi::decode(&[call_type], &data[4..]).unwrap();
+        let calls = calls[0].clone().into_tokens().unwrap();
+
+        let usdt = abi::parse_address(USDT).unwrap();
+        let mkr = abi::parse_address(MKR).unwrap();
+        let results = calls
+            .into_iter()
+            .map(|call| {
+                let call = call.into_tokens().unwrap();
+                let target = match &call[0] {
+                    Token::Address(target) => *target,
+                    _ => panic!("Unexpected call target"),
+                };
+                let data = call[2].clone().into_bytes().unwrap();
+                let selector: [u8; 4] = data[..4].try_into().unwrap();
+                let output = match (target, selector) {
+                    (t, abi::BALANCE_OF) if t == usdt => {
+                        abi::encode(&[Token::Uint(5_000_000.into())])
+                    }
+                    (t, abi::BALANCE_OF) if t == mkr => {
+                        abi::encode(&[Token::Uint(U256::exp10(18) * 2)])
+                    }
+                    (_, abi::BALANCE_OF) => abi::encode(&[Token::Uint(U256::zero())]),
+                    (t, abi::DECIMALS) if t == usdt => abi::encode(&[Token::Uint(6.into())]),
+                    (t, abi::SYMBOL) if t == usdt => abi::encode(&[Token::String("USDT".into())]),
+                    (t, abi::SYMBOL) if t == mkr => {
+                        let mut word = b"MKR".to_vec();
+                        word.resize(32, 0);
+                        word
+                    }
+                    _ => return Token::Tuple(vec![Token::Bool(false), Token::Bytes(Vec::new())]),
+                };
+                Token::Tuple(vec![Token::Bool(true), Token::Bytes(output)])
+            })
+            .collect();
+        let output = abi::encode(&[Token::Array(results)]);
+        format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":"0x{}"}}"#,
+            hex::encode(output)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_evm_token_balances_stub() {
+        let transport = StubTransport::default().with_handler("/", stub_node);
+        let rpc =
+            EvmRpc::with_url(Arc::new(transport), "https://rpc.example.com/".to_string()).unwrap();
+
+        let tokens: Vec<_> = TokenList::bundled().tokens_for_chain(1).cloned().collect();
+        let balances = rpc.token_balances(ADDRESS, &tokens).await.unwrap();
+        assert_eq!(balances.len(), 2);
+
+        assert_eq!(balances[0].contract_address, USDT);
+        assert_eq!(balances[0].balance.to_string(), "5 USDT");
+
+        // `decimals()` reverted, so the token list value is used.
+        assert_eq!(balances[1].contract_address, MKR);
+        assert_eq!(balances[1].balance.to_string(), "2 MKR");
+        assert_eq!(balances[1].balance.coin().decimals, 18);
+    }
+
+    #[tokio::test]
+    async fn test_evm_json_rpc_stub() {
+        let transport = StubTransport::default().with_handler("/", |body| {
+            let req: Value = serde_json::from_slice(body).unwrap();
+            match req["method"].as_str().unwrap() {
+                "eth_chainId" => r#"{"jsonrpc":"2.0","id":1,"result":"0x89"}"#.to_string(),
+                _ => r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Method not found"}}"#
+                    .to_string(),
+            }
+        });
+        let rpc =
+            EvmRpc::with_url(Arc::new(transport), "https://rpc.example.com".to_string()).unwrap();
+
+        assert_eq!(rpc.chain_id().await.unwrap(), 137);
+        let err = rpc.balance(ADDRESS, Coin::ETH).await.err().unwrap();
+        assert!(matches!(err, HttpError::ApiError(_)), "{err}");
+    }
+}
@@ -0,0 +1,360 @@
+//! Fails the requests over across a few endpoints of the same API, e.g. the Esplora instances,
+//! skipping the endpoints that keep failing for a while.
+
+use crate::http::{HttpError, HttpTransport, Request, Response, SharedTransport, StatusCode, Uri};
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The number of the consecutive failures that open the circuit of an endpoint.
+const DEFAULT_MAX_FAILURES: u32 = 3;
+/// The time an endpoint with an open circuit is skipped for.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// An API endpoint: the base URL, as the clients are created with, and its share of the requests.
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    url: String,
+    weight: u32,
+}
+
+impl Endpoint {
+    pub fn new(url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(Endpoint { url, weight: 1 })
+    }
+
+    /// Sets the share of the requests sent to the endpoint by a [`FailoverTransport::weighted`] transport.
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+}
+
+/// An `HttpTransport` that sends the requests of the `primary` endpoint to the first healthy endpoint,
+/// and falls through to the next one on the transport errors, timeouts, rate limits and server errors,
+/// and on the API failures of [`FailoverTransport::with_failure_check`].
+/// The clients are created with the `primary` endpoint URL, the other requests are sent as they are.
+///
+/// An endpoint that fails `max_failures` times in a row is skipped for the `cooldown` (its circuit is open),
+/// unless all endpoints are skipped. Then it's tried again, and skipped again if it fails once more.
+pub struct FailoverTransport {
+    inner: SharedTransport,
+    endpoints: Vec<Endpoint>,
+    /// Whether the requests are distributed across the endpoints by their weights,
+    /// instead of being sent to the first healthy endpoint.
+    weighted: bool,
+    max_failures: u32,
+    cooldown: Duration,
+    /// Tells the API failures among the responses that aren't failures for HTTP, see [`FailoverTransport::with_failure_check`].
+    failure_check: Option<FailureCheck>,
+    state: Mutex<Vec<EndpointState>>,
+}
+
+type FailureCheck = Box<dyn Fn(&Response<Vec<u8>>) -> bool + Send + Sync>;
+
+#[derive(Default)]
+struct EndpointState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// The current weight of the smooth weighted round-robin.
+    current_weight: i64,
+}
+
+impl FailoverTransport {
+    pub fn new(inner: SharedTransport, primary: Endpoint, fallbacks: Vec<Endpoint>) -> Self {
+        let endpoints: Vec<_> = std::iter::once(primary).chain(fallbacks).collect();
+        let state = endpoints.iter().map(|_| EndpointState::default()).collect();
+        FailoverTransport {
+            inner,
+            endpoints,
+            weighted: false,
+            max_failures: DEFAULT_MAX_FAILURES,
+            cooldown: DEFAULT_COOLDOWN,
+            failure_check: None,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Creates a transport failing over from the first of the `urls` to the others.
+    /// Returns `None` if there are no `urls`.
+    pub fn from_urls(inner: SharedTransport, urls: &[String]) -> Result<Option<Self>, InvalidUri> {
+        let mut endpoints = urls
+            .iter()
+            .map(|url| Endpoint::new(url.clone()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        Ok(endpoints
+            .next()
+            .map(|primary| FailoverTransport::new(inner, primary, endpoints.collect())))
+    }
+
+    /// Distributes the requests across the endpoints by their weights, instead of preferring the first ones.
+    pub fn weighted(mut self) -> Self {
+        self.weighted = true;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, max_failures: u32, cooldown: Duration) -> Self {
+        self.max_failures = max_failures;
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Also falls through to the next endpoint on the responses the `check` tells failures,
+    /// e.g. the rate limits an API answers with `200 OK`.
+    pub fn with_failure_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&Response<Vec<u8>>) -> bool + Send + Sync + 'static,
+    {
+        self.failure_check = Some(Box::new(check));
+        self
+    }
+
+    /// Returns the URL of the `primary` endpoint to create the clients with.
+    pub fn url(&self) -> &str { &self.endpoints[0].url }
+
+    /// Returns the endpoint indexes in the order to try them:
+    /// the healthy ones first, then the ones with the open circuits, the soonest to close first.
+    fn endpoint_order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("Endpoints lock is poisoned");
+        let mut order: Vec<_> = (0..self.endpoints.len()).collect();
+        if self.weighted {
+            // Sort by the weights, preferring the next endpoint of the smooth weighted round-robin.
+            order.sort_by_key(|i| std::cmp::Reverse(self.endpoints[*i].weight));
+            let healthy = order
+                .iter()
+                .copied()
+                .filter(|i| !state[*i].is_open(now))
+                .collect();
+            if let Some(next) = next_weighted(&self.endpoints, &mut state, healthy) {
+                order.retain(|i| *i != next);
+                order.insert(0, next);
+            }
+        }
+        // The sort is stable, so the healthy endpoints keep their order.
+        order.sort_by_key(|i| state[*i].open_until.filter(|open_until| *open_until > now));
+        order
+    }
+
+    /// Whether the request should be retried with the next endpoint.
+    fn is_failure(&self, result: &Result<Response<Vec<u8>>, HttpError>) -> bool {
+        match result {
+            Ok(res) => {
+                res.status() == StatusCode::TOO_MANY_REQUESTS
+                    || res.status().is_server_error()
+                    || self.failure_check.as_ref().is_some_and(|check| check(res))
+            }
+            Err(e) => matches!(e, HttpError::TransportError(_) | HttpError::Timeout),
+        }
+    }
+
+    fn record(&self, endpoint: usize, is_success: bool) {
+        let mut state = self.state.lock().expect("Endpoints lock is poisoned");
+        let state = &mut state[endpoint];
+        if is_success {
+            state.consecutive_failures = 0;
+            state.open_until = None;
+        } else {
+            state.consecutive_failures += 1;
+            if state.consecutive_failures >= self.max_failures {
+                state.open_until = Some(Instant::now() + self.cooldown);
+            }
+        }
+    }
+}
+
+impl EndpointState {
+    fn is_open(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|open_until| open_until > now)
+    }
+}
+
+/// Picks the next of the `candidates` by the smooth weighted round-robin, as nginx does.
+fn next_weighted(
+    endpoints: &[Endpoint],
+    state: &mut [EndpointState],
+    candidates: Vec<usize>,
+) -> Option<usize> {
+    let total: i64 = candidates
+        .iter()
+        .map(|i| i64::from(endpoints[*i].weight))
+        .sum();
+    for i in &candidates {
+        state[*i].current_weight += i64::from(endpoints[*i].weight);
+    }
+    let next = candidates
+        .into_iter()
+        .max_by_key(|i| (state[*i].current_weight, std::cmp::Reverse(*i)))?;
+    state[next].current_weight -= total;
+    Some(next)
+}
+
+#[async_trait]
+impl HttpTransport for FailoverTransport {
+    async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        let uri = request.uri().to_string();
+        let path = match uri.strip_prefix(self.url()) {
+            Some(path) if path.is_empty() || path.starts_with(['/', '?']) => path.to_string(),
+            _ => return self.inner.send(request).await,
+        };
+
+        let order = self.endpoint_order();
+        let last = order.len() - 1;
+        let (parts, body) = request.into_parts();
+        for (attempt, endpoint) in order.into_iter().enumerate() {
+            let uri = format!("{}{path}", self.endpoints[endpoint].url);
+            let mut request = Request::builder()
+                .method(parts.method.clone())
+                .uri(uri)
+                .version(parts.version)
+                .body(body.clone())?;
+            *request.headers_mut() = parts.headers.clone();
+            crate::http::copy_extensions(&parts.extensions, request.extensions_mut());
+
+            let result = self.inner.send(request).await;
+            let is_failure = self.is_failure(&result);
+            self.record(endpoint, !is_failure);
+            if !is_failure || attempt == last {
+                return result;
+            }
+        }
+        unreachable!("'FailoverTransport' is expected to have at least one endpoint")
+    }
+}
+
+/// The answers of two providers to the same query, see [`quorum`].
+#[derive(Debug, PartialEq)]
+pub enum Quorum<T> {
+    /// Both providers answered the same.
+    Agreed(T),
+    /// One of the providers failed, so the answer of the other one isn't confirmed.
+    Unconfirmed(T),
+    /// The providers answered differently.
+    Disagreed { first: T, second: T },
+}
+
+/// Sends the same query to two providers at once and compares the answers.
+/// Fails with the `first` error if both providers fail.
+pub async fn quorum<T, E, A, B>(first: A, second: B) -> Result<Quorum<T>, E>
+where
+    T: PartialEq,
+    A: Future<Output = Result<T, E>>,
+    B: Future<Output = Result<T, E>>,
+{
+    quorum_by(first, second, T::eq).await
+}
+
+/// Sends the same query to two providers at once and compares the answers with the `agree` function,
+/// e.g. to ignore the parts of the answers that may differ, as the mempool of every node is different.
+/// Fails with the `first` error if both providers fail.
+pub async fn quorum_by<T, E, A, B, F>(first: A, second: B, agree: F) -> Result<Quorum<T>, E>
+where
+    A: Future<Output = Result<T, E>>,
+    B: Future<Output = Result<T, E>>,
+    F: Fn(&T, &T) -> bool,
+{
+    match futures::join!(first, second) {
+        (Ok(first), Ok(second)) if agree(&first, &second) => Ok(Quorum::Agreed(first)),
+        (Ok(first), Ok(second)) => Ok(Quorum::Disagreed { first, second }),
+        (Ok(answer), Err(_)) | (Err(_), Ok(answer)) => Ok(Quorum::Unconfirmed(answer)),
+        (Err(e), Err(_)) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpTransportExt;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    fn endpoint(url: &str) -> Endpoint { Endpoint::new(url.to_string()).unwrap() }
+
+    async fn height(transport: &FailoverTransport) -> u64 {
+        let uri = format!("{}/height", transport.url()).parse().unwrap();
+        transport.get_json(uri).await.unwrap().into_body()
+    }
+
+    #[tokio::test]
+    async fn test_failover_transport() {
+        let inner = StubTransport::default()
+            .with_status("/a/height", StatusCode::TOO_MANY_REQUESTS)
+            .with_response("/b/height", "2")
+            .with_response("/c/height", "3")
+            .with_status("/a/tx", StatusCode::NOT_FOUND);
+        let transport = FailoverTransport::new(
+            Arc::new(inner),
+            endpoint("https://a.example/a"),
+            vec![
+                endpoint("https://b.example/b"),
+                endpoint("https://c.example/c"),
+            ],
+        )
+        .with_circuit_breaker(2, Duration::from_secs(60));
+
+        // A not found response is an answer, not an endpoint failure.
+        let uri = Uri::from_static("https://a.example/a/tx");
+        assert_eq!(
+            transport.get(uri).await.unwrap().status(),
+            StatusCode::NOT_FOUND
+        );
+
+        // The rate limited endpoint falls through to the next one.
+        assert_eq!(height(&transport).await, 2);
+        assert_eq!(transport.endpoint_order(), [0, 1, 2]);
+        assert_eq!(height(&transport).await, 2);
+        // The circuit of `a` is open after 2 failures, so `a` is tried last.
+        assert_eq!(transport.endpoint_order(), [1, 2, 0]);
+        assert_eq!(height(&transport).await, 2);
+
+        // The requests to the other hosts are sent as they are.
+        let uri = Uri::from_static("https://c.example/c/height");
+        assert_eq!(transport.get(uri).await.unwrap().into_body(), b"3");
+    }
+
+    #[test]
+    fn test_weighted_order() {
+        let transport = FailoverTransport::new(
+            Arc::new(StubTransport::default()),
+            endpoint("https://a.example"),
+            vec![endpoint("https://b.example").with_weight(2)],
+        )
+        .weighted();
+        let firsts: Vec<_> = (0..6).map(|_| transport.endpoint_order()[0]).collect();
+        assert_eq!(firsts, [1, 0, 1, 1, 0, 1]);
+
+        transport.record(1, false);
+        transport.record(1, false);
+        transport.record(1, false);
+        let firsts: Vec<_> = (0..3).map(|_| transport.endpoint_order()).collect();
+        assert_eq!(firsts, [[0, 1], [0, 1], [0, 1]]);
+    }
+
+    #[tokio::test]
+    async fn test_quorum() {
+        let answer = |answer: Result<u64, &'static str>| async move { answer };
+        let quorum = |first, second| quorum(answer(first), answer(second));
+        assert_eq!(quorum(Ok(1), Ok(1)).await, Ok(Quorum::Agreed(1)));
+        assert_eq!(
+            quorum(Ok(1), Ok(2)).await,
+            Ok(Quorum::Disagreed {
+                first: 1,
+                second: 2
+            })
+        );
+        assert_eq!(quorum(Err("a"), Ok(2)).await, Ok(Quorum::Unconfirmed(2)));
+        assert_eq!(quorum(Err("a"), Err("b")).await, Err("a"));
+
+        // Only the confirmed part of the answers is compared.
+        let same_tens = |first: &u64, second: &u64| first / 10 == second / 10;
+        let quorum = quorum_by(answer(Ok(11)), answer(Ok(12)), same_tens);
+        assert_eq!(quorum.await, Ok(Quorum::Agreed(11)));
+    }
+}
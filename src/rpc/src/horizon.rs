@@ -0,0 +1,220 @@
+use crate::account::AccountState;
+use crate::amount::{Amount, AmountError, Coin};
+use crate::http::{HttpError, HttpTransportExt, Request, SharedTransport};
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const HORIZON_URL: &str = "https://horizon.stellar.org";
+
+/// The maximum `limit` of the Horizon collection endpoints.
+const PAGE_LIMIT: usize = 200;
+/// The `asset_type` of XLM.
+const NATIVE_ASSET_TYPE: &str = "native";
+
+/// A Stellar Horizon API client: https://developers.stellar.org/docs/data/apis/horizon
+#[derive(Clone)]
+pub struct HorizonRpc {
+    transport: SharedTransport,
+    url: String,
+}
+
+impl HorizonRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        HorizonRpc::with_url(transport, HORIZON_URL.to_string())
+            .expect("'HORIZON_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(HorizonRpc { transport, url })
+    }
+
+    /// Requests the account. Horizon responds with `404` if the account hasn't been created.
+    pub async fn account(
+        &self,
+        account_id: &str,
+    ) -> Result<AccountState<HorizonAccount>, HttpError> {
+        match self.get_json(&format!("/accounts/{account_id}")).await? {
+            Some(account) => Ok(AccountState::Active(account)),
+            None => Ok(AccountState::NotActivated),
+        }
+    }
+
+    /// Requests all payments of the account page by page, oldest first, including the account creation.
+    /// Returns no payments if the account isn't activated.
+    pub async fn payments(&self, account_id: &str) -> Result<Vec<Payment>, HttpError> {
+        let mut payments: Vec<Payment> = Vec::new();
+        loop {
+            let cursor = payments
+                .last()
+                .map(|payment| format!("&cursor={}", payment.paging_token))
+                .unwrap_or_default();
+            let path =
+                format!("/accounts/{account_id}/payments?limit={PAGE_LIMIT}&order=asc{cursor}");
+            let page: Page<Payment> = match self.get_json(&path).await? {
+                Some(page) => page,
+                None => return Ok(payments),
+            };
+
+            let records = page.embedded.records;
+            let is_last_page = records.len() < PAGE_LIMIT;
+            payments.extend(records);
+            if is_last_page {
+                return Ok(payments);
+            }
+        }
+    }
+
+    /// Returns `None` if the resource isn't found.
+    async fn get_json<R>(&self, path: &str) -> Result<Option<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let request = Request::get(format!("{}{path}", self.url)).body(Vec::new())?;
+        self.transport.send_json_opt(request).await
+    }
+}
+
+/// https://developers.stellar.org/docs/data/apis/horizon/api-reference/resources/accounts/object
+#[derive(Debug, Deserialize)]
+pub struct HorizonAccount {
+    pub id: String,
+    pub sequence: String,
+    /// The count of the trust lines, offers and other entries, each adding to the minimum balance.
+    pub subentry_count: u32,
+    pub balances: Vec<HorizonBalance>,
+}
+
+impl HorizonAccount {
+    /// Returns the XLM balance.
+    pub fn native_balance(&self) -> Option<&Amount> {
+        self.balances
+            .iter()
+            .find(|balance| balance.asset_type == NATIVE_ASSET_TYPE)
+            .map(|balance| &balance.balance)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "RawHorizonBalance")]
+pub struct HorizonBalance {
+    /// In XLM, or in the `asset_code` units for the issued assets.
+    pub balance: Amount,
+    /// `native` for XLM, `credit_alphanum4` or `credit_alphanum12` for the issued assets.
+    pub asset_type: String,
+    /// Absent for XLM.
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawHorizonBalance {
+    /// A decimal string with 7 digits after the point, e.g. `100.0000000`.
+    balance: String,
+    asset_type: String,
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+}
+
+impl TryFrom<RawHorizonBalance> for HorizonBalance {
+    type Error = AmountError;
+
+    fn try_from(raw: RawHorizonBalance) -> Result<Self, Self::Error> {
+        // All Stellar assets have the same 7 decimals as XLM.
+        let coin = if raw.asset_type == NATIVE_ASSET_TYPE {
+            Coin::XLM
+        } else {
+            // Liquidity pool shares have no asset code.
+            let code = raw.asset_code.as_ref().unwrap_or(&raw.asset_type);
+            Coin::token(code.clone(), Coin::XLM.decimals)
+        };
+        Ok(HorizonBalance {
+            balance: Amount::parse(&raw.balance, coin)?,
+            asset_type: raw.asset_type,
+            asset_code: raw.asset_code,
+            asset_issuer: raw.asset_issuer,
+        })
+    }
+}
+
+/// A payment-like operation: `create_account`, `payment`, `path_payment_*` or `account_merge`.
+#[derive(Debug, Deserialize)]
+pub struct Payment {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub operation_type: String,
+    pub transaction_hash: String,
+    pub created_at: String,
+    /// Absent for `create_account` and `account_merge`.
+    pub amount: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Page<R> {
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<R>,
+}
+
+#[derive(Deserialize)]
+struct Embedded<R> {
+    records: Vec<R>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ACCOUNT_ID: &str = "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7";
+    const UNFUNDED: &str = "GDQJUTQYK2MQX2VGDR2FYWLIYAQIEGXTQVTFEMGH2BEWFG4BRUY4CKI7";
+
+    fn payments_page(range: std::ops::Range<usize>) -> String {
+        let records: Vec<_> = range
+            .map(|i| {
+                format!(
+                    r#"{{"id":"{i}","paging_token":"{i}","type":"payment","transaction_hash":"{i}",
+                    "created_at":"2024-01-01T00:00:00Z","amount":"1.0000000"}}"#
+                )
+            })
+            .collect();
+        format!(r#"{{"_embedded":{{"records":[{}]}}}}"#, records.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_horizon_rpc_stub() {
+        let payments_path = |cursor: &str| {
+            format!("/accounts/{ACCOUNT_ID}/payments?limit={PAGE_LIMIT}&order=asc{cursor}")
+        };
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/accounts/{ACCOUNT_ID}"),
+                &format!(
+                    r#"{{"id":"{ACCOUNT_ID}","sequence":"1234","subentry_count":1,"balances":[
+                    {{"balance":"12.5000000","asset_type":"credit_alphanum4","asset_code":"USDC","asset_issuer":"GA5Z"}},
+                    {{"balance":"100.0000000","asset_type":"native"}}]}}"#
+                ),
+            )
+            .with_response(&payments_path(""), &payments_page(0..PAGE_LIMIT))
+            .with_response(
+                &payments_path(&format!("&cursor={}", PAGE_LIMIT - 1)),
+                &payments_page(PAGE_LIMIT..250),
+            )
+            .with_status(&format!("/accounts/{UNFUNDED}"), StatusCode::NOT_FOUND)
+            .with_status(&format!("/accounts/{UNFUNDED}/payments?limit={PAGE_LIMIT}&order=asc"), StatusCode::NOT_FOUND);
+        let rpc = HorizonRpc::with_default_url(Arc::new(transport));
+
+        let account = rpc.account(ACCOUNT_ID).await.unwrap().active().unwrap();
+        assert_eq!(account.native_balance().unwrap().to_string(), "100 XLM");
+        assert_eq!(account.balances[0].balance.to_string(), "12.5 USDC");
+        assert_eq!(rpc.payments(ACCOUNT_ID).await.unwrap().len(), 250);
+
+        assert!(!rpc.account(UNFUNDED).await.unwrap().is_activated());
+        assert!(rpc.payments(UNFUNDED).await.unwrap().is_empty());
+    }
+}
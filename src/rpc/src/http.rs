@@ -1,16 +1,42 @@
+use crate::cache::CacheCheck;
+use crate::proxy::{ProxyConnector, SocksCredentials};
+use crate::tls;
+use crate::transport_config::TransportConfig;
 use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use hyper::body::HttpBody;
 use hyper::client::{Client, HttpConnector};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
 
-pub use http::{uri::InvalidUri, Response, Uri};
+pub use http::{header, uri::InvalidUri, Method, Request, Response, StatusCode, Uri};
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 
+/// The buffer size of the brotli decoder.
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
 #[derive(Debug)]
 pub enum HttpError {
     InvalidUri(String),
     InvalidPayload(String),
     TransportError(String),
+    UnexpectedStatus(StatusCode),
+    /// The API responded successfully, but with an error in the payload.
+    ApiError(String),
+    /// The request wasn't finished within the configured timeout.
+    Timeout,
+    /// The response body is larger than the configured maximum size in bytes.
+    TooLargeResponse(usize),
+    /// The certificates of the host don't match its public key pins.
+    PinMismatch(String),
+    /// The response isn't cached, and the [`CachingTransport`](crate::cache::CachingTransport) is offline.
+    NotCached(String),
 }
 
 impl fmt::Display for HttpError {
@@ -19,6 +45,29 @@ impl fmt::Display for HttpError {
             HttpError::InvalidUri(e) => write!(f, "Invalid URI: {e}"),
             HttpError::InvalidPayload(e) => write!(f, "Invalid response payload: {e}"),
             HttpError::TransportError(e) => write!(f, "Transport error: {e}"),
+            HttpError::UnexpectedStatus(status) => {
+                write!(f, "Unexpected response status: {status}")
+            }
+            HttpError::ApiError(e) => write!(f, "API error: {e}"),
+            HttpError::Timeout => write!(f, "Request timed out"),
+            HttpError::TooLargeResponse(max_size) => {
+                write!(f, "Response is larger than {max_size} bytes")
+            }
+            HttpError::PinMismatch(host) => {
+                write!(f, "Certificates of {host} don't match its public key pins")
+            }
+            HttpError::NotCached(uri) => write!(f, "Response of {uri} is not cached"),
+        }
+    }
+}
+
+impl HttpError {
+    /// Returns an error if the `response` status isn't successful (2xx).
+    pub fn check_status<T>(response: &Response<T>) -> Result<(), HttpError> {
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(HttpError::UnexpectedStatus(response.status()))
         }
     }
 }
@@ -27,23 +76,46 @@ impl From<hyper::Error> for HttpError {
     fn from(e: hyper::Error) -> Self { HttpError::TransportError(e.to_string()) }
 }
 
+impl From<http::Error> for HttpError {
+    fn from(e: http::Error) -> Self { HttpError::InvalidUri(e.to_string()) }
+}
+
 impl From<InvalidUri> for HttpError {
     fn from(e: InvalidUri) -> Self { HttpError::InvalidUri(e.to_string()) }
 }
 
+/// A transport shared by the RPC clients, so that the clients can be cloned, stored and moved between tasks.
+pub type SharedTransport = Arc<dyn HttpTransport + Send + Sync>;
+
 /// An `HttpTransport` builder.
 /// This pattern allows us to change the transport for the whole app in one line.
 pub struct HttpBuilder;
 
 impl HttpBuilder {
-    pub fn build() -> impl HttpTransport { HyperTransport::default() }
+    pub fn build(config: TransportConfig) -> SharedTransport {
+        let max_requests_per_host = config.max_requests_per_host;
+        Arc::new(HostLimitedTransport::new(
+            HyperTransport::new(config),
+            max_requests_per_host,
+        ))
+    }
 }
 
+/// The object-safe HTTP transport, see [`HttpTransportExt`] for the Json requests.
 #[async_trait]
 pub trait HttpTransport {
+    /// Sends an HTTP request.
+    async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError>;
+
     /// Sends an `HTTP GET` request.
-    async fn get(&self, uri: Uri) -> Result<Response<Vec<u8>>, HttpError>;
+    async fn get(&self, uri: Uri) -> Result<Response<Vec<u8>>, HttpError> {
+        self.send(Request::get(uri).body(Vec::new())?).await
+    }
+}
 
+/// The Json requests of any [`HttpTransport`], kept apart as the generic methods aren't object-safe.
+#[async_trait]
+pub trait HttpTransportExt: HttpTransport + Sync {
     /// Sends an `HTTP GET` request, parses payload as a Json.
     async fn get_json<T>(&self, uri: Uri) -> Result<Response<T>, HttpError>
     where
@@ -54,30 +126,386 @@ pub trait HttpTransport {
             serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
         Ok(Response::from_parts(parts, json))
     }
+
+    /// Sends an HTTP request, parses payload as a Json.
+    /// Returns `None` if the resource isn't found, i.e. the response status is `404`.
+    async fn send_json_opt<T>(&self, request: Request<Vec<u8>>) -> Result<Option<T>, HttpError>
+    where
+        T: DeserializeOwned,
+    {
+        let res = self.send(request).await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+
+    /// Sends an `HTTP POST` request with a Json payload, parses response payload as a Json.
+    async fn post_json<B, T>(&self, uri: Uri, body: &B) -> Result<Response<T>, HttpError>
+    where
+        B: Serialize + Sync,
+        T: DeserializeOwned,
+    {
+        let body =
+            serde_json::to_vec(body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        let request = Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)?;
+        let (parts, body) = self.send(request).await?.into_parts();
+        let json =
+            serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        Ok(Response::from_parts(parts, json))
+    }
 }
 
+impl<T> HttpTransportExt for T where T: HttpTransport + Sync + ?Sized {}
+
+type HyperClient = Client<HttpsConnector<ProxyConnector>>;
+
 pub struct HyperTransport {
-    client: Client<HttpsConnector<HttpConnector>>,
+    client: HyperClient,
+    /// The clients of the stream isolation groups, see [`IsolatedTransport`].
+    isolated_clients: Mutex<HashMap<String, HyperClient>>,
+    /// A random SOCKS5 password of the transport, so that the groups don't share the circuits
+    /// with the groups of the same name of another process.
+    session: String,
+    /// Shared by the clients, so that the native roots are loaded once.
+    tls_config: rustls::ClientConfig,
+    config: TransportConfig,
 }
 
 impl Default for HyperTransport {
-    fn default() -> Self {
-        let connector = HttpsConnectorBuilder::default()
-            .with_native_roots()
-            .https_or_http()
-            .enable_http2()
-            .build();
+    fn default() -> Self { HyperTransport::new(TransportConfig::default()) }
+}
+
+impl HyperTransport {
+    pub fn new(config: TransportConfig) -> Self {
+        let session = format!("{:016x}", RandomState::new().build_hasher().finish());
+        let tls_config =
+            tls::client_config(config.native_roots, &config.root_certificates, &config.pins);
         HyperTransport {
-            client: Client::builder().build(connector),
+            client: build_client(&config, &tls_config, None),
+            isolated_clients: Mutex::default(),
+            session,
+            tls_config,
+            config,
         }
     }
+
+    /// Returns the client of the stream isolation `group`, so that the groups don't share the connections.
+    /// Through a SOCKS5 proxy the group is sent as the username, so Tor uses a separate circuit for it.
+    fn client(&self, group: Option<&StreamIsolation>) -> HyperClient {
+        let Some(StreamIsolation(group)) = group else {
+            return self.client.clone();
+        };
+        let mut clients = self
+            .isolated_clients
+            .lock()
+            .expect("Isolated clients lock is poisoned");
+        clients
+            .entry(group.clone())
+            .or_insert_with(|| {
+                let credentials = SocksCredentials {
+                    username: group.clone(),
+                    password: self.session.clone(),
+                };
+                build_client(&self.config, &self.tls_config, Some(credentials))
+            })
+            .clone()
+    }
+
+    async fn fetch(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        let client = self.client(request.extensions().get());
+        let request = request.map(hyper::Body::from);
+        let res = client
+            .request(request)
+            .await
+            .map_err(|e| match tls::pin_mismatch(&e) {
+                Some(host) => HttpError::PinMismatch(host),
+                None => HttpError::from(e),
+            })?;
+        let (mut parts, body) = res.into_parts();
+        let max_size = self.config.max_response_size;
+        let mut bytes = read_body(body, max_size).await?;
+        if self.config.decompression {
+            bytes = decode_body(&mut parts.headers, bytes, max_size)?;
+        }
+        Ok(Response::from_parts(parts, bytes))
+    }
+}
+
+/// Builds a client with the SOCKS5 `credentials` that take precedence over the ones of the proxy URL.
+fn build_client(
+    config: &TransportConfig,
+    tls_config: &rustls::ClientConfig,
+    credentials: Option<SocksCredentials>,
+) -> HyperClient {
+    let mut http = HttpConnector::new();
+    http.set_connect_timeout(Some(config.connect_timeout));
+    let mut proxy_connector = ProxyConnector::new(http, config.proxy.clone(), config.proxy_only);
+    if let Some(credentials) = credentials {
+        proxy_connector = proxy_connector.with_socks_credentials(credentials);
+    }
+    let connector = HttpsConnectorBuilder::default()
+        .with_tls_config(tls_config.clone())
+        .https_or_http()
+        .enable_http2()
+        .wrap_connector(proxy_connector);
+    Client::builder().build(connector)
 }
 
 #[async_trait]
 impl HttpTransport for HyperTransport {
-    async fn get(&self, uri: Uri) -> Result<Response<Vec<u8>>, HttpError> {
-        let (parts, body) = self.client.get(uri).await?.into_parts();
-        let bytes = hyper::body::to_bytes(body).await?;
-        Ok(Response::from_parts(parts, bytes.into()))
+    async fn send(&self, mut request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        self.config.apply_headers(&mut request);
+        tokio::time::timeout(self.config.request_timeout, self.fetch(request))
+            .await
+            .map_err(|_| HttpError::Timeout)?
+    }
+}
+
+/// Reads the `body` up to the `max_size` bytes instead of buffering a response of any size.
+async fn read_body(mut body: hyper::Body, max_size: usize) -> Result<Vec<u8>, HttpError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > max_size {
+            return Err(HttpError::TooLargeResponse(max_size));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Decompresses the `body` by the `Content-Encoding` header, and removes the header.
+fn decode_body(
+    headers: &mut header::HeaderMap,
+    body: Vec<u8>,
+    max_size: usize,
+) -> Result<Vec<u8>, HttpError> {
+    let encoding = match headers.get(header::CONTENT_ENCODING) {
+        Some(encoding) => encoding.to_str().unwrap_or_default().trim().to_lowercase(),
+        None => return Ok(body),
+    };
+    let decoded = match encoding.as_str() {
+        "identity" => return Ok(body),
+        "gzip" | "x-gzip" => read_decoded(GzDecoder::new(body.as_slice()), max_size)?,
+        "br" => read_decoded(
+            brotli_decompressor::Decompressor::new(body.as_slice(), BROTLI_BUFFER_SIZE),
+            max_size,
+        )?,
+        _ => {
+            return Err(HttpError::InvalidPayload(format!(
+                "Unsupported content encoding: '{encoding}'"
+            )))
+        }
+    };
+    headers.remove(header::CONTENT_ENCODING);
+    headers.remove(header::CONTENT_LENGTH);
+    Ok(decoded)
+}
+
+/// Reads the `decoder` up to the `max_size` bytes, so that a small compressed body can't take all memory.
+fn read_decoded<R>(decoder: R, max_size: usize) -> Result<Vec<u8>, HttpError>
+where
+    R: Read,
+{
+    let mut decoded = Vec::new();
+    decoder
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+    if decoded.len() > max_size {
+        return Err(HttpError::TooLargeResponse(max_size));
+    }
+    Ok(decoded)
+}
+
+/// The stream isolation group of a request, set by the [`IsolatedTransport`].
+#[derive(Clone, Debug)]
+struct StreamIsolation(String);
+
+/// Copies the stream isolation group and the [`CacheCheck`] to the extensions of a rebuilt request.
+pub(crate) fn copy_extensions(from: &http::Extensions, to: &mut http::Extensions) {
+    if let Some(group) = from.get::<StreamIsolation>() {
+        to.insert(group.clone());
+    }
+    if let Some(check) = from.get::<CacheCheck>() {
+        to.insert(*check);
+    }
+}
+
+/// An `HttpTransport` that sends the requests of the `group`, e.g. of the addresses of one chain,
+/// over separate connections, and through a SOCKS5 proxy like Tor, over a separate circuit.
+/// So the providers and the exit nodes can't link the addresses of the different groups.
+pub struct IsolatedTransport {
+    inner: SharedTransport,
+    group: StreamIsolation,
+}
+
+impl IsolatedTransport {
+    pub fn new(inner: SharedTransport, group: &str) -> Self {
+        IsolatedTransport {
+            inner,
+            group: StreamIsolation(group.to_string()),
+        }
+    }
+
+    /// Wraps the `inner` transport, e.g. `IsolatedTransport::shared(transport.clone(), "BTC")`.
+    pub fn shared(inner: SharedTransport, group: &str) -> SharedTransport {
+        Arc::new(IsolatedTransport::new(inner, group))
+    }
+}
+
+#[async_trait]
+impl HttpTransport for IsolatedTransport {
+    async fn send(&self, mut request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        request.extensions_mut().insert(self.group.clone());
+        self.inner.send(request).await
+    }
+}
+
+/// An `HttpTransport` that limits the number of concurrent requests to each host.
+/// The other requests wait until one of the requests to the same host is finished.
+pub struct HostLimitedTransport<T> {
+    inner: T,
+    max_requests_per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl<T> HostLimitedTransport<T> {
+    pub fn new(inner: T, max_requests_per_host: usize) -> Self {
+        HostLimitedTransport {
+            inner,
+            max_requests_per_host,
+            hosts: Mutex::default(),
+        }
+    }
+
+    fn host_semaphore(&self, uri: &Uri) -> Arc<Semaphore> {
+        let host = uri.authority().map(|a| a.as_str()).unwrap_or_default();
+        let mut hosts = self.hosts.lock().expect("Hosts lock is poisoned");
+        hosts
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_requests_per_host)))
+            .clone()
+    }
+}
+
+#[async_trait]
+impl<T> HttpTransport for HostLimitedTransport<T>
+where
+    T: HttpTransport + Send + Sync,
+{
+    async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        let semaphore = self.host_semaphore(request.uri());
+        let _permit = semaphore
+            .acquire()
+            .await
+            .map_err(|e| HttpError::TransportError(e.to_string()))?;
+        self.inner.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts the requests in flight, keeping each one pending for a few polls.
+    #[derive(Default)]
+    struct InFlightTransport {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpTransport for InFlightTransport {
+        async fn send(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            for _ in 0..3 {
+                tokio::task::yield_now().await;
+            }
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(Response::new(Vec::new()))
+        }
+    }
+
+    #[test]
+    fn test_decode_body() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let json = br#"{"result":"0x1"}"#;
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::CONTENT_ENCODING, "gzip".parse().unwrap());
+        headers.insert(header::CONTENT_LENGTH, gzip.len().into());
+        let decoded = decode_body(&mut headers.clone(), gzip.clone(), 1024).unwrap();
+        assert_eq!(decoded, json);
+
+        let err = decode_body(&mut headers.clone(), gzip, json.len() - 1).unwrap_err();
+        assert!(matches!(err, HttpError::TooLargeResponse(_)), "{err}");
+
+        decode_body(&mut headers, json.to_vec(), 1024).unwrap_err();
+        let mut headers = header::HeaderMap::new();
+        assert_eq!(
+            decode_body(&mut headers, json.to_vec(), 1024).unwrap(),
+            json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_host_limited_transport() {
+        let transport = HostLimitedTransport::new(InFlightTransport::default(), 2);
+        let requests = (0..6).map(|_| transport.get(Uri::from_static("https://a.example/")));
+        for response in futures::future::join_all(requests).await {
+            assert!(response.is_ok());
+        }
+        assert_eq!(transport.inner.max_in_flight.load(Ordering::SeqCst), 2);
+
+        let transport = HostLimitedTransport::new(InFlightTransport::default(), 1);
+        let requests = [
+            "https://a.example/",
+            "https://b.example/",
+            "https://a.example/x",
+        ]
+        .map(|uri| transport.get(Uri::from_static(uri)));
+        futures::future::join_all(requests).await;
+        assert_eq!(transport.inner.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_isolated_transport() {
+        let (proxy, mut connections) = crate::proxy::tests::socks_stub(true).await;
+        let config = TransportConfig::default()
+            .with_proxy(proxy)
+            .with_proxy_only(true);
+        let transport: SharedTransport = Arc::new(HyperTransport::new(config));
+
+        // The stub closes the tunnels, so only the SOCKS5 usernames of the connections are checked.
+        for group in ["BTC", "ETH"] {
+            let isolated = IsolatedTransport::shared(transport.clone(), group);
+            let uri = Uri::from_static("https://blockstream.info/api");
+            isolated.get(uri).await.unwrap_err();
+            assert_eq!(
+                connections.recv().await.unwrap(),
+                format!("{group}@blockstream.info:443")
+            );
+        }
+        transport
+            .get(Uri::from_static("https://blockstream.info/api"))
+            .await
+            .unwrap_err();
+        assert_eq!(connections.recv().await.unwrap(), "@blockstream.info:443");
     }
 }
@@ -0,0 +1,64 @@
+//! JSON-RPC 2.0 over `HTTP POST`, as served by the EVM, Solana and other nodes.
+//! https://www.jsonrpc.org/specification
+
+use crate::http::{HttpError, HttpTransportExt, SharedTransport};
+use http::{Response, Uri};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Sends a JSON-RPC request, returns its `result` or an [`HttpError::ApiError`] with the `error` message.
+pub(crate) async fn request<R>(
+    transport: &SharedTransport,
+    uri: &Uri,
+    method: &str,
+    params: Value,
+) -> Result<R, HttpError>
+where
+    R: DeserializeOwned,
+{
+    let req = JsonRpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method,
+        params,
+    };
+    let res: Response<JsonRpcResponse<R>> = transport.post_json(uri.clone(), &req).await?;
+    HttpError::check_status(&res)?;
+
+    match res.into_body() {
+        JsonRpcResponse {
+            error: Some(error), ..
+        } => Err(HttpError::ApiError(format!(
+            "{} (code {})",
+            error.message, error.code
+        ))),
+        JsonRpcResponse {
+            result: Some(result),
+            ..
+        } => Ok(result),
+        _ => Err(HttpError::InvalidPayload(format!(
+            "Neither 'result' nor 'error' in the '{method}' response"
+        ))),
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse<R> {
+    result: Option<R>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
@@ -1,4 +1,40 @@
+pub mod abi;
+pub mod account;
+pub mod activity;
+pub mod algorand;
+pub mod amount;
+pub mod aptos;
+pub mod blockbook;
+pub mod blockbook_instances;
+pub mod blockfrost;
 pub mod blockstream;
+pub mod cache;
+pub mod coingecko;
 pub mod cosmos;
+pub mod cosmos_registry;
+pub mod electrum;
 pub mod etherscan;
+pub mod etherscan_explorers;
+pub mod evm;
+pub mod failover;
+pub mod horizon;
 pub mod http;
+mod json_rpc;
+pub mod near;
+pub mod price;
+mod proxy;
+mod serde_str;
+pub mod sidecar;
+pub mod solana;
+pub mod ss58;
+pub mod subscan;
+pub mod substrate_networks;
+pub mod tls;
+pub mod token_list;
+pub mod transport_config;
+pub mod tron;
+pub mod tzkt;
+pub mod xrpl;
+
+#[cfg(test)]
+mod testing;
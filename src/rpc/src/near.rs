@@ -0,0 +1,196 @@
+use crate::account::AccountState;
+use crate::activity::{Activity, ActivityRpc};
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, HttpTransportExt, SharedTransport};
+use crate::serde_str::deserialize_from_str;
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use http::{Response, Uri};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const NEAR_URL: &str = "https://rpc.mainnet.near.org";
+
+/// The error cause nearcore responds with if the account doesn't exist.
+const UNKNOWN_ACCOUNT: &str = "UNKNOWN_ACCOUNT";
+
+/// 1 NEAR = 10^24 yoctoNEAR.
+pub type YoctoNear = u128;
+
+/// A client of the NEAR JSON-RPC API: https://docs.near.org/api/rpc/introduction
+#[derive(Clone)]
+pub struct NearRpc {
+    transport: SharedTransport,
+    uri: Uri,
+}
+
+impl NearRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        NearRpc::with_url(transport, NEAR_URL.to_string())
+            .expect("'NEAR_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        Ok(NearRpc {
+            transport,
+            uri: url.parse()?,
+        })
+    }
+
+    /// Requests the account at the final block.
+    /// The implicit accounts, e.g. derived from a key, don't exist until they are funded.
+    pub async fn view_account(
+        &self,
+        account_id: &str,
+    ) -> Result<AccountState<NearAccount>, HttpError> {
+        let params = json!({
+            "request_type": "view_account",
+            "finality": "final",
+            "account_id": account_id,
+        });
+        match self.request("query", params).await {
+            Ok(account) => Ok(AccountState::Active(account)),
+            Err(NearError::Rpc(cause)) if cause == UNKNOWN_ACCOUNT => {
+                Ok(AccountState::NotActivated)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// nearcore responds with the error kind in `error.cause.name` and a generic `error.message`,
+    /// so the shared JSON-RPC helper isn't used here.
+    async fn request<R>(&self, method: &str, params: Value) -> Result<R, NearError>
+    where
+        R: DeserializeOwned,
+    {
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let res: Response<NearResponse<R>> =
+            self.transport.post_json(self.uri.clone(), &req).await?;
+        HttpError::check_status(&res)?;
+
+        match res.into_body() {
+            NearResponse {
+                error: Some(error), ..
+            } => match error.cause {
+                Some(cause) => Err(NearError::Rpc(cause.name)),
+                None => Err(HttpError::ApiError(error.message).into()),
+            },
+            NearResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            _ => Err(HttpError::InvalidPayload(format!(
+                "Neither 'result' nor 'error' in the '{method}' response"
+            ))
+            .into()),
+        }
+    }
+}
+
+#[async_trait]
+impl ActivityRpc for NearRpc {
+    /// NEAR RPC doesn't index the transactions by account, so the transaction count isn't reported.
+    async fn activity(&self, address: &str) -> Result<AccountState<Activity>, HttpError> {
+        let account = match self.view_account(address).await? {
+            AccountState::Active(account) => account,
+            AccountState::NotActivated => return Ok(AccountState::NotActivated),
+        };
+        Ok(AccountState::Active(Activity {
+            balance: Amount::from_base_units(account.amount, Coin::NEAR),
+            transaction_count: None,
+        }))
+    }
+}
+
+/// https://docs.near.org/api/rpc/contracts#view-account
+#[derive(Debug, Deserialize)]
+pub struct NearAccount {
+    /// The liquid balance.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub amount: YoctoNear,
+    /// The balance locked for validator staking.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub locked: YoctoNear,
+    /// `11111111111111111111111111111111` if there is no contract deployed.
+    pub code_hash: String,
+    pub storage_usage: u64,
+    pub block_height: u64,
+}
+
+#[derive(Deserialize)]
+struct NearResponse<R> {
+    result: Option<R>,
+    error: Option<NearRpcError>,
+}
+
+#[derive(Deserialize)]
+struct NearRpcError {
+    message: String,
+    cause: Option<NearRpcErrorCause>,
+}
+
+#[derive(Deserialize)]
+struct NearRpcErrorCause {
+    name: String,
+}
+
+enum NearError {
+    Http(HttpError),
+    /// The `error.cause.name`, e.g. `UNKNOWN_ACCOUNT`.
+    Rpc(String),
+}
+
+impl From<HttpError> for NearError {
+    fn from(e: HttpError) -> Self { NearError::Http(e) }
+}
+
+impl From<NearError> for HttpError {
+    fn from(e: NearError) -> Self {
+        match e {
+            NearError::Http(e) => e,
+            NearError::Rpc(cause) => HttpError::ApiError(cause),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ACCOUNT_ID: &str = "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de";
+    const UNFUNDED: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    #[tokio::test]
+    async fn test_near_rpc_stub() {
+        let transport = StubTransport::default().with_handler("/", |body| {
+            let req: Value = serde_json::from_slice(body).unwrap();
+            match req["params"]["account_id"].as_str().unwrap() {
+                ACCOUNT_ID => {
+                    r#"{"jsonrpc":"2.0","id":1,"result":{"amount":"1500000000000000000000000",
+                "locked":"0","code_hash":"11111111111111111111111111111111","storage_usage":182,
+                "storage_paid_at":0,"block_height":120000000,"block_hash":"8sQ5"}}"#
+                }
+                _ => {
+                    r#"{"jsonrpc":"2.0","id":1,"error":{"name":"HANDLER_ERROR","code":-32000,
+                "message":"Server error","data":"account does not exist while viewing",
+                "cause":{"name":"UNKNOWN_ACCOUNT","info":{"block_height":120000000}}}}"#
+                }
+            }
+            .to_string()
+        });
+        let rpc = NearRpc::with_default_url(Arc::new(transport));
+
+        let activity = rpc.activity(ACCOUNT_ID).await.unwrap().active().unwrap();
+        assert_eq!(activity.balance.to_string(), "1.5 NEAR");
+        assert_eq!(activity.transaction_count, None);
+        assert!(!rpc.view_account(UNFUNDED).await.unwrap().is_activated());
+    }
+}
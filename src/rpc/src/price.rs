@@ -0,0 +1,476 @@
+//! Fiat prices of the coins, requested from a [`PriceSource`].
+
+use crate::amount::{Amount, AmountError, Coin, Rounding};
+use crate::http::HttpError;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+/// The decimals the prices are kept with, enough for the coins worth a fraction of a cent.
+const PRICE_DECIMALS: u8 = 18;
+/// The key of the latest prices in a prices file, see [`StaticPrices::from_json`].
+const LATEST_KEY: &str = "latest";
+/// The key of the token price ids in a prices file, see [`StaticPrices::from_json`].
+const TOKENS_KEY: &str = "tokens";
+
+/// The prices by the coin price id, then by the lowercase fiat currency.
+pub type Prices = BTreeMap<String, BTreeMap<String, Amount>>;
+
+/// Returns the CoinGecko price id of the coin by the `trustwallet/wallet-core` coin type.
+pub fn price_id(coin_type: u32) -> Option<&'static str> {
+    PRICE_IDS
+        .iter()
+        .find(|(id_coin_type, _)| *id_coin_type == coin_type)
+        .map(|(_, id)| *id)
+}
+
+/// Returns the CoinGecko asset platform id of the chain by the `trustwallet/wallet-core` coin type,
+/// the platform the token contracts of the chain are looked up on.
+pub fn asset_platform(coin_type: u32) -> Option<&'static str> {
+    ASSET_PLATFORMS
+        .iter()
+        .find(|(platform_coin_type, _)| *platform_coin_type == coin_type)
+        .map(|(_, platform)| *platform)
+}
+
+/// Returns the CoinGecko asset platform id of the EVM chain by its chain id, e.g. `ethereum` for `1`.
+pub fn evm_asset_platform(chain_id: u64) -> Option<&'static str> {
+    EVM_ASSET_PLATFORMS
+        .iter()
+        .find(|(platform_chain_id, _)| *platform_chain_id == chain_id)
+        .map(|(_, platform)| *platform)
+}
+
+/// Returns the units of the fiat `currency`, e.g. `USD` for `usd`.
+pub fn fiat_coin(currency: &str) -> Coin { Coin::token(currency.to_uppercase(), PRICE_DECIMALS) }
+
+/// Converts a price of a JSON response to the `currency` units.
+pub(crate) fn parse_price(price: f64, currency: &str) -> Result<Amount, AmountError> {
+    // `f64` is formatted without an exponent, e.g. `0.00001234`.
+    Amount::parse_rounded(&price.to_string(), fiat_coin(currency), Rounding::HalfEven)
+}
+
+/// A token contract on a CoinGecko asset platform, e.g. USDT on `ethereum`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenContract {
+    /// The asset platform id, see [`asset_platform`].
+    pub platform: String,
+    /// The contract address, lowercase on the EVM chains. The mint of an SPL token, `CODE-ISSUER`
+    /// of a Stellar asset, the policy id and the asset name of a Cardano asset, or a Cosmos denom.
+    pub address: String,
+}
+
+impl TokenContract {
+    pub fn new<P>(platform: P, address: &str) -> TokenContract
+    where
+        P: Into<String>,
+    {
+        // The EVM addresses are case-insensitive, the other ones aren't.
+        let address = if address.starts_with("0x") {
+            address.to_lowercase()
+        } else {
+            address.to_string()
+        };
+        TokenContract {
+            platform: platform.into(),
+            address,
+        }
+    }
+}
+
+/// The price id of a token, see [`PriceSource::token_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub id: String,
+    /// The token decimals if the price source knows them.
+    pub decimals: Option<u8>,
+}
+
+/// A source of the fiat prices of the coins.
+#[async_trait]
+pub trait PriceSource {
+    /// Requests the latest prices of the `ids` in the fiat `currency`, e.g. `usd`.
+    /// The ids without a price are absent from the result.
+    async fn prices(
+        &self,
+        ids: &[&str],
+        currency: &str,
+    ) -> Result<BTreeMap<String, Amount>, HttpError>;
+
+    /// Requests the price of the `id` in the fiat `currency` on the `date`, or `None` if there is no price.
+    async fn historical_price(
+        &self,
+        id: &str,
+        currency: &str,
+        date: PriceDate,
+    ) -> Result<Option<Amount>, HttpError>;
+
+    /// Requests the price id of the `token`, or `None` if the token isn't listed.
+    async fn token_info(&self, token: &TokenContract) -> Result<Option<TokenInfo>, HttpError>;
+}
+
+/// A calendar date in the `YYYY-MM-DD` format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PriceDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidPriceDate(pub String);
+
+impl fmt::Display for InvalidPriceDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid date, expected 'YYYY-MM-DD': '{}'", self.0)
+    }
+}
+
+impl FromStr for PriceDate {
+    type Err = InvalidPriceDate;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidPriceDate(s.to_string());
+        let mut parts = s.split('-');
+        let (year, month, day) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(year), Some(month), Some(day), None)
+                if year.len() == 4 && month.len() == 2 && day.len() == 2 =>
+            {
+                (year, month, day)
+            }
+            _ => return Err(invalid()),
+        };
+        let date = PriceDate {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?,
+        };
+        if !(1..=12).contains(&date.month) || !(1..=date.days_in_month()).contains(&date.day) {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
+
+impl fmt::Display for PriceDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl PriceDate {
+    fn days_in_month(&self) -> u8 {
+        let is_leap_year = self.year.is_multiple_of(4)
+            && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400));
+        match self.month {
+            2 if is_leap_year => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PricesFileError {
+    Io(String),
+    InvalidFormat(String),
+}
+
+impl fmt::Display for PricesFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PricesFileError::Io(e) => write!(f, "Error on reading the prices file: {e}"),
+            PricesFileError::InvalidFormat(e) => write!(f, "Invalid prices file format: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for PricesFileError {
+    fn from(e: io::Error) -> Self { PricesFileError::Io(e.to_string()) }
+}
+
+impl From<serde_json::Error> for PricesFileError {
+    fn from(e: serde_json::Error) -> Self { PricesFileError::InvalidFormat(e.to_string()) }
+}
+
+/// The prices loaded from a file, e.g. to value the balances offline.
+#[derive(Debug, Default)]
+pub struct StaticPrices {
+    latest: Prices,
+    history: BTreeMap<PriceDate, Prices>,
+    tokens: BTreeMap<TokenContract, String>,
+}
+
+impl StaticPrices {
+    /// Loads the prices from a file. See [`StaticPrices::from_json`].
+    pub fn from_file(path: &str) -> Result<StaticPrices, PricesFileError> {
+        StaticPrices::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the prices of the CoinGecko `/simple/price` response format, keyed by `latest` or a date:
+    /// `{"latest": {"bitcoin": {"usd": 65000.5}}, "2024-01-01": {"bitcoin": {"usd": 42280.23}}}`
+    /// The price ids of the tokens are keyed by `tokens`, then by the asset platform and the contract:
+    /// `{"tokens": {"ethereum": {"0xdac17f958d2ee523a2206206994597c13d831ec7": "tether"}}}`
+    pub fn from_json(json: &str) -> Result<StaticPrices, PricesFileError> {
+        let file: BTreeMap<String, serde_json::Value> = serde_json::from_str(json)?;
+        let mut prices = StaticPrices::default();
+        for (key, value) in file {
+            if key == TOKENS_KEY {
+                let tokens: BTreeMap<String, BTreeMap<String, String>> =
+                    serde_json::from_value(value)?;
+                for (platform, contracts) in tokens {
+                    for (address, id) in contracts {
+                        prices
+                            .tokens
+                            .insert(TokenContract::new(platform.as_str(), &address), id);
+                    }
+                }
+                continue;
+            }
+            let prices_json: PricesJson = serde_json::from_value(value)?;
+            let parsed = prices_json
+                .into_prices()
+                .map_err(|e| PricesFileError::InvalidFormat(e.to_string()))?;
+            if key == LATEST_KEY {
+                prices.latest = parsed;
+            } else {
+                let date = key
+                    .parse()
+                    .map_err(|e: InvalidPriceDate| PricesFileError::InvalidFormat(e.to_string()))?;
+                prices.history.insert(date, parsed);
+            }
+        }
+        Ok(prices)
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPrices {
+    async fn prices(
+        &self,
+        ids: &[&str],
+        currency: &str,
+    ) -> Result<BTreeMap<String, Amount>, HttpError> {
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                let price = find_price(&self.latest, id, currency)?;
+                Some((id.to_string(), price))
+            })
+            .collect())
+    }
+
+    async fn historical_price(
+        &self,
+        id: &str,
+        currency: &str,
+        date: PriceDate,
+    ) -> Result<Option<Amount>, HttpError> {
+        Ok(self
+            .history
+            .get(&date)
+            .and_then(|prices| find_price(prices, id, currency)))
+    }
+
+    /// The decimals of the tokens aren't kept in the file.
+    async fn token_info(&self, token: &TokenContract) -> Result<Option<TokenInfo>, HttpError> {
+        Ok(self.tokens.get(token).map(|id| TokenInfo {
+            id: id.clone(),
+            decimals: None,
+        }))
+    }
+}
+
+/// The CoinGecko `/simple/price` format: `{"bitcoin": {"usd": 65000.5}}`.
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub(crate) struct PricesJson(BTreeMap<String, BTreeMap<String, f64>>);
+
+impl PricesJson {
+    pub(crate) fn into_prices(self) -> Result<Prices, AmountError> {
+        self.0
+            .into_iter()
+            .map(|(id, prices)| {
+                let prices = prices
+                    .into_iter()
+                    .map(|(currency, price)| {
+                        let currency = currency.to_lowercase();
+                        let price = parse_price(price, &currency)?;
+                        Ok((currency, price))
+                    })
+                    .collect::<Result<_, AmountError>>()?;
+                Ok((id, prices))
+            })
+            .collect()
+    }
+}
+
+fn find_price(prices: &Prices, id: &str, currency: &str) -> Option<Amount> {
+    prices.get(id)?.get(&currency.to_lowercase()).cloned()
+}
+
+const PRICE_IDS: &[(u32, &str)] = &[
+    (0, "bitcoin"),
+    (2, "litecoin"),
+    (3, "dogecoin"),
+    (5, "dash"),
+    (14, "viacoin"),
+    (17, "groestlcoin"),
+    (20, "digibyte"),
+    (60, "ethereum"),
+    (118, "cosmos"),
+    (133, "zcash"),
+    (136, "zcoin"),
+    (144, "ripple"),
+    (145, "bitcoin-cash"),
+    (148, "stellar"),
+    (175, "ravencoin"),
+    (195, "tron"),
+    (283, "algorand"),
+    (354, "polkadot"),
+    (394, "crypto-com-chain"),
+    (397, "near"),
+    (434, "kusama"),
+    (459, "kava"),
+    (494, "band-protocol"),
+    (501, "solana"),
+    (529, "secret"),
+    (637, "aptos"),
+    (931, "thorchain"),
+    (966, "matic-network"),
+    (1729, "tezos"),
+    (1815, "cardano"),
+    (52752, "celo"),
+    (10000025, "crypto-com-chain"),
+    (10000070, "ethereum"),
+    (10000100, "xdai"),
+    (10000118, "osmosis"),
+    (10000250, "fantom"),
+    (10000280, "ethereum"),
+    (10000288, "ethereum"),
+    (10001284, "moonbeam"),
+    (10001285, "moonriver"),
+    (10009000, "avalanche-2"),
+    (10042221, "ethereum"),
+    (20000714, "binancecoin"),
+    (20009001, "evmos"),
+];
+
+const ASSET_PLATFORMS: &[(u32, &str)] = &[
+    (60, "ethereum"),
+    (118, "cosmos"),
+    (148, "stellar"),
+    (195, "tron"),
+    (330, "terra"),
+    (459, "kava"),
+    (501, "solana"),
+    (529, "secret"),
+    (966, "polygon-pos"),
+    (1815, "cardano"),
+    (52752, "celo"),
+    (10000025, "cronos"),
+    (10000070, "optimistic-ethereum"),
+    (10000100, "xdai"),
+    (10000118, "osmosis"),
+    (10000250, "fantom"),
+    (10000280, "zksync"),
+    (10000288, "boba"),
+    (10000330, "terra-2"),
+    (10001284, "moonbeam"),
+    (10001285, "moonriver"),
+    (10009000, "avalanche"),
+    (10042221, "arbitrum-one"),
+    (20000714, "binance-smart-chain"),
+    (20009001, "evmos"),
+];
+
+const EVM_ASSET_PLATFORMS: &[(u64, &str)] = &[
+    (1, "ethereum"),
+    (10, "optimistic-ethereum"),
+    (25, "cronos"),
+    (56, "binance-smart-chain"),
+    (100, "xdai"),
+    (137, "polygon-pos"),
+    (250, "fantom"),
+    (288, "boba"),
+    (324, "zksync"),
+    (1284, "moonbeam"),
+    (1285, "moonriver"),
+    (8453, "base"),
+    (42161, "arbitrum-one"),
+    (42220, "celo"),
+    (43114, "avalanche"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRICES: &str = r#"{
+        "latest": {"bitcoin": {"usd": 65000.5, "eur": 60000}, "dogecoin": {"usd": 0.00001234}},
+        "2024-01-01": {"bitcoin": {"USD": 42280.23}},
+        "tokens": {"ethereum": {"0xdAC17F958D2ee523a2206206994597C13D831ec7": "tether"}}
+    }"#;
+
+    #[test]
+    fn test_price_date() {
+        let date: PriceDate = "2024-02-29".parse().unwrap();
+        assert_eq!(
+            date,
+            PriceDate {
+                year: 2024,
+                month: 2,
+                day: 29
+            }
+        );
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert!("2023-02-29".parse::<PriceDate>().is_err());
+        assert!("2024-13-01".parse::<PriceDate>().is_err());
+        assert!("01-01-2024".parse::<PriceDate>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_static_prices() {
+        let prices = StaticPrices::from_json(PRICES).unwrap();
+
+        let latest = prices
+            .prices(&["bitcoin", "dogecoin", "unknown"], "usd")
+            .await
+            .unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest["bitcoin"].to_string(), "65000.5 USD");
+        assert_eq!(latest["dogecoin"].to_string(), "0.00001234 USD");
+
+        let date = "2024-01-01".parse().unwrap();
+        let price = prices
+            .historical_price("bitcoin", "usd", date)
+            .await
+            .unwrap();
+        assert_eq!(price.unwrap().to_string(), "42280.23 USD");
+        let date = "2024-01-02".parse().unwrap();
+        assert!(prices
+            .historical_price("bitcoin", "usd", date)
+            .await
+            .unwrap()
+            .is_none());
+
+        let usdt = TokenContract::new("ethereum", "0xdac17f958d2ee523a2206206994597c13d831ec7");
+        assert_eq!(
+            prices.token_info(&usdt).await.unwrap(),
+            Some(TokenInfo {
+                id: "tether".to_string(),
+                decimals: None
+            })
+        );
+        let usdt_on_tron = TokenContract::new("tron", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert!(prices.token_info(&usdt_on_tron).await.unwrap().is_none());
+
+        assert!(StaticPrices::from_json(r#"{"yesterday": {}}"#).is_err());
+        assert_eq!(price_id(0), Some("bitcoin"));
+        assert_eq!(asset_platform(966), Some("polygon-pos"));
+        assert_eq!(evm_asset_platform(42161), Some("arbitrum-one"));
+    }
+}
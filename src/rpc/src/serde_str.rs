@@ -0,0 +1,36 @@
+//! Deserializers of the values that the APIs encode as JSON strings, e.g. the balances that don't fit
+//! into a JavaScript number.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// Parses a value from a JSON string.
+pub(crate) fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse()
+        .map_err(|e| D::Error::custom(format!("Invalid value '{s}': {e}")))
+}
+
+/// Parses a value from a JSON string, which is `None` if the string is `null` or empty.
+pub(crate) fn deserialize_optional_from_str<'de, D, T>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => s
+            .parse()
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("Invalid value '{s}': {e}"))),
+        _ => Ok(None),
+    }
+}
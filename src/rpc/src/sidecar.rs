@@ -0,0 +1,168 @@
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
+use crate::serde_str::{deserialize_from_str, deserialize_optional_from_str};
+use crate::ss58;
+use crate::substrate_networks::SubstrateNetwork;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// The smallest units of the Substrate tokens, e.g. 1 DOT = 10^10 planck.
+pub type Planck = u128;
+
+/// A Substrate API Sidecar client: https://paritytech.github.io/substrate-api-sidecar/dist
+#[derive(Clone)]
+pub struct SidecarRpc {
+    transport: SharedTransport,
+    url: String,
+    /// The addresses are re-encoded with this prefix before requesting, if set.
+    ss58_prefix: Option<u16>,
+}
+
+impl SidecarRpc {
+    /// Creates a client of the given `network`, with the URL overridden by `network.sidecar_url_env` if set.
+    pub fn with_network(
+        transport: SharedTransport,
+        network: &SubstrateNetwork,
+    ) -> Result<Self, InvalidUri> {
+        let rpc = SidecarRpc::with_url(transport, network.configured_sidecar_url())?;
+        Ok(SidecarRpc {
+            ss58_prefix: Some(network.ss58_prefix),
+            ..rpc
+        })
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(SidecarRpc {
+            transport,
+            url,
+            ss58_prefix: None,
+        })
+    }
+
+    /// Requests the free, reserved and locked balances of the account at the latest block.
+    pub async fn balance_info(&self, address: &str) -> Result<BalanceInfo, HttpError> {
+        let address = match self.ss58_prefix {
+            Some(prefix) => {
+                ss58::reencode(address, prefix).map_err(|e| HttpError::InvalidUri(e.to_string()))?
+            }
+            None => address.to_string(),
+        };
+        self.get_json(&format!("/accounts/{address}/balance-info"))
+            .await
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let uri = format!("{}{path}", self.url).parse()?;
+        let res = self.transport.get(uri).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+}
+
+/// https://paritytech.github.io/substrate-api-sidecar/dist/#operations-accounts-getAccountBalanceInfo
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceInfo {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub nonce: u64,
+    pub token_symbol: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub free: Planck,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub reserved: Planck,
+    /// Replaced by `frozen` since the runtimes with the fungible traits.
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub misc_frozen: Option<Planck>,
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub fee_frozen: Option<Planck>,
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub frozen: Option<Planck>,
+    #[serde(default)]
+    pub locks: Vec<BalanceLock>,
+}
+
+impl BalanceInfo {
+    /// Returns the part of the free balance that can't be transferred, e.g. staked or voted with.
+    /// The locks overlap, so the locked balance is the largest of them rather than the sum.
+    pub fn locked(&self) -> Planck {
+        [self.misc_frozen, self.fee_frozen, self.frozen]
+            .into_iter()
+            .flatten()
+            .chain(self.locks.iter().map(|lock| lock.amount))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the free balance that can be transferred.
+    pub fn transferable(&self) -> Planck { self.free.saturating_sub(self.locked()) }
+
+    /// Returns the balances in the units of the network token, see [`SubstrateNetwork::coin`].
+    pub fn to_balance(&self, coin: &Coin) -> SubstrateBalance {
+        let amount = |planck: Planck| Amount::from_base_units(planck, coin.clone());
+        SubstrateBalance {
+            free: amount(self.free),
+            reserved: amount(self.reserved),
+            locked: amount(self.locked()),
+            transferable: amount(self.transferable()),
+        }
+    }
+}
+
+/// The balances of [`BalanceInfo`] in the units of the network token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubstrateBalance {
+    pub free: Amount,
+    pub reserved: Amount,
+    pub locked: Amount,
+    pub transferable: Amount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceLock {
+    /// E.g. `staking `, `democrac`.
+    pub id: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub amount: Planck,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const GENERIC: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const POLKADOT: &str = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+
+    #[tokio::test]
+    async fn test_sidecar_rpc_stub() {
+        let polkadot = SubstrateNetwork::by_coin_type(354).unwrap();
+        let transport = StubTransport::default().with_response(
+            &format!("/accounts/{POLKADOT}/balance-info"),
+            r#"{"at":{"hash":"0x00","height":"20000000"},"nonce":"12","tokenSymbol":"DOT",
+            "free":"150000000000","reserved":"20000000000","frozen":"100000000000",
+            "locks":[{"id":"staking ","amount":"100000000000","reasons":"All"},{"id":"pyconvot","amount":"50000000000","reasons":"All"}]}"#,
+        );
+        let rpc = SidecarRpc::with_network(Arc::new(transport), polkadot).unwrap();
+
+        // The generic address is re-encoded with the Polkadot prefix.
+        let info = rpc.balance_info(GENERIC).await.unwrap();
+        assert_eq!(info.nonce, 12);
+        assert_eq!(info.reserved, 20_000_000_000);
+        assert_eq!(info.locked(), 100_000_000_000);
+        assert_eq!(info.transferable(), 50_000_000_000);
+
+        let balance = info.to_balance(&polkadot.coin());
+        assert_eq!(balance.free.to_string(), "15 DOT");
+        assert_eq!(balance.transferable.to_string(), "5 DOT");
+    }
+}
@@ -0,0 +1,288 @@
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
+use crate::json_rpc;
+use crate::serde_str::deserialize_from_str;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const SOLANA_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// The SPL Token program, and the Token-2022 program that extends it.
+const TOKEN_PROGRAM_IDS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+];
+/// The maximum `limit` of `getSignaturesForAddress`.
+const SIGNATURES_PAGE_LIMIT: usize = 1_000;
+
+pub type Lamports = u64;
+
+/// A client of the Solana JSON-RPC API: https://solana.com/docs/rpc
+#[derive(Clone)]
+pub struct SolanaRpc {
+    transport: SharedTransport,
+    uri: Uri,
+}
+
+impl SolanaRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        SolanaRpc::with_url(transport, SOLANA_URL.to_string())
+            .expect("'SOLANA_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        Ok(SolanaRpc {
+            transport,
+            uri: url.parse()?,
+        })
+    }
+
+    /// Requests the SOL balance of the address.
+    pub async fn balance(&self, address: &str) -> Result<Amount, HttpError> {
+        let res: WithContext<Lamports> = self.request("getBalance", json!([address])).await?;
+        Ok(Amount::from_base_units(res.value, Coin::SOL))
+    }
+
+    /// Requests the account, or `None` if the account doesn't exist, i.e. it has never been funded.
+    pub async fn account_info(&self, address: &str) -> Result<Option<AccountInfo>, HttpError> {
+        let params = json!([address, {"encoding": "jsonParsed"}]);
+        let res: WithContext<Option<AccountInfo>> = self.request("getAccountInfo", params).await?;
+        Ok(res.value)
+    }
+
+    /// Requests the decimals of the SPL token `mint`.
+    pub async fn mint_decimals(&self, mint: &str) -> Result<u8, HttpError> {
+        let account = self
+            .account_info(mint)
+            .await?
+            .ok_or_else(|| HttpError::ApiError(format!("Mint account not found: {mint}")))?;
+        account.data["parsed"]["info"]["decimals"]
+            .as_u64()
+            .and_then(|decimals| u8::try_from(decimals).ok())
+            .ok_or_else(|| HttpError::InvalidPayload(format!("'{mint}' isn't a token mint")))
+    }
+
+    /// Requests the count of the transactions the address has taken part in.
+    pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
+        Ok(self.signatures(address).await?.len())
+    }
+
+    /// Requests all transaction signatures of the address, newest first.
+    pub async fn signatures(&self, address: &str) -> Result<Vec<SignatureInfo>, HttpError> {
+        let mut signatures: Vec<SignatureInfo> = Vec::new();
+        loop {
+            let before = signatures.last().map(|info| info.signature.as_str());
+            let page = self
+                .signatures_for_address(address, before, SIGNATURES_PAGE_LIMIT)
+                .await?;
+            let is_last_page = page.len() < SIGNATURES_PAGE_LIMIT;
+            signatures.extend(page);
+            if is_last_page {
+                return Ok(signatures);
+            }
+        }
+    }
+
+    /// Requests up to `limit` transaction signatures of the address, newest first,
+    /// starting before the `before` signature, if given.
+    pub async fn signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, HttpError> {
+        let mut config = json!({ "limit": limit });
+        if let Some(before) = before {
+            config["before"] = json!(before);
+        }
+        self.request("getSignaturesForAddress", json!([address, config]))
+            .await
+    }
+
+    /// Requests the SPL token accounts owned by the address, of both the Token and Token-2022 programs.
+    pub async fn token_accounts_by_owner(
+        &self,
+        owner: &str,
+    ) -> Result<Vec<TokenAccount>, HttpError> {
+        let mut accounts = Vec::new();
+        for program_id in TOKEN_PROGRAM_IDS {
+            let params = json!([owner, {"programId": program_id}, {"encoding": "jsonParsed"}]);
+            let res: WithContext<Vec<KeyedAccount>> =
+                self.request("getTokenAccountsByOwner", params).await?;
+            for keyed in res.value {
+                let info = keyed.account.data.parsed.info;
+                let token = Coin::token(info.mint.clone(), info.token_amount.decimals);
+                accounts.push(TokenAccount {
+                    address: keyed.pubkey,
+                    mint: info.mint,
+                    amount: Amount::from_base_units(info.token_amount.amount, token),
+                });
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Requests the non-zero SPL token balances of the address.
+    pub async fn token_balances(&self, owner: &str) -> Result<Vec<TokenAccount>, HttpError> {
+        let mut accounts = self.token_accounts_by_owner(owner).await?;
+        accounts.retain(|account| !account.amount.is_zero());
+        Ok(accounts)
+    }
+
+    async fn request<R>(&self, method: &str, params: Value) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        json_rpc::request(&self.transport, &self.uri, method, params).await
+    }
+}
+
+/// https://solana.com/docs/rpc/http/getsignaturesforaddress
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    /// `None` if the transaction succeeded.
+    pub err: Option<Value>,
+    pub block_time: Option<i64>,
+}
+
+/// https://solana.com/docs/rpc/http/getaccountinfo
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub lamports: Lamports,
+    /// The program owning the account, e.g. the System program for wallets.
+    pub owner: String,
+    pub executable: bool,
+    /// The parsed account data if the owner program is known to the node, otherwise `[data, encoding]`.
+    pub data: Value,
+}
+
+/// An SPL token account.
+#[derive(Clone, Debug)]
+pub struct TokenAccount {
+    pub address: String,
+    pub mint: String,
+    /// In the units of the mint, named after the mint address as SPL tokens have no on-chain symbol.
+    pub amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: ParsedTokenAccount,
+}
+
+#[derive(Deserialize)]
+struct ParsedTokenAccount {
+    data: ParsedTokenData,
+}
+
+#[derive(Deserialize)]
+struct ParsedTokenData {
+    parsed: ParsedToken,
+}
+
+#[derive(Deserialize)]
+struct ParsedToken {
+    info: ParsedTokenInfo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParsedTokenInfo {
+    mint: String,
+    token_amount: TokenAmount,
+}
+
+#[derive(Deserialize)]
+struct TokenAmount {
+    /// Token amounts are `u64` serialized as strings.
+    #[serde(deserialize_with = "deserialize_from_str")]
+    amount: u64,
+    decimals: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn token_account(mint: &str, amount: &str, decimals: u8) -> String {
+        format!(
+            r#"{{"pubkey":"Acc{mint}","account":{{"lamports":2039280,"owner":"{}","executable":false,
+            "data":{{"program":"spl-token","parsed":{{"type":"account","info":{{"mint":"{mint}","owner":"{ADDRESS}",
+            "tokenAmount":{{"amount":"{amount}","decimals":{decimals},"uiAmountString":"0"}}}}}}}}}}}}"#,
+            TOKEN_PROGRAM_IDS[0]
+        )
+    }
+
+    /// Answers as if the address had 2500 signatures, 1.5 SOL, 12.5 USDC and an empty token account.
+    fn stub_node(body: &[u8]) -> String {
+        let req: Value = serde_json::from_slice(body).unwrap();
+        let params = &req["params"];
+        let result = match req["method"].as_str().unwrap() {
+            "getBalance" => json!({"context": {"slot": 1}, "value": 1_500_000_000u64}),
+            "getSignaturesForAddress" => {
+                // Signatures are numbered from the newest `2499` down to `0`.
+                let before: usize = params[1]["before"]
+                    .as_str()
+                    .map_or(2500, |s| s.parse().unwrap());
+                let limit = params[1]["limit"].as_u64().unwrap() as usize;
+                let signatures: Vec<_> = (before.saturating_sub(limit)..before)
+                    .rev()
+                    .map(|i| json!({"signature": i.to_string(), "slot": i, "err": null, "blockTime": null}))
+                    .collect();
+                json!(signatures)
+            }
+            "getTokenAccountsByOwner" if params[1]["programId"] == TOKEN_PROGRAM_IDS[0] => {
+                let accounts = format!(
+                    "[{},{}]",
+                    token_account(USDC, "12500000", 6),
+                    token_account("Bonk", "0", 5)
+                );
+                json!({"context": {"slot": 1}, "value": serde_json::from_str::<Value>(&accounts).unwrap()})
+            }
+            "getTokenAccountsByOwner" => json!({"context": {"slot": 1}, "value": []}),
+            "getAccountInfo" if params[0] == USDC => json!({"context": {"slot": 1}, "value": {
+                "lamports": 1, "owner": TOKEN_PROGRAM_IDS[0], "executable": false,
+                "data": {"program": "spl-token", "parsed": {"type": "mint", "info": {"decimals": 6}}}
+            }}),
+            "getAccountInfo" => json!({"context": {"slot": 1}, "value": null}),
+            method => panic!("Unexpected method: {method}"),
+        };
+        json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_solana_rpc_stub() {
+        let transport = StubTransport::default().with_handler("/", stub_node);
+        let rpc = SolanaRpc::with_default_url(Arc::new(transport));
+
+        assert_eq!(rpc.balance(ADDRESS).await.unwrap().to_string(), "1.5 SOL");
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 2500);
+
+        let balances = rpc.token_balances(ADDRESS).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].mint, USDC);
+        assert_eq!(balances[0].amount.raw(), 12_500_000.into());
+        assert_eq!(balances[0].amount.coin().decimals, 6);
+
+        assert_eq!(rpc.mint_decimals(USDC).await.unwrap(), 6);
+        assert!(rpc.account_info(ADDRESS).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,110 @@
+//! SS58 addresses of the Substrate-based networks, e.g. Polkadot (prefix `0`) and Kusama (prefix `2`).
+//! https://docs.substrate.io/reference/address-formats
+
+use blake2::{Blake2b512, Digest};
+use std::fmt;
+
+const CHECKSUM_PREFIX: &[u8] = b"SS58PRE";
+const CHECKSUM_LEN: usize = 2;
+const PUBLIC_KEY_LEN: usize = 32;
+/// The prefixes below `64` are encoded in one byte, the others up to `16383` in two bytes.
+const SIMPLE_PREFIX_LIMIT: u16 = 64;
+const MAX_PREFIX: u16 = 16383;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidSs58Address(pub String);
+
+impl fmt::Display for InvalidSs58Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid SS58 address: '{}'", self.0)
+    }
+}
+
+/// Decodes the address into its network prefix and the account public key.
+pub fn decode(address: &str) -> Result<(u16, [u8; PUBLIC_KEY_LEN]), InvalidSs58Address> {
+    let invalid = || InvalidSs58Address(address.to_string());
+    let data = bs58::decode(address).into_vec().map_err(|_| invalid())?;
+
+    let (prefix, prefix_len) = match data.first() {
+        Some(first) if u16::from(*first) < SIMPLE_PREFIX_LIMIT => (u16::from(*first), 1),
+        Some(first) => {
+            let second = *data.get(1).ok_or_else(invalid)?;
+            let lower = (first << 2) | (second >> 6);
+            let upper = second & 0b0011_1111;
+            (u16::from(lower) | (u16::from(upper) << 8), 2)
+        }
+        None => return Err(invalid()),
+    };
+    if data.len() != prefix_len + PUBLIC_KEY_LEN + CHECKSUM_LEN {
+        return Err(invalid());
+    }
+
+    let (payload, checksum) = data.split_at(prefix_len + PUBLIC_KEY_LEN);
+    if checksum != &ss58_hash(payload)[..CHECKSUM_LEN] {
+        return Err(invalid());
+    }
+    let public_key = payload[prefix_len..]
+        .try_into()
+        .expect("Public key length is checked above");
+    Ok((prefix, public_key))
+}
+
+/// Encodes the account public key as an address of the network with the given `prefix`.
+pub fn encode(prefix: u16, public_key: &[u8; PUBLIC_KEY_LEN]) -> String {
+    assert!(prefix <= MAX_PREFIX, "SS58 prefix {prefix} is out of range");
+
+    let mut payload = if prefix < SIMPLE_PREFIX_LIMIT {
+        vec![prefix as u8]
+    } else {
+        let first = ((prefix & 0b1111_1100) >> 2) as u8 | 0b0100_0000;
+        let second = (prefix >> 8) as u8 | ((prefix & 0b11) << 6) as u8;
+        vec![first, second]
+    };
+    payload.extend(public_key);
+    let checksum = ss58_hash(&payload);
+    payload.extend(&checksum[..CHECKSUM_LEN]);
+    bs58::encode(payload).into_string()
+}
+
+/// Re-encodes the address of any network as an address of the network with the given `prefix`.
+pub fn reencode(address: &str, prefix: u16) -> Result<String, InvalidSs58Address> {
+    let (_prefix, public_key) = decode(address)?;
+    Ok(encode(prefix, &public_key))
+}
+
+fn ss58_hash(payload: &[u8]) -> Vec<u8> {
+    let mut hasher = Blake2b512::new();
+    hasher.update(CHECKSUM_PREFIX);
+    hasher.update(payload);
+    hasher.finalize().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Alice dev account.
+    const GENERIC: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const POLKADOT: &str = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+    const KUSAMA: &str = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F";
+
+    #[test]
+    fn test_ss58_reencode() {
+        let (prefix, public_key) = decode(GENERIC).unwrap();
+        assert_eq!(prefix, 42);
+        assert_eq!(
+            hex::encode(public_key),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+
+        assert_eq!(reencode(GENERIC, 0).unwrap(), POLKADOT);
+        assert_eq!(reencode(POLKADOT, 2).unwrap(), KUSAMA);
+        assert_eq!(reencode(KUSAMA, 42).unwrap(), GENERIC);
+
+        // Two-byte prefixes round-trip, e.g. `1284` of Moonbeam.
+        let moonbeam = encode(1284, &public_key);
+        assert_eq!(decode(&moonbeam).unwrap(), (1284, public_key));
+
+        assert!(decode("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ").is_err());
+    }
+}
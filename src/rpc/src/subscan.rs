@@ -0,0 +1,200 @@
+use crate::http::{header, HttpError, Request, SharedTransport};
+use crate::ss58;
+use crate::substrate_networks::SubstrateNetwork;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// The header to pass the Subscan API key in.
+const API_KEY_HEADER: &str = "X-API-Key";
+/// The maximum `row` of the Subscan list endpoints.
+const TRANSFERS_PAGE_ROWS: usize = 100;
+
+/// A client of a Subscan-compatible API: https://support.subscan.io
+#[derive(Clone)]
+pub struct SubscanRpc {
+    transport: SharedTransport,
+    url: String,
+    api_key: Option<String>,
+    /// The addresses are re-encoded with this prefix before requesting, if set.
+    ss58_prefix: Option<u16>,
+}
+
+impl SubscanRpc {
+    /// Creates a client of the Subscan API of the given `network`.
+    pub fn with_network(
+        transport: SharedTransport,
+        api_key: Option<String>,
+        network: &SubstrateNetwork,
+    ) -> Self {
+        let rpc = SubscanRpc::with_url(transport, api_key, network.subscan_url.to_string())
+            .expect("'SubstrateNetwork::subscan_url' is expected to be a valid URL");
+        SubscanRpc {
+            ss58_prefix: Some(network.ss58_prefix),
+            ..rpc
+        }
+    }
+
+    pub fn with_url(
+        transport: SharedTransport,
+        api_key: Option<String>,
+        url: String,
+    ) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(SubscanRpc {
+            transport,
+            url,
+            api_key,
+            ss58_prefix: None,
+        })
+    }
+
+    /// Requests the count of the incoming and outgoing transfers of the account.
+    pub async fn transfer_count(&self, address: &str) -> Result<usize, HttpError> {
+        let page = self.transfers_page(address, 0, 1).await?;
+        Ok(page.count)
+    }
+
+    /// Requests all transfers of the account page by page, newest first.
+    pub async fn transfers(&self, address: &str) -> Result<Vec<Transfer>, HttpError> {
+        let mut transfers = Vec::new();
+        for page in 0.. {
+            let res = self
+                .transfers_page(address, page, TRANSFERS_PAGE_ROWS)
+                .await?;
+            let is_last_page = res.transfers.len() < TRANSFERS_PAGE_ROWS;
+            transfers.extend(res.transfers);
+            if is_last_page || transfers.len() >= res.count {
+                break;
+            }
+        }
+        Ok(transfers)
+    }
+
+    /// Requests a page of the account transfers, newest first. The `page` starts from `0`.
+    pub async fn transfers_page(
+        &self,
+        address: &str,
+        page: usize,
+        row: usize,
+    ) -> Result<TransfersPage, HttpError> {
+        let address = match self.ss58_prefix {
+            Some(prefix) => {
+                ss58::reencode(address, prefix).map_err(|e| HttpError::InvalidUri(e.to_string()))?
+            }
+            None => address.to_string(),
+        };
+        let body = TransfersRequest { address, page, row };
+        self.post_json("/api/v2/scan/transfers", &body).await
+    }
+
+    async fn post_json<B, R>(&self, path: &str, body: &B) -> Result<R, HttpError>
+    where
+        B: Serialize,
+        R: DeserializeOwned,
+    {
+        let body =
+            serde_json::to_vec(body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        let mut request = Request::post(format!("{}{path}", self.url))
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        let res = self.transport.send(request.body(body)?).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        let res: SubscanResponse<R> =
+            serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        match res {
+            SubscanResponse {
+                code: 0,
+                data: Some(data),
+                ..
+            } => Ok(data),
+            SubscanResponse { code, message, .. } => {
+                Err(HttpError::ApiError(format!("{message} (code {code})")))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransfersPage {
+    /// The total count of the account transfers.
+    pub count: usize,
+    /// `null` if there are no transfers.
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub transfers: Vec<Transfer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Transfer {
+    pub from: String,
+    pub to: String,
+    /// The amount in tokens as a decimal string, e.g. `1.5`.
+    pub amount: String,
+    pub hash: String,
+    pub block_num: u64,
+    pub block_timestamp: u64,
+    pub success: bool,
+}
+
+#[derive(Serialize)]
+struct TransfersRequest {
+    address: String,
+    page: usize,
+    row: usize,
+}
+
+#[derive(Deserialize)]
+struct SubscanResponse<R> {
+    code: i64,
+    message: String,
+    data: Option<R>,
+}
+
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    const KUSAMA: &str = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F";
+    const POLKADOT: &str = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+
+    #[tokio::test]
+    async fn test_subscan_rpc_stub() {
+        let kusama = SubstrateNetwork::by_coin_type(434).unwrap();
+        let transport = StubTransport::default().with_handler("/api/v2/scan/transfers", |body| {
+            let req: Value = serde_json::from_slice(body).unwrap();
+            match req["address"].as_str().unwrap() {
+                KUSAMA => format!(
+                    r#"{{"code":0,"message":"Success","data":{{"count":3,"transfers":[{{"from":"{KUSAMA}","to":"{KUSAMA}",
+                    "amount":"1.5","hash":"0x01","block_num":1,"block_timestamp":1,"success":true}}]}}}}"#
+                ),
+                _ => r#"{"code":10004,"message":"Record Not Found","data":null}"#.to_string(),
+            }
+        });
+        let transport: SharedTransport = Arc::new(transport);
+        let rpc = SubscanRpc::with_network(transport.clone(), None, kusama);
+
+        // The Polkadot address is re-encoded with the Kusama prefix.
+        assert_eq!(rpc.transfer_count(POLKADOT).await.unwrap(), 3);
+
+        let rpc = SubscanRpc::with_url(transport, None, kusama.subscan_url.to_string()).unwrap();
+        let err = rpc.transfer_count(POLKADOT).await.err().unwrap();
+        assert!(matches!(err, HttpError::ApiError(_)), "{err}");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::amount::Coin;
+use std::borrow::Cow;
+use std::env;
+
+/// A Substrate-based network and the API instances to query it through.
+#[derive(Clone, Copy, Debug)]
+pub struct SubstrateNetwork {
+    /// The `trustwallet/wallet-core` coin type.
+    pub coin_type: u32,
+    /// The display symbol of the native token, e.g. `DOT`.
+    pub symbol: &'static str,
+    /// The SS58 address prefix, see [`crate::ss58`].
+    pub ss58_prefix: u16,
+    /// 1 DOT = 10^10 planck, 1 KSM = 10^12 planck.
+    pub decimals: u8,
+    /// The default Substrate API Sidecar instance URL.
+    pub sidecar_url: &'static str,
+    /// The environment variable to override the Sidecar URL with, e.g. to use a self-hosted instance.
+    pub sidecar_url_env: &'static str,
+    /// The Subscan API URL of the network.
+    pub subscan_url: &'static str,
+}
+
+impl SubstrateNetwork {
+    /// Returns the network by the `trustwallet/wallet-core` coin type.
+    pub fn by_coin_type(coin_type: u32) -> Option<&'static SubstrateNetwork> {
+        NETWORKS
+            .iter()
+            .find(|network| network.coin_type == coin_type)
+    }
+
+    pub fn all() -> &'static [SubstrateNetwork] { NETWORKS }
+
+    /// Returns the units of the native token, e.g. `planck` and `DOT`.
+    pub fn coin(&self) -> Coin {
+        Coin {
+            symbol: Cow::Borrowed(self.symbol),
+            base_unit: Cow::Borrowed("planck"),
+            decimals: self.decimals,
+            sub_units: &[],
+        }
+    }
+
+    /// Returns the URL from the `sidecar_url_env` environment variable if it's set, otherwise the default one.
+    pub fn configured_sidecar_url(&self) -> String {
+        env::var(self.sidecar_url_env).unwrap_or_else(|_| self.sidecar_url.to_string())
+    }
+}
+
+const NETWORKS: &[SubstrateNetwork] = &[
+    SubstrateNetwork {
+        coin_type: 354,
+        symbol: "DOT",
+        ss58_prefix: 0,
+        decimals: 10,
+        sidecar_url: "https://polkadot-public-sidecar.parity-chains.parity.io",
+        sidecar_url_env: "POLKADOT_SIDECAR_URL",
+        subscan_url: "https://polkadot.api.subscan.io",
+    },
+    SubstrateNetwork {
+        coin_type: 434,
+        symbol: "KSM",
+        ss58_prefix: 2,
+        decimals: 12,
+        sidecar_url: "https://kusama-public-sidecar.parity-chains.parity.io",
+        sidecar_url_env: "KUSAMA_SIDECAR_URL",
+        subscan_url: "https://kusama.api.subscan.io",
+    },
+];
@@ -0,0 +1,59 @@
+//! Test helpers shared by the RPC client tests.
+
+use crate::http::{HttpError, HttpTransport, Request, Response, StatusCode};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+type Handler = Box<dyn Fn(&[u8]) -> String + Send + Sync>;
+
+/// An `HttpTransport` that answers with canned responses instead of sending requests.
+/// Responses are looked up by the request path and query, e.g. `/api/address/bc1q...`.
+#[derive(Default)]
+pub(crate) struct StubTransport {
+    responses: HashMap<String, (StatusCode, Handler)>,
+}
+
+impl StubTransport {
+    pub(crate) fn with_response(self, path_and_query: &str, body: &str) -> Self {
+        let body = body.to_string();
+        self.with_handler(path_and_query, move |_| body.clone())
+    }
+
+    pub(crate) fn with_status(mut self, path_and_query: &str, status: StatusCode) -> Self {
+        self.responses.insert(
+            path_and_query.to_string(),
+            (status, Box::new(|_| String::default())),
+        );
+        self
+    }
+
+    /// Answers with a body built from the request body, e.g. to stub a JSON-RPC endpoint.
+    pub(crate) fn with_handler<F>(mut self, path_and_query: &str, handler: F) -> Self
+    where
+        F: Fn(&[u8]) -> String + Send + Sync + 'static,
+    {
+        self.responses.insert(
+            path_and_query.to_string(),
+            (StatusCode::OK, Box::new(handler)),
+        );
+        self
+    }
+}
+
+#[async_trait]
+impl HttpTransport for StubTransport {
+    async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, HttpError> {
+        let uri = request.uri();
+        let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or_default();
+        let (status, handler) = self
+            .responses
+            .get(path_and_query)
+            .ok_or_else(|| HttpError::TransportError(format!("No stub response for {uri}")))?;
+
+        let response = Response::builder()
+            .status(*status)
+            .body(handler(request.body()).into_bytes())
+            .expect("Stub response is expected to be valid");
+        Ok(response)
+    }
+}
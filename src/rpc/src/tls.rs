@@ -0,0 +1,477 @@
+//! The TLS settings of the [`HyperTransport`](crate::http::HyperTransport):
+//! the root certificates, e.g. the private CAs of the self-hosted nodes, and the public key pins of the hosts.
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::{fmt, io};
+
+/// The prefix of the verification error of a host whose certificates don't match its pins,
+/// followed by the host, see [`pin_mismatch`].
+const PIN_MISMATCH: &str = "Certificate public key pin mismatch: ";
+/// The prefix of the pins in the HPKP and `curl --pinnedpubkey` format.
+const SHA256_PIN_PREFIX: &str = "sha256/";
+
+const DER_SEQUENCE: u8 = 0x30;
+/// The optional `[0] EXPLICIT Version` of the `TBSCertificate`.
+const DER_CERTIFICATE_VERSION: u8 = 0xa0;
+
+/// The signature algorithms of the chains checked against a pinned intermediate or root, as rustls supports.
+static SIGNATURE_ALGORITHMS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+#[derive(Debug)]
+pub struct InvalidPin(String);
+
+impl fmt::Display for InvalidPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid public key pin: {}", self.0)
+    }
+}
+
+/// A trusted root certificate in the DER format.
+#[derive(Clone, Debug)]
+pub struct RootCertificate(Vec<u8>);
+
+impl RootCertificate {
+    pub fn from_der(der: Vec<u8>) -> Self { RootCertificate(der) }
+
+    /// Parses all certificates of a PEM file, e.g. the CA of a self-hosted node.
+    pub fn from_pem(mut pem: &[u8]) -> io::Result<Vec<RootCertificate>> {
+        let certificates = rustls_pemfile::certs(&mut pem)?;
+        if certificates.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "No certificates in the PEM file",
+            ));
+        }
+        Ok(certificates.into_iter().map(RootCertificate).collect())
+    }
+}
+
+/// The SHA-256 hash of the `SubjectPublicKeyInfo` of a certificate.
+/// Formatted as `sha256/<base64>`, as the HPKP pins and the `curl --pinnedpubkey` ones:
+///
+/// ```shell
+/// openssl x509 -in cert.pem -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpkiPin([u8; 32]);
+
+impl SpkiPin {
+    /// Returns the pin of the DER `certificate`, or `None` if the certificate can't be parsed.
+    pub fn of_certificate(certificate: &[u8]) -> Option<SpkiPin> {
+        let spki = subject_public_key_info(certificate)?;
+        Some(SpkiPin(Sha256::digest(spki).into()))
+    }
+}
+
+impl FromStr for SpkiPin {
+    type Err = InvalidPin;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hash = s.strip_prefix(SHA256_PIN_PREFIX).ok_or_else(|| {
+            InvalidPin(format!(
+                "'{s}' is expected to start with '{SHA256_PIN_PREFIX}'"
+            ))
+        })?;
+        let hash = base64::decode(hash).map_err(|e| InvalidPin(format!("'{s}': {e}")))?;
+        let hash = hash
+            .try_into()
+            .map_err(|_| InvalidPin(format!("'{s}' is expected to be a SHA-256 hash")))?;
+        Ok(SpkiPin(hash))
+    }
+}
+
+impl fmt::Display for SpkiPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SHA256_PIN_PREFIX}{}", base64::encode(self.0))
+    }
+}
+
+/// Builds the rustls config that trusts the native roots if `native_roots` is set, and the `roots`.
+/// The certificates of the hosts of the `pins` are checked against them after the usual verification.
+pub(crate) fn client_config(
+    native_roots: bool,
+    roots: &[RootCertificate],
+    pins: &HashMap<String, Vec<SpkiPin>>,
+) -> ClientConfig {
+    let verifier = pinning_verifier(native_roots, roots, pins);
+    ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth()
+}
+
+fn pinning_verifier(
+    native_roots: bool,
+    roots: &[RootCertificate],
+    pins: &HashMap<String, Vec<SpkiPin>>,
+) -> PinningVerifier {
+    let mut root_certs: Vec<_> = roots.iter().map(|root| root.0.clone()).collect();
+    if native_roots {
+        let native_certs = rustls_native_certs::load_native_certs()
+            .expect("could not load platform certs")
+            .into_iter()
+            .map(|cert| cert.0);
+        root_certs.extend(native_certs);
+    }
+    let mut root_store = RootCertStore::empty();
+    // Skip the certificates that can't be parsed, as `hyper-rustls` does.
+    root_store.add_parsable_certificates(&root_certs);
+
+    // Only the pinned roots are kept to check the chains against, not the whole native store.
+    let pinned_roots = root_certs
+        .into_iter()
+        .filter(|root| {
+            SpkiPin::of_certificate(root)
+                .is_some_and(|pin| pins.values().flatten().any(|p| *p == pin))
+        })
+        .map(Certificate)
+        .collect();
+    PinningVerifier {
+        inner: WebPkiVerifier::new(root_store, None),
+        pins: pins.clone(),
+        pinned_roots,
+    }
+}
+
+/// Returns the host whose certificates don't match its pins, if the request failed for that reason.
+pub(crate) fn pin_mismatch(error: &(dyn Error + 'static)) -> Option<String> {
+    let mut error = Some(error);
+    while let Some(e) = error {
+        if let Some(rustls::Error::InvalidCertificateData(message)) = e.downcast_ref() {
+            if let Some(host) = message.strip_prefix(PIN_MISMATCH) {
+                return Some(host.to_string());
+            }
+        }
+        // `io::Error::source` skips the wrapped error itself.
+        error = match e.downcast_ref::<io::Error>() {
+            Some(e) => e.get_ref().map(|e| e as &(dyn Error + 'static)),
+            None => e.source(),
+        };
+    }
+    None
+}
+
+/// Verifies the certificate chain with the `inner` verifier, then requires the end-entity certificate
+/// of a pinned host, an intermediate one that issued it, or the trusted root the chain is anchored at,
+/// to match its pins. So a host can be pinned to a private CA that it doesn't present.
+struct PinningVerifier {
+    inner: WebPkiVerifier,
+    pins: HashMap<String, Vec<SpkiPin>>,
+    /// The trusted roots that match any of the `pins`.
+    pinned_roots: Vec<Certificate>,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )?;
+
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_string(),
+            ServerName::IpAddress(ip) => ip.to_string(),
+            _ => return Ok(verified),
+        };
+        let pins = match self.pins.get(&host) {
+            Some(pins) => pins,
+            None => return Ok(verified),
+        };
+        let is_pinned = |certificate: &Certificate| {
+            SpkiPin::of_certificate(&certificate.0).is_some_and(|pin| pins.contains(&pin))
+        };
+        // The pins are public, so a pinned certificate appended to a chain it didn't issue must not match.
+        let is_pinned = is_pinned(end_entity)
+            || intermediates.iter().enumerate().any(|(i, intermediate)| {
+                let others: Vec<_> = intermediates
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other)| other.0.as_slice())
+                    .collect();
+                is_pinned(intermediate) && is_issued_by(end_entity, intermediate, &others, now)
+            })
+            || self.pinned_roots.iter().any(|root| {
+                let intermediates: Vec<_> = intermediates.iter().map(|i| i.0.as_slice()).collect();
+                // Several trusted roots can match the pins, so the one the chain is anchored at is required.
+                is_pinned(root) && is_issued_by(end_entity, root, &intermediates, now)
+            });
+        if is_pinned {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::InvalidCertificateData(format!(
+                "{PIN_MISMATCH}{host}"
+            )))
+        }
+    }
+}
+
+/// Whether the `end_entity` certificate chains up to the key of the `issuer` one, through the `intermediates`.
+fn is_issued_by(
+    end_entity: &Certificate,
+    issuer: &Certificate,
+    intermediates: &[&[u8]],
+    now: SystemTime,
+) -> bool {
+    let (Ok(end_entity), Ok(anchor), Ok(time)) = (
+        webpki::EndEntityCert::try_from(end_entity.0.as_slice()),
+        webpki::TrustAnchor::try_from_cert_der(&issuer.0),
+        webpki::Time::try_from(now),
+    ) else {
+        return false;
+    };
+    end_entity
+        .verify_is_valid_tls_server_cert(
+            SIGNATURE_ALGORITHMS,
+            &webpki::TlsServerTrustAnchors(&[anchor]),
+            intermediates,
+            time,
+        )
+        .is_ok()
+}
+
+/// Returns the DER `SubjectPublicKeyInfo` of the DER X.509 `certificate`.
+/// https://www.rfc-editor.org/rfc/rfc5280#section-4.1
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (certificate, _) = der_sequence(certificate)?;
+    let (tbs_certificate, _) = der_sequence(certificate)?;
+
+    let mut fields = tbs_certificate;
+    if fields.first() == Some(&DER_CERTIFICATE_VERSION) {
+        fields = der_element(fields)?.1;
+    }
+    // Skip the `serialNumber`, `signature`, `issuer`, `validity` and `subject`.
+    for _ in 0..5 {
+        fields = der_element(fields)?.1;
+    }
+    let (spki, _) = der_element(fields)?;
+    (spki.first() == Some(&DER_SEQUENCE)).then_some(spki)
+}
+
+/// Returns the content of the DER sequence at the start of the `input`, and the rest of the `input`.
+fn der_sequence(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    if input.first() != Some(&DER_SEQUENCE) {
+        return None;
+    }
+    let (element, rest) = der_element(input)?;
+    let header_len = element.len() - der_content_len(element)?;
+    Some((&element[header_len..], rest))
+}
+
+/// Splits the `input` into the DER element at its start, including the tag and length, and the rest.
+fn der_element(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    let content_len = der_content_len(input)?;
+    let header_len = match input.get(1)? {
+        len @ 0x81..=0x84 => 2 + usize::from(len - 0x80),
+        _ => 2,
+    };
+    let element_len = header_len.checked_add(content_len)?;
+    (input.len() >= element_len).then(|| input.split_at(element_len))
+}
+
+/// Returns the length of the content of the DER element at the start of the `input`.
+fn der_content_len(input: &[u8]) -> Option<usize> {
+    match *input.get(1)? {
+        len @ 0..=0x7f => Some(usize::from(len)),
+        len @ 0x81..=0x84 => {
+            let len_bytes = input.get(2..2 + usize::from(len - 0x80))?;
+            Some(
+                len_bytes
+                    .iter()
+                    .fold(0, |len, b| (len << 8) | usize::from(*b)),
+            )
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A self-signed test CA, valid until 2126.
+    const CA_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBizCCATGgAwIBAgIUZFLjXVV+2LaqKeMp4L6gJrltzrowCgYIKoZIzj0EAwIw
+EjEQMA4GA1UEAwwHVGVzdCBDQTAgFw0yNjEwMTkwNDA1NThaGA8yMTI2MDkyNTA0
+MDU1OFowEjEQMA4GA1UEAwwHVGVzdCBDQTBZMBMGByqGSM49AgEGCCqGSM49AwEH
+A0IABD5QOO+hMrK1KvnJxSNol1CuWp+zOkiqe7/SAxwX6DQVEepF6fk4JIr8eNJu
+1bVL6inUlEvdX6e/LWMUUl0UVnujYzBhMB0GA1UdDgQWBBTWWEdrLhvSStaj3BH9
+lbnQpy50JDAfBgNVHSMEGDAWgBTWWEdrLhvSStaj3BH9lbnQpy50JDAPBgNVHRMB
+Af8EBTADAQH/MA4GA1UdDwEB/wQEAwICBDAKBggqhkjOPQQDAgNIADBFAiAOlutg
+SJw+MpFfY8Au68H5OH+UcCE4zs9vqRne3Q2CWAIhAJn5mJffuf16nO6dd/QK0Jmx
+sSJbfisetFw0eeMg2Oj4
+-----END CERTIFICATE-----
+";
+    /// The `node.local` certificate issued by the test CA.
+    const NODE_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBpDCCAUqgAwIBAgIUO/YMWutmIM5BxESPq7pkrnYfdDAwCgYIKoZIzj0EAwIw
+EjEQMA4GA1UEAwwHVGVzdCBDQTAgFw0yNjEwMTkwNDA1NThaGA8yMTI2MDkyNTA0
+MDU1OFowFTETMBEGA1UEAwwKbm9kZS5sb2NhbDBZMBMGByqGSM49AgEGCCqGSM49
+AwEHA0IABP3O5TIVJ+UBqJmtjjvXO25oGNWIdHQH07bChi5jMQn77Spye9XCVGfZ
+HlfdGZTxeA+IneLqWnvdssiAVLd5wXCjeTB3MBUGA1UdEQQOMAyCCm5vZGUubG9j
+YWwwCQYDVR0TBAIwADATBgNVHSUEDDAKBggrBgEFBQcDATAdBgNVHQ4EFgQUlqhj
+SSBLoQUitxiZQ79PDvUvq20wHwYDVR0jBBgwFoAU1lhHay4b0krWo9wR/ZW50Kcu
+dCQwCgYIKoZIzj0EAwIDSAAwRQIhAN+/S/X1eMcU6ge+9K7JlcybtxZadhd115o4
+JQkGqAKqAiA+mzMC+cSs0zDi6c66M8/nt3wjjqD7lIeQVXP8YuCFwg==
+-----END CERTIFICATE-----
+";
+    /// Another self-signed test CA, trusted too.
+    const OTHER_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBjDCCATOgAwIBAgIUYqQhbggAbmg3K4LlERxhw8K7YXowCgYIKoZIzj0EAwIw
+EzERMA8GA1UEAwwIT3RoZXIgQ0EwIBcNMjYxMDE5MDQzNzI0WhgPMjEyNjA5MjUw
+NDM3MjRaMBMxETAPBgNVBAMMCE90aGVyIENBMFkwEwYHKoZIzj0CAQYIKoZIzj0D
+AQcDQgAE1MWX9TLvV8u0N4TYY8xL2Zy55M3IXjFilszzqPTe3Mo7JJvlGV2oxw0O
+8Za3CHn4kxdZJE+ZnqgqaobvdSoJ0aNjMGEwHQYDVR0OBBYEFBCigdfkXHqCDkpA
+dKMy7V9C/l2NMB8GA1UdIwQYMBaAFBCigdfkXHqCDkpAdKMy7V9C/l2NMA8GA1Ud
+EwEB/wQFMAMBAf8wDgYDVR0PAQH/BAQDAgIEMAoGCCqGSM49BAMCA0cAMEQCIGMq
+5cozPg+svDwn4FjScnccBGjmMjBH2OnztM8E4C+9AiBwIT6XX+gQxjI24bHaWwys
+42A6hoA+cBOSptQ+Ty6wlQ==
+-----END CERTIFICATE-----
+";
+    /// The `node.local` certificate issued by the other CA, as a mis-issued one.
+    const OTHER_NODE_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBpDCCAUugAwIBAgIUCyMgSmH0PFmAGXYzcfLlJl6CrV8wCgYIKoZIzj0EAwIw
+EzERMA8GA1UEAwwIT3RoZXIgQ0EwIBcNMjYxMDE5MDQzNzI0WhgPMjEyNjA5MjUw
+NDM3MjRaMBUxEzARBgNVBAMMCm5vZGUubG9jYWwwWTATBgcqhkjOPQIBBggqhkjO
+PQMBBwNCAATj3z7CthdDFoRHf8evdNZRLBsAdWIdBHc/eIYDLiNqRKoG3X+EQBGS
+5ubjVa+aWM14m0Ess18VcIULUMuO+GDbo3kwdzAVBgNVHREEDjAMggpub2RlLmxv
+Y2FsMAkGA1UdEwQCMAAwEwYDVR0lBAwwCgYIKwYBBQUHAwEwHQYDVR0OBBYEFGiZ
+oJkWG3dgurysryCCiIBNeg5DMB8GA1UdIwQYMBaAFBCigdfkXHqCDkpAdKMy7V9C
+/l2NMAoGCCqGSM49BAMCA0cAMEQCICT08+lqnqZ1DHeWBFe+u8vCYnflVckktUwH
+mYMcHmZAAiBZc+Ywv8zwC0xGGeKyedRHGz54P9hJywHMgKrvRXH4yA==
+-----END CERTIFICATE-----
+";
+    /// The pins computed with `openssl`.
+    const CA_PIN: &str = "sha256/ivlTyaiBcIfc82ubh9/nTBNP3lvsn2jLTcJ1kr/X0nk=";
+    const NODE_PIN: &str = "sha256/PxXCsV9zaeks903+rdgxqPr9uz/5xEH/mxVEDQd4QwQ=";
+
+    #[test]
+    fn test_spki_pin() {
+        let ca = RootCertificate::from_pem(CA_PEM.as_bytes()).unwrap();
+        let node = RootCertificate::from_pem(NODE_PEM.as_bytes()).unwrap();
+        assert_eq!(
+            SpkiPin::of_certificate(&ca[0].0).unwrap().to_string(),
+            CA_PIN
+        );
+        assert_eq!(
+            SpkiPin::of_certificate(&node[0].0).unwrap(),
+            NODE_PIN.parse().unwrap()
+        );
+
+        assert!(SpkiPin::of_certificate(b"\x30\x03\x02\x01\x01").is_none());
+        assert!("ivlTyaiBcIfc82ubh9/nTBNP3lvsn2jLTcJ1kr/X0nk="
+            .parse::<SpkiPin>()
+            .is_err());
+        assert!("sha256/AAAA".parse::<SpkiPin>().is_err());
+        assert!(RootCertificate::from_pem(b"").is_err());
+    }
+
+    #[test]
+    fn test_pinning_verifier() {
+        let certificate = |pem: &str| {
+            let certificate = RootCertificate::from_pem(pem.as_bytes()).unwrap().remove(0);
+            Certificate(certificate.0)
+        };
+        let mut roots = RootCertificate::from_pem(CA_PEM.as_bytes()).unwrap();
+        roots.extend(RootCertificate::from_pem(OTHER_CA_PEM.as_bytes()).unwrap());
+        let node = certificate(NODE_PEM);
+        let server_name = ServerName::try_from("node.local").unwrap();
+        // The test certificates are valid from October 2026.
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_900_000_000);
+        let verifier = |pins: &[&str]| {
+            let mut pinned = HashMap::new();
+            if !pins.is_empty() {
+                let pins = pins.iter().map(|pin| pin.parse().unwrap()).collect();
+                pinned.insert("node.local".to_string(), pins);
+            }
+            pinning_verifier(false, &roots, &pinned)
+        };
+        let verify_chain = |end_entity: &Certificate, intermediate: &Certificate, pins: &[&str]| {
+            let intermediates = [intermediate.clone()];
+            verifier(pins).verify_server_cert(
+                end_entity,
+                &intermediates,
+                &server_name,
+                &mut std::iter::empty(),
+                &[],
+                now,
+            )
+        };
+        let verify = |pins: &[&str]| {
+            verifier(pins).verify_server_cert(
+                &node,
+                &[],
+                &server_name,
+                &mut std::iter::empty(),
+                &[],
+                now,
+            )
+        };
+
+        assert!(verify(&[]).is_ok());
+        assert!(verify(&[CA_PIN, NODE_PIN]).is_ok());
+        // The CA that issued the node certificate, presented as an intermediate, matches.
+        let ca = Certificate(roots[0].0.clone());
+        assert!(verify_chain(&node, &ca, &[CA_PIN]).is_ok());
+        // The pinned CA appended to a chain it didn't issue doesn't match.
+        let other_node = certificate(OTHER_NODE_PEM);
+        assert!(verify_chain(&other_node, &ca, &[]).is_ok());
+        assert!(verify_chain(&other_node, &ca, &[CA_PIN]).is_err());
+        // The CA isn't presented by the host, but the chain is anchored at it.
+        assert!(verify(&[CA_PIN]).is_ok());
+        // The other trusted CA didn't issue the node certificate.
+        let other_ca_pin = SpkiPin::of_certificate(&roots[1].0).unwrap().to_string();
+        assert!(verify(&[&other_ca_pin]).is_err());
+        assert!(verify_chain(&other_node, &ca, &[&other_ca_pin]).is_ok());
+        let err = verify(&[&other_ca_pin]).err().unwrap();
+        // Wrapped as by `tokio-rustls` and `hyper-rustls`.
+        let err = io::Error::other(io::Error::new(io::ErrorKind::InvalidData, err));
+        assert_eq!(pin_mismatch(&err).as_deref(), Some("node.local"));
+
+        let verifier = pinning_verifier(false, &[], &HashMap::new());
+        let err = verifier
+            .verify_server_cert(&node, &[], &server_name, &mut std::iter::empty(), &[], now)
+            .err()
+            .unwrap();
+        // The chain isn't trusted without the test CA.
+        assert!(
+            matches!(err, rustls::Error::InvalidCertificateData(_)),
+            "{err}"
+        );
+        let err = io::Error::new(io::ErrorKind::InvalidData, err);
+        assert_eq!(pin_mismatch(&err), None);
+    }
+}
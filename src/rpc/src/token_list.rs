@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use std::{fmt, fs, io};
+
+/// The token list bundled with the app.
+const BUNDLED_TOKENS: &str = include_str!("../res/tokenlist.json");
+
+#[derive(Debug)]
+pub enum TokenListError {
+    Io(String),
+    InvalidFormat(String),
+}
+
+impl fmt::Display for TokenListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenListError::Io(e) => write!(f, "Error on reading the token list: {e}"),
+            TokenListError::InvalidFormat(e) => write!(f, "Invalid token list format: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for TokenListError {
+    fn from(e: io::Error) -> Self { TokenListError::Io(e.to_string()) }
+}
+
+impl From<serde_json::Error> for TokenListError {
+    fn from(e: serde_json::Error) -> Self { TokenListError::InvalidFormat(e.to_string()) }
+}
+
+/// A token of a Uniswap-style token list: https://github.com/Uniswap/token-lists
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub chain_id: u64,
+    /// The `0x`-prefixed contract address.
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// A list of ERC-20 tokens to look up balances of.
+#[derive(Debug, Deserialize)]
+pub struct TokenList {
+    pub name: String,
+    pub tokens: Vec<TokenInfo>,
+}
+
+impl TokenList {
+    /// Returns the token list bundled with the app.
+    pub fn bundled() -> TokenList {
+        TokenList::from_json(BUNDLED_TOKENS).expect("Bundled token list is expected to be valid")
+    }
+
+    /// Loads the token list from a file. See [`TokenList::from_json`].
+    pub fn from_file(path: &str) -> Result<TokenList, TokenListError> {
+        TokenList::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a token list in the format of https://uniswap.org/tokenlist.schema.json
+    /// The fields other than `name` and `tokens` are ignored.
+    pub fn from_json(json: &str) -> Result<TokenList, TokenListError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns the tokens of the chain, see https://chainlist.org for the chain ids.
+    pub fn tokens_for_chain(&self, chain_id: u64) -> impl Iterator<Item = &TokenInfo> {
+        self.tokens
+            .iter()
+            .filter(move |token| token.chain_id == chain_id)
+    }
+}
@@ -0,0 +1,507 @@
+use crate::account::AccountState;
+use crate::amount::{Amount, Coin};
+use crate::http::{header, HttpError, Request, SharedTransport};
+use http::request;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub use primitive_types::U256;
+
+const TRONGRID_URL: &str = "https://api.trongrid.io";
+/// The header to pass the TronGrid API key in. Requests without a key are rate-limited harder.
+const API_KEY_HEADER: &str = "TRON-PRO-API-KEY";
+
+/// The maximum `limit` of the TronGrid list endpoints.
+const PAGE_LIMIT: usize = 200;
+/// The prefix byte of the mainnet addresses, `T` in Base58.
+const ADDRESS_PREFIX: u8 = 0x41;
+/// The `type` of the TRC-20 transfers that move tokens, unlike `Approval`.
+const TRANSFER_EVENT: &str = "Transfer";
+
+/// 1 TRX = 1,000,000 sun.
+pub type Sun = i64;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidTronAddress(pub String);
+
+impl fmt::Display for InvalidTronAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid Tron address: '{}'", self.0)
+    }
+}
+
+impl From<InvalidTronAddress> for HttpError {
+    fn from(e: InvalidTronAddress) -> Self { HttpError::InvalidUri(e.to_string()) }
+}
+
+/// Converts a Base58Check address, e.g. `TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t`, to the hex form
+/// used by the full node API, e.g. `41a614f803b6fd780986a42c78ec9c7f77e6ded13c`.
+pub fn address_to_hex(address: &str) -> Result<String, InvalidTronAddress> {
+    let bytes = bs58::decode(address)
+        .with_check(None)
+        .into_vec()
+        .map_err(|_| InvalidTronAddress(address.to_string()))?;
+    if bytes.len() != 21 || bytes[0] != ADDRESS_PREFIX {
+        return Err(InvalidTronAddress(address.to_string()));
+    }
+    Ok(hex::encode(bytes))
+}
+
+/// Converts a hex address, e.g. `41a614f803b6fd780986a42c78ec9c7f77e6ded13c`, to the Base58Check form.
+pub fn address_from_hex(hex_address: &str) -> Result<String, InvalidTronAddress> {
+    let bytes =
+        hex::decode(hex_address).map_err(|_| InvalidTronAddress(hex_address.to_string()))?;
+    if bytes.len() != 21 || bytes[0] != ADDRESS_PREFIX {
+        return Err(InvalidTronAddress(hex_address.to_string()));
+    }
+    Ok(bs58::encode(bytes).with_check().into_string())
+}
+
+/// A client of the TronGrid-compatible HTTP API: https://developers.tron.network/reference/api-overview
+#[derive(Clone)]
+pub struct TronRpc {
+    transport: SharedTransport,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl TronRpc {
+    pub fn with_default_url(transport: SharedTransport, api_key: Option<String>) -> Self {
+        TronRpc::with_url(transport, api_key, TRONGRID_URL.to_string())
+            .expect("'TRONGRID_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(
+        transport: SharedTransport,
+        api_key: Option<String>,
+        url: String,
+    ) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(TronRpc {
+            transport,
+            url,
+            api_key,
+        })
+    }
+
+    /// Requests the account. TronGrid responds with no data if the account hasn't been activated.
+    pub async fn account(&self, address: &str) -> Result<AccountState<TronAccount>, HttpError> {
+        let res: ListResponse<TronAccount> =
+            self.get_json(&format!("/v1/accounts/{address}")).await?;
+        match res.data.into_iter().next() {
+            Some(account) => Ok(AccountState::Active(account)),
+            None => Ok(AccountState::NotActivated),
+        }
+    }
+
+    /// Requests the bandwidth and energy of the account.
+    pub async fn account_resource(&self, address: &str) -> Result<AccountResource, HttpError> {
+        // The full node API expects hex addresses unless `visible` is set.
+        let body = AddressRequest {
+            address: address_to_hex(address)?,
+        };
+        let body =
+            serde_json::to_vec(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+        let uri = format!("{}/wallet/getaccountresource", self.url);
+        let request = self
+            .request(Request::post(uri))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)?;
+        self.send(request).await
+    }
+
+    /// Requests the count of the account transactions, including the TRC-20 transfers.
+    pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
+        let txs: Vec<Transaction> = self
+            .list(&format!("/v1/accounts/{address}/transactions"))
+            .await?;
+        let trc20_txs = self.trc20_transfers(address).await?;
+        // A TRC-20 transfer is also listed as a `TriggerSmartContract` transaction if the account sent it.
+        let mut tx_ids: Vec<_> = txs.into_iter().map(|tx| tx.tx_id).collect();
+        tx_ids.extend(trc20_txs.into_iter().map(|tx| tx.transaction_id));
+        tx_ids.sort();
+        tx_ids.dedup();
+        Ok(tx_ids.len())
+    }
+
+    /// Requests all TRC-20 transfers of the account page by page, newest first.
+    pub async fn trc20_transfers(&self, address: &str) -> Result<Vec<Trc20Transfer>, HttpError> {
+        self.list(&format!("/v1/accounts/{address}/transactions/trc20"))
+            .await
+    }
+
+    /// Requests the non-zero TRC-20 balances of the account, with the token info of their latest transfers.
+    /// Receiving TRC-20 tokens doesn't activate an account, so the balances of an account that hasn't been
+    /// activated are summed up from its transfers.
+    pub async fn trc20_balances(&self, address: &str) -> Result<Vec<Trc20Balance>, HttpError> {
+        let account = match self.account(address).await?.active() {
+            Some(account) => account,
+            None => {
+                let transfers = self.trc20_transfers(address).await?;
+                return Ok(Trc20Balance::from_transfers(address, &transfers));
+            }
+        };
+
+        let mut balances = Vec::new();
+        for (contract_address, balance) in account.trc20.into_iter().flatten() {
+            if balance.is_zero() {
+                continue;
+            }
+            let path = format!(
+                "/v1/accounts/{address}/transactions/trc20?limit=1&contract_address={contract_address}"
+            );
+            let res: ListResponse<Trc20Transfer> = self.get_json(&path).await?;
+            let token_info = res
+                .data
+                .into_iter()
+                .next()
+                .map(|transfer| transfer.token_info);
+            // Without the token info, the balance is reported in the contract base units.
+            let token = match token_info {
+                Some(ref info) => Coin::token(info.symbol.clone(), info.decimals),
+                None => Coin::token(contract_address.clone(), 0),
+            };
+            balances.push(Trc20Balance {
+                token_info,
+                contract_address,
+                balance: Amount::from_base_units(balance, token),
+            });
+        }
+        Ok(balances)
+    }
+
+    /// Requests all records of the list `path` page by page, following the `fingerprint` of the last page.
+    async fn list<R>(&self, path: &str) -> Result<Vec<R>, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let mut records = Vec::new();
+        let mut fingerprint: Option<String> = None;
+        loop {
+            let cursor = fingerprint
+                .map(|fingerprint| format!("&fingerprint={fingerprint}"))
+                .unwrap_or_default();
+            let res: ListResponse<R> = self
+                .get_json(&format!("{path}?limit={PAGE_LIMIT}{cursor}"))
+                .await?;
+            records.extend(res.data);
+            fingerprint = res.meta.and_then(|meta| meta.fingerprint);
+            if fingerprint.is_none() {
+                return Ok(records);
+            }
+        }
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let request = self.request(Request::get(format!("{}{path}", self.url)));
+        self.send(request.body(Vec::new())?).await
+    }
+
+    /// Adds the API key, if any, to the `request`.
+    fn request(&self, request: request::Builder) -> request::Builder {
+        match &self.api_key {
+            Some(api_key) => request.header(API_KEY_HEADER, api_key),
+            None => request,
+        }
+    }
+
+    async fn send<R>(&self, request: Request<Vec<u8>>) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let res = self.transport.send(request).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+}
+
+/// https://developers.tron.network/reference/get-account-info-by-address
+#[derive(Debug, Deserialize)]
+pub struct TronAccount {
+    /// The hex address, see [`address_from_hex`].
+    pub address: String,
+    /// Absent if the account has no TRX.
+    #[serde(default = "zero_trx", deserialize_with = "deserialize_sun")]
+    pub balance: Amount,
+    /// The TRC-20 balances, each map is a contract address to a balance.
+    #[serde(default, deserialize_with = "deserialize_trc20")]
+    pub trc20: Vec<BTreeMap<String, U256>>,
+}
+
+/// https://developers.tron.network/reference/getaccountresource
+#[derive(Debug, Default, Deserialize)]
+pub struct AccountResource {
+    /// The daily free bandwidth.
+    #[serde(default, rename = "freeNetLimit")]
+    pub free_net_limit: i64,
+    #[serde(default, rename = "freeNetUsed")]
+    pub free_net_used: i64,
+    /// The bandwidth obtained by staking TRX.
+    #[serde(default, rename = "NetLimit")]
+    pub net_limit: i64,
+    #[serde(default, rename = "NetUsed")]
+    pub net_used: i64,
+    #[serde(default, rename = "EnergyLimit")]
+    pub energy_limit: i64,
+    #[serde(default, rename = "EnergyUsed")]
+    pub energy_used: i64,
+}
+
+impl AccountResource {
+    /// Returns the bandwidth left today, free and staked.
+    pub fn bandwidth(&self) -> i64 {
+        (self.free_net_limit - self.free_net_used) + (self.net_limit - self.net_used)
+    }
+
+    pub fn energy(&self) -> i64 { self.energy_limit - self.energy_used }
+}
+
+/// A transaction of the account, or an internal transaction of a contract call that sent TRX to or from it.
+#[derive(Debug, Deserialize)]
+pub struct Transaction {
+    /// The ID of the transaction, or of the contract call transaction of an internal one.
+    #[serde(rename = "txID", alias = "tx_id")]
+    pub tx_id: String,
+    pub block_timestamp: u64,
+}
+
+/// https://developers.tron.network/reference/get-trc20-transaction-info-by-account-address
+#[derive(Debug, Deserialize)]
+pub struct Trc20Transfer {
+    pub transaction_id: String,
+    /// `Transfer` or `Approval`.
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub token_info: Trc20TokenInfo,
+    pub block_timestamp: u64,
+    pub from: String,
+    pub to: String,
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub value: U256,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Trc20TokenInfo {
+    /// The Base58 contract address.
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+}
+
+/// A non-zero TRC-20 token holding.
+#[derive(Debug)]
+pub struct Trc20Balance {
+    pub contract_address: String,
+    /// In the token units if the token info is known.
+    pub balance: Amount,
+    /// Absent if the token has never been transferred to or from the account, e.g. if it was minted to it.
+    pub token_info: Option<Trc20TokenInfo>,
+}
+
+impl Trc20Balance {
+    /// Sums up the incoming and outgoing `transfers` of the `address` per token.
+    /// Returns the non-zero balances only.
+    pub fn from_transfers(address: &str, transfers: &[Trc20Transfer]) -> Vec<Trc20Balance> {
+        // contract => (incoming, outgoing, the token info)
+        let mut sums: BTreeMap<&str, (U256, U256, &Trc20TokenInfo)> = BTreeMap::new();
+        for transfer in transfers {
+            if transfer.event_type != TRANSFER_EVENT {
+                continue;
+            }
+            let info = &transfer.token_info;
+            let (incoming, outgoing, _) =
+                sums.entry(&info.address)
+                    .or_insert((U256::zero(), U256::zero(), info));
+            if transfer.to == address {
+                *incoming = incoming.saturating_add(transfer.value);
+            }
+            if transfer.from == address {
+                *outgoing = outgoing.saturating_add(transfer.value);
+            }
+        }
+
+        sums.into_iter()
+            .map(|(contract_address, (incoming, outgoing, info))| {
+                let token = Coin::token(info.symbol.clone(), info.decimals);
+                Trc20Balance {
+                    contract_address: contract_address.to_string(),
+                    balance: Amount::from_base_units(incoming.saturating_sub(outgoing), token),
+                    token_info: Some(info.clone()),
+                }
+            })
+            .filter(|balance| !balance.balance.is_zero())
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct ListResponse<R> {
+    data: Vec<R>,
+    meta: Option<ListMeta>,
+}
+
+#[derive(Deserialize)]
+struct ListMeta {
+    /// Present if there are more pages.
+    fingerprint: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AddressRequest {
+    address: String,
+}
+
+fn zero_trx() -> Amount { Amount::zero(Coin::TRX) }
+
+fn deserialize_sun<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let sun = Sun::deserialize(deserializer)?;
+    Ok(Amount::from_signed_base_units(i128::from(sun), Coin::TRX))
+}
+
+fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    U256::from_dec_str(&s).map_err(|e| D::Error::custom(format!("{e:?}")))
+}
+
+fn deserialize_trc20<'de, D>(deserializer: D) -> Result<Vec<BTreeMap<String, U256>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let maps = Vec::<BTreeMap<String, String>>::deserialize(deserializer)?;
+    maps.into_iter()
+        .map(|map| {
+            map.into_iter()
+                .map(|(contract, balance)| {
+                    let balance = U256::from_dec_str(&balance)
+                        .map_err(|e| D::Error::custom(format!("{e:?}")))?;
+                    Ok((contract, balance))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "TJRabPrwbZy45sbavfcjinPJC18kjpRTv8";
+    const UNFUNDED: &str = "TXuR8kJrpaL2wnv8vuvZWZqMhVbCVFrxXq";
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+    fn stub_trc20_transfer(tx_id: &str) -> String { stub_trc20_event(tx_id, "Transfer", ADDRESS) }
+
+    fn stub_trc20_event(tx_id: &str, event_type: &str, to: &str) -> String {
+        format!(
+            r#"{{"transaction_id":"{tx_id}","block_timestamp":1,"from":"{USDT}","to":"{to}","type":"{event_type}","value":"5000000",
+            "token_info":{{"symbol":"USDT","address":"{USDT}","decimals":6,"name":"Tether USD"}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_tron_address_hex() {
+        let hex_address = address_to_hex(USDT).unwrap();
+        assert_eq!(hex_address, "41a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(address_from_hex(&hex_address).unwrap(), USDT);
+        assert!(address_to_hex("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tron_rpc_stub() {
+        let hex_address = address_to_hex(ADDRESS).unwrap();
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}"),
+                &format!(
+                    r#"{{"data":[{{"address":"{hex_address}","balance":1500000,"create_time":1,
+                    "trc20":[{{"{USDT}":"5000000"}},{{"TEkxiTehnzSmSe2XqrBj4w32RUN966rdz8":"0"}}]}}],"success":true,"meta":{{"page_size":1}}}}"#
+                ),
+            )
+            .with_response(
+                &format!("/v1/accounts/{UNFUNDED}"),
+                r#"{"data":[],"success":true,"meta":{"page_size":0}}"#,
+            )
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}/transactions?limit={PAGE_LIMIT}"),
+                r#"{"data":[{"txID":"a","block_timestamp":3}],"success":true,"meta":{"page_size":1,"fingerprint":"next"}}"#,
+            )
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}/transactions?limit={PAGE_LIMIT}&fingerprint=next"),
+                r#"{"data":[{"txID":"b","block_timestamp":2},{"internal_tx_id":"d1","tx_id":"d","block_timestamp":2,
+                "from_address":"41a614f803b6fd780986a42c78ec9c7f77e6ded13c","to_address":"41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                "data":{"call_value":{"_":1000000},"note":"63616c6c","rejected":false}}],"success":true,"meta":{"page_size":2}}"#,
+            )
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}/transactions/trc20?limit={PAGE_LIMIT}"),
+                &format!(
+                    r#"{{"data":[{},{}],"success":true,"meta":{{"page_size":2}}}}"#,
+                    stub_trc20_transfer("b"),
+                    stub_trc20_transfer("c")
+                ),
+            )
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}/transactions/trc20?limit=1&contract_address={USDT}"),
+                &format!(
+                    r#"{{"data":[{}],"success":true,"meta":{{"page_size":1}}}}"#,
+                    stub_trc20_transfer("c")
+                ),
+            )
+            .with_response(
+                &format!("/v1/accounts/{UNFUNDED}/transactions/trc20?limit={PAGE_LIMIT}"),
+                &format!(
+                    r#"{{"data":[{},{}],"success":true,"meta":{{"page_size":2}}}}"#,
+                    stub_trc20_event("e", "Transfer", UNFUNDED),
+                    stub_trc20_event("f", "Approval", UNFUNDED)
+                ),
+            )
+            .with_handler("/wallet/getaccountresource", move |body| {
+                let req: Value = serde_json::from_slice(body).unwrap();
+                assert_eq!(req["address"], hex_address.as_str());
+                r#"{"freeNetLimit":600,"freeNetUsed":100,"EnergyLimit":1000}"#.to_string()
+            });
+        let rpc = TronRpc::with_default_url(Arc::new(transport), None);
+
+        let account = rpc.account(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(account.balance.to_string(), "1.5 TRX");
+        assert_eq!(address_from_hex(&account.address).unwrap(), ADDRESS);
+        assert!(!rpc.account(UNFUNDED).await.unwrap().is_activated());
+
+        // `b` is both a transaction and a TRC-20 transfer, `d` is an internal transaction.
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 4);
+
+        let balances = rpc.trc20_balances(ADDRESS).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].balance.to_string(), "5 USDT");
+        assert_eq!(balances[0].token_info.as_ref().unwrap().symbol, "USDT");
+
+        // Receiving TRC-20 tokens doesn't activate an account.
+        let balances = rpc.trc20_balances(UNFUNDED).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].contract_address, USDT);
+        assert_eq!(balances[0].balance.to_string(), "5 USDT");
+
+        let resource = rpc.account_resource(ADDRESS).await.unwrap();
+        assert_eq!(resource.bandwidth(), 500);
+        assert_eq!(resource.energy(), 1000);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::account::AccountState;
+use crate::activity::{Activity, ActivityRpc};
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
+use async_trait::async_trait;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const TZKT_URL: &str = "https://api.tzkt.io";
+
+/// The type TzKT reports for the addresses that have never been used.
+const EMPTY_ACCOUNT_TYPE: &str = "empty";
+
+/// 1 XTZ = 1,000,000 mutez.
+pub type Mutez = u64;
+
+/// A client of the TzKT Tezos API: https://api.tzkt.io
+#[derive(Clone)]
+pub struct TzktRpc {
+    transport: SharedTransport,
+    url: String,
+}
+
+impl TzktRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        TzktRpc::with_url(transport, TZKT_URL.to_string())
+            .expect("'TZKT_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        // Check if the given `url` is correct.
+        url.parse::<Uri>()?;
+        Ok(TzktRpc { transport, url })
+    }
+
+    /// Requests the account. TzKT responds with the `empty` type if the address has never been used.
+    pub async fn account(&self, address: &str) -> Result<AccountState<TezosAccount>, HttpError> {
+        let account: TezosAccount = self.get_json(&format!("/v1/accounts/{address}")).await?;
+        if account.account_type == EMPTY_ACCOUNT_TYPE {
+            return Ok(AccountState::NotActivated);
+        }
+        Ok(AccountState::Active(account))
+    }
+
+    /// Requests the count of the transaction operations the address is the sender or the target of.
+    pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
+        self.get_json(&format!(
+            "/v1/operations/transactions/count?anyof.sender.target={address}"
+        ))
+        .await
+    }
+
+    async fn get_json<R>(&self, path: &str) -> Result<R, HttpError>
+    where
+        R: DeserializeOwned,
+    {
+        let uri = format!("{}{path}", self.url).parse()?;
+        let res = self.transport.get(uri).await?;
+        HttpError::check_status(&res)?;
+
+        let (_parts, body) = res.into_parts();
+        serde_json::from_slice(&body).map_err(|e| HttpError::InvalidPayload(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl ActivityRpc for TzktRpc {
+    async fn activity(&self, address: &str) -> Result<AccountState<Activity>, HttpError> {
+        let account = match self.account(address).await? {
+            AccountState::Active(account) => account,
+            AccountState::NotActivated => return Ok(AccountState::NotActivated),
+        };
+        let transaction_count = self.transaction_count(address).await?;
+        Ok(AccountState::Active(Activity {
+            balance: Amount::from_base_units(account.balance, Coin::XTZ),
+            transaction_count: Some(transaction_count),
+        }))
+    }
+}
+
+/// https://api.tzkt.io/#operation/Accounts_GetByAddress
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TezosAccount {
+    /// `user`, `delegate`, `contract` or `empty`.
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub address: String,
+    /// Absent for the `empty` accounts.
+    #[serde(default)]
+    pub balance: Mutez,
+    /// The baker the account is delegated to.
+    pub delegate: Option<TezosDelegate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TezosDelegate {
+    pub address: String,
+    pub alias: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb";
+    const EMPTY: &str = "tz1burnburnburnburnburnburnburjAYjjX";
+
+    #[tokio::test]
+    async fn test_tzkt_rpc_stub() {
+        let transport = StubTransport::default()
+            .with_response(
+                &format!("/v1/accounts/{ADDRESS}"),
+                &format!(
+                    r#"{{"type":"user","address":"{ADDRESS}","balance":2500000,"counter":42,
+                    "delegate":{{"alias":"Baker","address":"tz1aRoaRhSpRYvFdyvgWLL6TGyRoGF51wDjM","active":true}}}}"#
+                ),
+            )
+            .with_response(
+                &format!("/v1/operations/transactions/count?anyof.sender.target={ADDRESS}"),
+                "17",
+            )
+            .with_response(
+                &format!("/v1/accounts/{EMPTY}"),
+                &format!(r#"{{"type":"empty","address":"{EMPTY}","counter":0}}"#),
+            );
+        let rpc = TzktRpc::with_default_url(Arc::new(transport));
+
+        let account = rpc.account(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(account.delegate.unwrap().alias.as_deref(), Some("Baker"));
+
+        let activity = rpc.activity(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(
+            activity,
+            Activity {
+                balance: Amount::from_base_units(2_500_000u64, Coin::XTZ),
+                transaction_count: Some(17)
+            }
+        );
+        assert!(!rpc.activity(EMPTY).await.unwrap().is_activated());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::account::AccountState;
+use crate::amount::{Amount, Coin};
+use crate::http::{HttpError, SharedTransport};
+use crate::json_rpc;
+use crate::serde_str::deserialize_from_str;
+use http::uri::InvalidUri;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+const XRPL_URL: &str = "https://s1.ripple.com:51234/";
+
+/// The maximum `limit` of `account_tx`.
+const ACCOUNT_TX_PAGE_LIMIT: usize = 400;
+/// The error rippled responds with if the account doesn't exist.
+const ACCOUNT_NOT_FOUND: &str = "actNotFound";
+
+/// 1 XRP = 1,000,000 drops.
+pub type Drops = u64;
+
+/// A client of the rippled JSON-RPC API: https://xrpl.org/docs/references/http-websocket-apis
+#[derive(Clone)]
+pub struct XrplRpc {
+    transport: SharedTransport,
+    uri: Uri,
+}
+
+impl XrplRpc {
+    pub fn with_default_url(transport: SharedTransport) -> Self {
+        XrplRpc::with_url(transport, XRPL_URL.to_string())
+            .expect("'XRPL_URL' is expected to be a valid URL")
+    }
+
+    pub fn with_url(transport: SharedTransport, url: String) -> Result<Self, InvalidUri> {
+        Ok(XrplRpc {
+            transport,
+            uri: url.parse()?,
+        })
+    }
+
+    /// Requests the account at the last validated ledger.
+    pub async fn account_info(
+        &self,
+        address: &str,
+    ) -> Result<AccountState<XrplAccount>, HttpError> {
+        let params = json!({"account": address, "ledger_index": "validated"});
+        match self
+            .request::<AccountInfoResult>("account_info", params)
+            .await
+        {
+            Ok(res) => Ok(AccountState::Active(res.account_data)),
+            Err(XrplError::Rpc(error)) if error == ACCOUNT_NOT_FOUND => {
+                Ok(AccountState::NotActivated)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Requests the count of the validated account transactions.
+    /// Returns `0` if the account isn't activated.
+    pub async fn transaction_count(&self, address: &str) -> Result<usize, HttpError> {
+        Ok(self.account_tx(address).await?.len())
+    }
+
+    /// Requests all validated account transactions page by page, newest first.
+    /// Returns no transactions if the account isn't activated.
+    pub async fn account_tx(&self, address: &str) -> Result<Vec<XrplTransaction>, HttpError> {
+        let mut txs = Vec::new();
+        let mut marker = None;
+        loop {
+            let mut params = json!({
+                "account": address,
+                "ledger_index_min": -1,
+                "ledger_index_max": -1,
+                "limit": ACCOUNT_TX_PAGE_LIMIT,
+            });
+            if let Some(marker) = marker {
+                params["marker"] = marker;
+            }
+
+            let page: AccountTxResult = match self.request("account_tx", params).await {
+                Ok(page) => page,
+                Err(XrplError::Rpc(error)) if error == ACCOUNT_NOT_FOUND => return Ok(txs),
+                Err(e) => return Err(e.into()),
+            };
+            txs.extend(page.transactions.into_iter().map(|item| item.tx));
+            // The marker is an opaque object that should be passed to the next request as is.
+            match page.marker {
+                Some(next_marker) => marker = Some(next_marker),
+                None => return Ok(txs),
+            }
+        }
+    }
+
+    /// rippled responds with errors in the `result` object rather than in the JSON-RPC `error`.
+    async fn request<R>(&self, method: &str, params: Value) -> Result<R, XrplError>
+    where
+        R: DeserializeOwned,
+    {
+        let result: Value =
+            json_rpc::request(&self.transport, &self.uri, method, json!([params])).await?;
+        if result["status"] == "error" {
+            let error = result["error"].as_str().unwrap_or_default().to_string();
+            return Err(XrplError::Rpc(error));
+        }
+        serde_json::from_value(result)
+            .map_err(|e| XrplError::Http(HttpError::InvalidPayload(e.to_string())))
+    }
+}
+
+/// https://xrpl.org/docs/references/protocol/ledger-data/ledger-entry-types/accountroot
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct XrplAccount {
+    pub account: String,
+    #[serde(deserialize_with = "deserialize_drops")]
+    pub balance: Amount,
+    pub sequence: u32,
+    /// The count of the objects the account owns, e.g. trust lines and offers, each adding to the reserve.
+    pub owner_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct XrplTransaction {
+    pub hash: String,
+    /// E.g. `Payment`, `TrustSet`.
+    #[serde(rename = "TransactionType")]
+    pub transaction_type: String,
+    #[serde(rename = "Account")]
+    pub account: String,
+    pub ledger_index: u64,
+}
+
+enum XrplError {
+    Http(HttpError),
+    /// The `error` code of the `result`, e.g. `actNotFound`.
+    Rpc(String),
+}
+
+impl From<HttpError> for XrplError {
+    fn from(e: HttpError) -> Self { XrplError::Http(e) }
+}
+
+impl From<XrplError> for HttpError {
+    fn from(e: XrplError) -> Self {
+        match e {
+            XrplError::Http(e) => e,
+            XrplError::Rpc(error) => HttpError::ApiError(error),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AccountInfoResult {
+    account_data: XrplAccount,
+}
+
+#[derive(Deserialize)]
+struct AccountTxResult {
+    transactions: Vec<AccountTxItem>,
+    marker: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct AccountTxItem {
+    tx: XrplTransaction,
+}
+
+fn deserialize_drops<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let drops: Drops = deserialize_from_str(deserializer)?;
+    Ok(Amount::from_base_units(drops, Coin::XRP))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::StubTransport;
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh";
+    const UNFUNDED: &str = "rGWrZyQqhTp9Xu7G5Pkayo7bXjH4k4QYpf";
+
+    fn stub_tx(hash: usize) -> Value {
+        json!({"tx": {"hash": hash.to_string(), "TransactionType": "Payment", "Account": ADDRESS, "ledger_index": hash}, "validated": true})
+    }
+
+    /// Answers as if the address had 450 transactions, and `UNFUNDED` didn't exist.
+    fn stub_node(body: &[u8]) -> String {
+        let req: Value = serde_json::from_slice(body).unwrap();
+        let params = &req["params"][0];
+        let result = match (
+            req["method"].as_str().unwrap(),
+            params["account"].as_str().unwrap(),
+        ) {
+            (_, UNFUNDED) => json!({
+                "account": UNFUNDED, "error": "actNotFound", "error_code": 19,
+                "error_message": "Account not found.", "status": "error", "validated": true
+            }),
+            ("account_info", _) => json!({
+                "account_data": {"Account": ADDRESS, "Balance": "25000000", "Flags": 0, "OwnerCount": 1, "Sequence": 7},
+                "status": "success", "validated": true
+            }),
+            ("account_tx", _) => match params.get("marker") {
+                None => json!({
+                    "account": ADDRESS, "status": "success", "marker": {"ledger": 100, "seq": 5},
+                    "transactions": (0..ACCOUNT_TX_PAGE_LIMIT).map(stub_tx).collect::<Vec<_>>()
+                }),
+                Some(marker) => {
+                    assert_eq!(marker, &json!({"ledger": 100, "seq": 5}));
+                    json!({
+                        "account": ADDRESS, "status": "success",
+                        "transactions": (ACCOUNT_TX_PAGE_LIMIT..450).map(stub_tx).collect::<Vec<_>>()
+                    })
+                }
+            },
+            (method, _) => panic!("Unexpected method: {method}"),
+        };
+        json!({ "result": result }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_xrpl_rpc_stub() {
+        let transport = StubTransport::default().with_handler("/", stub_node);
+        let rpc = XrplRpc::with_default_url(Arc::new(transport));
+
+        let account = rpc.account_info(ADDRESS).await.unwrap().active().unwrap();
+        assert_eq!(account.balance.to_string(), "25 XRP");
+        assert_eq!(account.owner_count, 1);
+        assert_eq!(rpc.transaction_count(ADDRESS).await.unwrap(), 450);
+
+        let unfunded = rpc.account_info(UNFUNDED).await.unwrap();
+        assert_eq!(unfunded.active().map(|account| account.balance), None);
+        assert_eq!(rpc.transaction_count(UNFUNDED).await.unwrap(), 0);
+    }
+}
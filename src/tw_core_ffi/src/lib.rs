@@ -1,3 +1,5 @@
+pub mod tw_bitcoin_script;
+pub mod tw_cardano;
 pub mod tw_coin_type;
 pub mod tw_data;
 pub mod tw_hd_wallet;
@@ -0,0 +1,77 @@
+use crate::tw_coin_type::{TWCoinType, TWCoinTypeRaw};
+use crate::tw_data::{TWData, TWDataRaw};
+use crate::tw_string::{TWString, TWStringRaw};
+
+pub struct TWBitcoinScript {
+    raw: *mut TWBitcoinScriptRaw,
+}
+
+impl TWBitcoinScript {
+    /// Builds an appropriate lock script for the given `address`.
+    /// Returns `None` if the `address` is invalid for the given `coin`.
+    pub fn lock_script_for_address<A>(address: A, coin: TWCoinType) -> Option<TWBitcoinScript>
+    where
+        A: Into<TWString>,
+    {
+        let address = address.into();
+
+        let raw = unsafe { TWBitcoinScriptLockScriptForAddress(address.as_ptr(), coin.as_raw()) };
+        let script = TWBitcoinScript { raw };
+
+        // `TWBitcoinScriptLockScriptForAddress` returns an empty script if the address is invalid.
+        if script.data().is_empty() {
+            return None;
+        }
+        Some(script)
+    }
+
+    /// Returns the script bytes.
+    pub fn data(&self) -> Vec<u8> {
+        let tw_data_raw = unsafe { TWBitcoinScriptData(self.raw) };
+        TWData::from_raw(tw_data_raw).to_vec()
+    }
+}
+
+impl Drop for TWBitcoinScript {
+    fn drop(&mut self) { unsafe { TWBitcoinScriptDelete(self.raw) } }
+}
+
+#[repr(C)]
+pub(crate) struct TWBitcoinScriptRaw {
+    private: [u8; 0],
+}
+
+extern "C" {
+    fn TWBitcoinScriptLockScriptForAddress(
+        address: *const TWStringRaw,
+        coin: TWCoinTypeRaw,
+    ) -> *mut TWBitcoinScriptRaw;
+
+    fn TWBitcoinScriptData(script: *const TWBitcoinScriptRaw) -> *mut TWDataRaw;
+
+    fn TWBitcoinScriptDelete(script: *mut TWBitcoinScriptRaw);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_script_for_address() {
+        let script = TWBitcoinScript::lock_script_for_address(
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            TWCoinType::TWCoinTypeBitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(script.data()),
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+
+        let invalid = TWBitcoinScript::lock_script_for_address(
+            "cosmos1mzfn3lk6f6vu6hnazc5fazxn9eme5acmay4p6m",
+            TWCoinType::TWCoinTypeBitcoin,
+        );
+        assert!(invalid.is_none());
+    }
+}
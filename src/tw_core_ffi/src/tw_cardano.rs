@@ -0,0 +1,42 @@
+use crate::tw_string::{TWString, TWStringRaw};
+
+/// Returns the reward (staking) address of the given Shelley base address, e.g. `stake1...` for `addr1...`.
+/// Returns `None` if the `base_address` is invalid or has no stake part.
+pub fn staking_address<A>(base_address: A) -> Option<String>
+where
+    A: Into<TWString>,
+{
+    let base_address = base_address.into();
+
+    let tw_string_raw = unsafe { TWCardanoGetStakingAddress(base_address.as_ptr()) };
+    let staking_address = TWString::from_raw(tw_string_raw)
+        .to_string()
+        .expect("'TWCardanoGetStakingAddress' should have returned a non UTF-8 string");
+    // `TWCardanoGetStakingAddress` returns an empty string if the address is invalid.
+    (!staking_address.is_empty()).then_some(staking_address)
+}
+
+extern "C" {
+    fn TWCardanoGetStakingAddress(base_address: *const TWStringRaw) -> *mut TWStringRaw;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tw_coin_type::TWCoinType;
+    use crate::tw_hd_wallet::TWHDWallet;
+
+    #[test]
+    fn test_cardano_staking_address() {
+        let tw_hd_wallet =
+            TWHDWallet::with_mnemonic("oil oil oil oil oil oil oil oil oil oil oil oil", "")
+                .unwrap();
+        let base_address = tw_hd_wallet.derive_default_address(TWCoinType::TWCoinTypeCardano);
+        assert!(base_address.starts_with("addr1"), "{base_address}");
+
+        let reward_address = staking_address(base_address).unwrap();
+        assert!(reward_address.starts_with("stake1"), "{reward_address}");
+
+        assert!(staking_address("bc1q98wufxmtfh5qlk7fe5dzy2z8cflvqjysrh4fx2").is_none());
+    }
+}
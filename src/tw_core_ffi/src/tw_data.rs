@@ -3,6 +3,18 @@ pub struct TWData {
 }
 
 impl TWData {
+    /// Copies the `TWData` bytes into `Vec<u8>`.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let bytes = unsafe { TWDataBytes(self.raw) };
+        let size = unsafe { TWDataSize(self.raw) };
+        if bytes.is_null() || size == 0 {
+            return Vec::new();
+        }
+        unsafe { std::slice::from_raw_parts(bytes, size) }.to_vec()
+    }
+
+    pub(crate) fn from_raw(raw: *mut TWDataRaw) -> TWData { TWData { raw } }
+
     pub(crate) fn as_ptr(&self) -> *const TWDataRaw { self.raw }
 }
 
@@ -29,5 +41,7 @@ pub(crate) struct TWDataRaw {
 
 extern "C" {
     fn TWDataCreateWithBytes(bytes: *const u8, size: usize) -> *mut TWDataRaw;
+    fn TWDataBytes(data: *const TWDataRaw) -> *const u8;
+    fn TWDataSize(data: *const TWDataRaw) -> usize;
     fn TWDataDelete(data: *mut TWDataRaw);
 }
@@ -69,6 +69,43 @@ impl TWHDWallet {
             .to_string()
             .expect("'TWHDWalletGetAddressForCoin' should have returned a non UTF-8 string")
     }
+
+    /// Derives the extended public key of the first account with the default purpose and version of the `coin`,
+    /// e.g. `zpub` for Litecoin BIP84. Returns `None` if the `coin` doesn't support extended keys.
+    pub fn derive_default_xpub(&self, coin: TWCoinType) -> Option<String> {
+        let tw_string_raw = unsafe {
+            TWHDWalletGetExtendedPublicKey(
+                self.raw,
+                TWCoinTypePurpose(coin.as_raw()),
+                coin.as_raw(),
+                TWCoinTypeXpubVersion(coin.as_raw()),
+            )
+        };
+        let xpub = TWString::from_raw(tw_string_raw)
+            .to_string()
+            .expect("'TWHDWalletGetExtendedPublicKey' should have returned a non UTF-8 string");
+        // An empty string is returned if the `coin` has no extended public key version.
+        (!xpub.is_empty()).then_some(xpub)
+    }
+
+    /// Derives the address of the first account with the default purpose of the UTXO `coin`,
+    /// at the `change` chain, `0` for the receive addresses and `1` for the change ones, and the `index`.
+    /// E.g. `m/84'/0'/0'/1/5` for the sixth Bitcoin change address.
+    pub fn derive_address(&self, coin: TWCoinType, change: u32, index: u32) -> String {
+        let default_path = TWString::from_raw(unsafe { TWCoinTypeDerivationPath(coin.as_raw()) })
+            .to_string()
+            .expect("'TWCoinTypeDerivationPath' should have returned a non UTF-8 string");
+        // Replace the `change` and `address_index` of the default path, e.g. `m/84'/0'/0'/0/0`.
+        let account_path = default_path.rsplitn(3, '/').last().unwrap_or_default();
+        let path = TWString::from(format!("{account_path}/{change}/{index}"));
+
+        let private_key = unsafe { TWHDWalletGetKey(self.raw, coin.as_raw(), path.as_ptr()) };
+        let tw_string_raw = unsafe { TWCoinTypeDeriveAddress(coin.as_raw(), private_key) };
+        unsafe { TWPrivateKeyDelete(private_key) };
+        TWString::from_raw(tw_string_raw)
+            .to_string()
+            .expect("'TWCoinTypeDeriveAddress' should have returned a non UTF-8 string")
+    }
 }
 
 impl Drop for TWHDWallet {
@@ -80,6 +117,16 @@ pub(crate) struct TWHDWalletRaw {
     private: [u8; 0],
 }
 
+#[repr(C)]
+struct TWPrivateKeyRaw {
+    private: [u8; 0],
+}
+
+/// `enum TWPurpose`, e.g. `84` for BIP84.
+type TWPurposeRaw = u32;
+/// `enum TWHDVersion`, e.g. `0x04b24746` for `zpub`.
+type TWHDVersionRaw = u32;
+
 extern "C" {
     /// Returns `nullptr` if the given `mnemonic` is invalid.
     fn TWHDWalletCreateWithMnemonicCheck(
@@ -102,6 +149,32 @@ extern "C" {
         coin: TWCoinTypeRaw,
     ) -> *mut TWStringRaw;
 
+    fn TWHDWalletGetExtendedPublicKey(
+        wallet: *const TWHDWalletRaw,
+        purpose: TWPurposeRaw,
+        coin: TWCoinTypeRaw,
+        version: TWHDVersionRaw,
+    ) -> *mut TWStringRaw;
+
+    fn TWHDWalletGetKey(
+        wallet: *const TWHDWalletRaw,
+        coin: TWCoinTypeRaw,
+        derivation_path: *const TWStringRaw,
+    ) -> *mut TWPrivateKeyRaw;
+
+    fn TWCoinTypeDerivationPath(coin: TWCoinTypeRaw) -> *mut TWStringRaw;
+
+    fn TWCoinTypeDeriveAddress(
+        coin: TWCoinTypeRaw,
+        private_key: *const TWPrivateKeyRaw,
+    ) -> *mut TWStringRaw;
+
+    fn TWPrivateKeyDelete(private_key: *mut TWPrivateKeyRaw);
+
+    fn TWCoinTypePurpose(coin: TWCoinTypeRaw) -> TWPurposeRaw;
+
+    fn TWCoinTypeXpubVersion(coin: TWCoinTypeRaw) -> TWHDVersionRaw;
+
     fn TWHDWalletDelete(wallet: *mut TWHDWalletRaw);
 }
 
@@ -174,4 +247,37 @@ mod tests {
         let actual = tw_hd_wallet.derive_default_address(TWCoinType::TWCoinTypeBitcoin);
         assert_eq!(actual, "bc1q98wufxmtfh5qlk7fe5dzy2z8cflvqjysrh4fx2");
     }
+
+    #[test]
+    fn test_hd_wallet_derive_address_at_index() {
+        let tw_hd_wallet = TWHDWallet::with_mnemonic(
+            "oil oil oil oil oil oil oil oil oil oil oil oil",
+            PASSPHRASE,
+        )
+        .unwrap();
+        // The default address is the first receive one.
+        let actual = tw_hd_wallet.derive_address(TWCoinType::TWCoinTypeBitcoin, 0, 0);
+        assert_eq!(actual, "bc1q98wufxmtfh5qlk7fe5dzy2z8cflvqjysrh4fx2");
+        let change = tw_hd_wallet.derive_address(TWCoinType::TWCoinTypeBitcoin, 1, 0);
+        assert!(change.starts_with("bc1q"), "{change}");
+        assert_ne!(change, actual);
+    }
+
+    #[test]
+    fn test_hd_wallet_derive_xpub() {
+        let tw_hd_wallet = TWHDWallet::with_mnemonic(
+            "oil oil oil oil oil oil oil oil oil oil oil oil",
+            PASSPHRASE,
+        )
+        .unwrap();
+        // Bitcoin uses BIP84 by default, Dogecoin has its own extended key version.
+        let bitcoin_xpub = tw_hd_wallet
+            .derive_default_xpub(TWCoinType::TWCoinTypeBitcoin)
+            .unwrap();
+        assert!(bitcoin_xpub.starts_with("zpub"), "{bitcoin_xpub}");
+        let dogecoin_xpub = tw_hd_wallet
+            .derive_default_xpub(TWCoinType::TWCoinTypeDogecoin)
+            .unwrap();
+        assert!(dogecoin_xpub.starts_with("dgub"), "{dogecoin_xpub}");
+    }
 }
@@ -1,4 +1,6 @@
 use std::ops::RangeInclusive;
+use tw_core_ffi::tw_bitcoin_script::TWBitcoinScript;
+use tw_core_ffi::tw_cardano;
 use tw_core_ffi::tw_hd_wallet::TWHDWallet;
 
 pub use tw_core_ffi::tw_coin_type::TWCoinType as CoinType;
@@ -39,6 +41,27 @@ impl HDWallet {
         self.inner.derive_default_address(coin)
     }
 
+    /// Derives the extended public key of the default account for the given UTXO `coin`.
+    /// Returns `None` if the `coin` doesn't support extended keys.
+    pub fn derive_default_xpub(&self, coin: CoinType) -> Option<String> {
+        self.inner.derive_default_xpub(coin)
+    }
+
+    /// Derives the lock script (`scriptPubKey`) of the address at the `change` chain and `index`
+    /// of the default account for the given UTXO `coin`. Returns `None` if the `coin` doesn't use Bitcoin scripts.
+    pub fn derive_lock_script(&self, coin: CoinType, change: u32, index: u32) -> Option<Vec<u8>> {
+        let address = self.inner.derive_address(coin, change, index);
+        TWBitcoinScript::lock_script_for_address(address, coin).map(|script| script.data())
+    }
+
+    /// Derives the Cardano Shelley base address (`addr1...`) and its reward address (`stake1...`)
+    /// of the default account, purpose 1852.
+    pub fn derive_cardano_addresses(&self) -> (String, Option<String>) {
+        let base_address = self.derive_default_address(CoinType::TWCoinTypeCardano);
+        let reward_address = tw_cardano::staking_address(base_address.as_str());
+        (base_address, reward_address)
+    }
+
     /// Derives default addresses for the given `coins`.
     pub fn derive_default_addresses<'a, 'b, I>(
         &'b self,
@@ -1,27 +1,78 @@
-use std::collections::BTreeSet;
+use futures::StreamExt;
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use std::{env, io};
 
 mod hd_wallet;
+mod portfolio;
+mod scheduler;
 mod wallet_input;
 
 use hd_wallet::{CoinType, HDWallet};
-use wallet_input::WalletInput;
+use portfolio::{Portfolio, TokenAmount};
+use rpc::account::AccountState;
+use rpc::activity::ActivityRpc;
+use rpc::amount::{Amount, Coin};
+use rpc::blockbook_instances::BlockbookInstance;
+use rpc::blockstream::{AddressInfo, BlockstreamRpc};
+use rpc::cache::CachingTransport;
+use rpc::cosmos_registry::{CosmosChain, CosmosChainRegistry};
+use rpc::electrum::ElectrumRpc;
+use rpc::etherscan::{EtherscanRpc, TokenBalance};
+use rpc::etherscan_explorers::EtherscanExplorer;
+use rpc::failover::{Endpoint, FailoverTransport, Quorum};
+use rpc::http::{IsolatedTransport, SharedTransport};
+use rpc::price::{self, PriceDate, PriceSource, StaticPrices, TokenContract};
+use rpc::substrate_networks::SubstrateNetwork;
+use rpc::tls::RootCertificate;
+use rpc::token_list::TokenList;
+use rpc::transport_config::TransportConfig;
+use scheduler::{QueryTimeout, Report, Scheduler};
+use wallet_input::{CmdArgs, WalletInput};
 
 const DEFAULT_PASSPHRASE: &str = "";
+/// The number of the chains queried at once, see `MAX_CONCURRENT_QUERIES`.
+const DEFAULT_MAX_CONCURRENT_QUERIES: usize = 8;
+/// The time to query the activity of one chain, see `QUERY_TIMEOUT_SECS`.
+const DEFAULT_QUERY_TIMEOUT_SECS: u64 = 30;
+/// The receive and the change address chains of a BIP44 account.
+const BIP44_CHAINS: [u32; 2] = [0, 1];
 
 fn help() {
     println!("Input: As command-line argument, a BIP39 mnemonic or a mnemonic entropy (hex string) is also accepted (16-32 bytes)");
+    println!("Options: '--proxy-only' to connect through the 'HTTPS_PROXY' only, refusing any direct connection");
+    println!(
+        "         '--quorum' to compare the answers of two providers, where a chain has a few"
+    );
+    println!(
+        "         '--offline' to answer from the 'CACHE_DIR' only, without sending any request"
+    );
 }
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
-    let input = match WalletInput::parse_cmd_args() {
-        Ok(input) => input,
+    let CmdArgs {
+        input,
+        proxy_only,
+        quorum,
+        offline,
+    } = match CmdArgs::parse() {
+        Ok(args) => args,
         Err(e) => {
             help();
             return Err(e.into());
         }
     };
+    if proxy_only && env::var("HTTPS_PROXY").is_err() {
+        let error = "Set 'HTTPS_PROXY' environment variable to connect with '--proxy-only'";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, error));
+    }
+    if offline && env::var("CACHE_DIR").is_err() {
+        let error = "Set 'CACHE_DIR' environment variable to run with '--offline'";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, error));
+    }
 
     let hd_wallet = match input {
         WalletInput::Mnemonic(mnemonic) => {
@@ -35,11 +86,13 @@ async fn main() -> io::Result<()> {
     };
 
     // Order the addresses alphabetically and remove duplicates by using `BTreeSet`.
-    let addresses: BTreeSet<_> = hd_wallet
+    let mut addresses: BTreeSet<_> = hd_wallet
         .derive_default_addresses(CoinType::iter_coins())
         .collect();
+    let (_cardano_address, cardano_reward_address) = hd_wallet.derive_cardano_addresses();
+    addresses.extend(cardano_reward_address);
 
-    print_activity(&hd_wallet).await;
+    print_activity(&hd_wallet, proxy_only, quorum, offline).await;
 
     println!("\nAll addresses:");
     for address in addresses {
@@ -48,56 +101,1160 @@ async fn main() -> io::Result<()> {
 
     Ok(())
 }
-
-/// Prints out, if, for the BTC, ETH, and ATOM addresses there is a current balance
-/// and whether there were any transactions on the address.
-async fn print_activity(hd_wallet: &HDWallet) {
+/// Prints out, if, for the BTC and other UTXO coins, SOL, XRP, XLM, TRX, DOT, KSM, ADA, XTZ, ALGO, NEAR, APT,
+/// EVM and Cosmos-SDK chains addresses there is a current balance and whether there were any transactions on the address.
+/// The XRP, XLM, TRX, XTZ, ALGO, NEAR and APT addresses that have never been funded are reported as not activated.
+/// The chains are queried concurrently, and the activity of each chain is printed as soon as it's finished.
+/// Each chain is queried over its own connections, see [`isolated`].
+/// The native balances are summed up into a portfolio valued in a fiat currency at the end.
+/// The `GET` responses are cached on disk if `CACHE_DIR` is set, see [`cached`].
+async fn print_activity(hd_wallet: &HDWallet, proxy_only: bool, quorum: bool, offline: bool) {
     println!("Activity:");
 
-    let transport = rpc::http::HttpBuilder::build();
+    let config = match transport_config(proxy_only) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let is_proxied = config.proxy().is_some();
+    // The Electrum connection is verified with the same TLS settings.
+    let transport = rpc::http::HttpBuilder::build(config.clone());
+    let transport = match cached(transport, offline) {
+        Ok(transport) => transport,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let transport = &transport;
+
+    let token_list = match env::var("TOKEN_LIST") {
+        Ok(path) => match TokenList::from_file(&path) {
+            Ok(token_list) => token_list,
+            Err(e) => {
+                eprintln!("{e}");
+                TokenList::bundled()
+            }
+        },
+        Err(_) => TokenList::bundled(),
+    };
+    let cosmos_registry = match env::var("COSMOS_CHAIN_REGISTRY") {
+        Ok(path) => match CosmosChainRegistry::from_file(&path) {
+            Ok(registry) => registry,
+            Err(e) => {
+                eprintln!("{e}");
+                CosmosChainRegistry::bundled()
+            }
+        },
+        Err(_) => CosmosChainRegistry::bundled(),
+    };
+
+    let max_concurrent = env_number("MAX_CONCURRENT_QUERIES", DEFAULT_MAX_CONCURRENT_QUERIES);
+    let timeout = Duration::from_secs(env_number("QUERY_TIMEOUT_SECS", DEFAULT_QUERY_TIMEOUT_SECS));
+    let mut scheduler = Scheduler::new(max_concurrent, timeout);
+
     let bitcoin_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeBitcoin);
-    let eth_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeEthereum);
-    let cosmos_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeCosmos);
+    let esplora_urls = match env::var("ESPLORA_URLS") {
+        Ok(urls) => urls.split(',').map(|url| url.trim().to_string()).collect(),
+        Err(_) => vec![
+            rpc::blockstream::BLOCKSTREAM_URL.to_string(),
+            rpc::blockstream::MEMPOOL_SPACE_URL.to_string(),
+        ],
+    };
+    let transport_btc = isolated(transport, "BTC");
+    let query = btc_activity(bitcoin_address, esplora_urls, quorum, transport_btc);
+    scheduler.add("BTC", query);
+
+    if let Ok(electrum_url) = env::var("ELECTRUM_URL") {
+        if is_proxied {
+            // The Electrum protocol runs over a plain TCP connection that bypasses the proxy.
+            eprintln!("Electrum server is not queried through a proxy");
+        } else if offline {
+            // The Electrum responses aren't HTTP ones, so they aren't cached.
+            eprintln!("Electrum server is not queried with '--offline'");
+        } else {
+            let query = electrum_activity(hd_wallet, electrum_url, &config);
+            scheduler.add("BTC, Electrum", query);
+        }
+    }
+
+    // Several explorers share an API key, e.g. `ETHERSCAN_APIKEY`, so the missing ones are listed once.
+    let mut missing_api_keys = BTreeSet::new();
+    let mut missing_symbols = Vec::new();
+    for coin in CoinType::iter_coins() {
+        let explorer = match EtherscanExplorer::by_coin_type(coin.as_raw()) {
+            Some(explorer) => explorer,
+            None => continue,
+        };
+        let (symbol, api_key_env) = (explorer.symbol, explorer.api_key_env);
+        match env::var(api_key_env) {
+            Ok(api_key) => {
+                let eth_address = hd_wallet.derive_default_address(coin);
+                let urls = env::var(explorer.urls_env)
+                    .ok()
+                    .map(|urls| urls.split(',').map(|url| url.trim().to_string()).collect());
+                let transport = isolated(transport, symbol);
+                scheduler.add(
+                    symbol,
+                    eth_activity(eth_address, explorer, urls, transport, api_key),
+                );
+            }
+            Err(_) => {
+                missing_api_keys.insert(format!("'{api_key_env}'"));
+                missing_symbols.push(symbol);
+            }
+        }
+    }
+    if !missing_api_keys.is_empty() {
+        let api_keys = missing_api_keys.into_iter().collect::<Vec<_>>().join(", ");
+        let symbols = missing_symbols.join(", ");
+        eprintln!("Set {api_keys} environment variables to show an activity of {symbols}");
+    }
+
+    for coin in CoinType::iter_coins() {
+        if let Some(instance) = BlockbookInstance::by_coin_type(coin.as_raw()) {
+            let transport = isolated(transport, instance.symbol);
+            let query = blockbook_activity(hd_wallet, coin, instance, transport);
+            scheduler.add(instance.symbol, query);
+        }
+    }
+
+    let solana_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeSolana);
+    let solana_rpc_url = env::var("SOLANA_RPC_URL").ok();
+    scheduler.add(
+        "SOL",
+        solana_activity(solana_address, solana_rpc_url, isolated(transport, "SOL")),
+    );
+
+    let xrp_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeXRP);
+    scheduler.add("XRP", xrp_activity(xrp_address, isolated(transport, "XRP")));
+
+    let stellar_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeStellar);
+    scheduler.add(
+        "XLM",
+        stellar_activity(stellar_address, isolated(transport, "XLM")),
+    );
+
+    let tron_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeTron);
+    let tron_api_key = env::var("TRONGRID_APIKEY").ok();
+    scheduler.add(
+        "TRX",
+        tron_activity(tron_address, tron_api_key, isolated(transport, "TRX")),
+    );
+
+    let tezos_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeTezos);
+    let tzkt_rpc = rpc::tzkt::TzktRpc::with_default_url(isolated(transport, "XTZ"));
+    let coin_type = CoinType::TWCoinTypeTezos.as_raw();
+    scheduler.add(
+        "XTZ",
+        account_activity(coin_type, "XTZ", tezos_address, tzkt_rpc),
+    );
+
+    let algorand_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeAlgorand);
+    let algorand_rpc = rpc::algorand::AlgorandRpc::with_default_url(isolated(transport, "ALGO"));
+    let coin_type = CoinType::TWCoinTypeAlgorand.as_raw();
+    scheduler.add(
+        "ALGO",
+        account_activity(coin_type, "ALGO", algorand_address, algorand_rpc),
+    );
+
+    let near_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeNEAR);
+    let near_rpc = rpc::near::NearRpc::with_default_url(isolated(transport, "NEAR"));
+    let coin_type = CoinType::TWCoinTypeNEAR.as_raw();
+    scheduler.add(
+        "NEAR",
+        account_activity(coin_type, "NEAR", near_address, near_rpc),
+    );
+
+    let aptos_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeAptos);
+    let aptos_rpc = rpc::aptos::AptosRpc::with_default_url(isolated(transport, "APT"));
+    let coin_type = CoinType::TWCoinTypeAptos.as_raw();
+    scheduler.add(
+        "APT",
+        account_activity(coin_type, "APT", aptos_address, aptos_rpc),
+    );
+
+    match env::var("BLOCKFROST_PROJECT_ID") {
+        Ok(project_id) => {
+            let transport = isolated(transport, "ADA");
+            scheduler.add("ADA", cardano_activity(hd_wallet, project_id, transport))
+        }
+        Err(_) => {
+            eprintln!("Set 'BLOCKFROST_PROJECT_ID' environment variable to show an ADA activity")
+        }
+    }
+
+    let subscan_api_key = env::var("SUBSCAN_APIKEY").ok();
+    for coin in CoinType::iter_coins() {
+        if let Some(network) = SubstrateNetwork::by_coin_type(coin.as_raw()) {
+            let substrate_address = hd_wallet.derive_default_address(coin);
+            let api_key = subscan_api_key.clone();
+            let transport = isolated(transport, network.symbol);
+            let query = substrate_activity(substrate_address, network, api_key, transport);
+            scheduler.add(network.symbol, query);
+        }
+    }
+
+    if let Ok(evm_rpc_url) = env::var("EVM_RPC_URL") {
+        let eth_address = hd_wallet.derive_default_address(CoinType::TWCoinTypeEthereum);
+        // The same address as of the ETH queries.
+        let transport = isolated(transport, "ETH");
+        let query = erc20_activity(eth_address, evm_rpc_url, &token_list, transport);
+        scheduler.add("ERC-20", query);
+    }
+
+    for coin in CoinType::iter_coins() {
+        if let Some(chain) = cosmos_registry.by_coin_type(coin.as_raw()) {
+            let cosmos_address = hd_wallet.derive_default_address(coin);
+            scheduler.add(
+                &chain.symbol,
+                cosmos_activity(cosmos_address, chain, isolated(transport, &chain.symbol)),
+            );
+        }
+    }
+
+    // E.g. `QUERY_TIMEOUT_SECS_ETH_ARBITRUM` for the `ETH (Arbitrum)` queries.
+    let chain_timeouts: Vec<_> = scheduler
+        .chains()
+        .filter_map(|chain| {
+            let name = format!("QUERY_TIMEOUT_SECS_{}", env_suffix(chain));
+            let secs = env::var(&name).ok()?;
+            match secs.parse() {
+                Ok(secs) => Some((chain.to_string(), Duration::from_secs(secs))),
+                Err(_) => {
+                    eprintln!("Invalid '{name}': '{secs}', expected a number");
+                    None
+                }
+            }
+        })
+        .collect();
+    for (chain, timeout) in chain_timeouts {
+        scheduler.set_timeout(&chain, timeout);
+    }
+
+    let mut portfolio = Portfolio::default();
+    let mut reports = scheduler.run();
+    while let Some(result) = reports.next().await {
+        match result {
+            Ok(report) => {
+                report.print();
+                for (coin_type, balance) in report.balances() {
+                    portfolio.add(*coin_type, balance);
+                }
+                for (token, balance) in report.token_balances() {
+                    portfolio.add_token(token, balance);
+                }
+            }
+            Err(QueryTimeout { chain, timeout }) => {
+                eprintln!("Timed out on getting {chain} activity after {timeout:?}")
+            }
+        }
+    }
+
+    // The prices are requested in one currency, `usd` by default.
+    let currency = env::var("FIAT_CURRENCY").unwrap_or_else(|_| "usd".to_string());
+    let date = match env::var("PRICE_DATE") {
+        Ok(date) => match date.parse::<PriceDate>() {
+            Ok(date) => Some(date),
+            Err(e) => {
+                eprintln!("Invalid 'PRICE_DATE': {e}");
+                return;
+            }
+        },
+        Err(_) => None,
+    };
+    match env::var("PRICES_FILE") {
+        Ok(path) => match StaticPrices::from_file(&path) {
+            Ok(prices) => print_portfolio(&portfolio, &prices, &currency, date).await,
+            Err(e) => eprintln!("{e}"),
+        },
+        Err(_) => {
+            let api_key = env::var("COINGECKO_APIKEY").ok();
+            let transport = isolated(transport, "prices");
+            let coingecko_rpc = match env::var("COINGECKO_URL") {
+                Ok(url) => match rpc::coingecko::CoinGeckoRpc::with_url(transport, api_key, url) {
+                    Ok(coingecko_rpc) => coingecko_rpc,
+                    Err(e) => {
+                        eprintln!("Invalid 'COINGECKO_URL': {e}");
+                        return;
+                    }
+                },
+                Err(_) => rpc::coingecko::CoinGeckoRpc::with_default_url(transport, api_key),
+            };
+            print_portfolio(&portfolio, &coingecko_rpc, &currency, date).await;
+        }
+    }
+}
 
-    print_btc_activity(&bitcoin_address, &transport).await;
+/// Returns the HTTP transport settings, connecting through the `HTTPS_PROXY` proxy if it's set.
+/// With `proxy_only`, the requests fail instead of ever connecting directly.
+/// Trusts the CAs of the `TLS_CA_FILE` PEM file, and pins the public keys of the `TLS_PINS` hosts.
+/// The invalid proxy and TLS settings are an error, so that they are not ignored silently.
+fn transport_config(proxy_only: bool) -> Result<TransportConfig, String> {
+    let mut config = TransportConfig::default().with_proxy_only(proxy_only);
+    if let Ok(proxy) = env::var("HTTPS_PROXY") {
+        let proxy = proxy
+            .parse()
+            .map_err(|e| format!("Invalid 'HTTPS_PROXY': {e}"))?;
+        config = config.with_proxy(proxy);
+    }
 
-    match env::var("ETHERSCAN_APIKEY") {
-        Ok(api_key) => print_eth_activity(&eth_address, &transport, api_key).await,
-        Err(_) => eprintln!("Set 'ETHERSCAN_APIKEY' environment variable to show an ETH activity"),
+    if let Ok(path) = env::var("TLS_CA_FILE") {
+        let certificates = std::fs::read(&path)
+            .and_then(|pem| RootCertificate::from_pem(&pem))
+            .map_err(|e| format!("Invalid 'TLS_CA_FILE' {path}: {e}"))?;
+        for certificate in certificates {
+            config = config.with_root_certificate(certificate);
+        }
+    }
+    // E.g. `blockstream.info=sha256/<base64>,mempool.space=sha256/<base64>`, a host can have a few pins.
+    if let Ok(pins) = env::var("TLS_PINS") {
+        for host_pin in pins.split(',').map(str::trim) {
+            let (host, pin) = host_pin.split_once('=').ok_or_else(|| {
+                format!("Invalid 'TLS_PINS': expected 'host=sha256/<base64>', got '{host_pin}'")
+            })?;
+            let pin = pin
+                .parse()
+                .map_err(|e| format!("Invalid 'TLS_PINS': {e}"))?;
+            config = config.with_pinned_key(host, pin);
+        }
+    }
+    Ok(config)
+}
+
+/// Caches the `GET` responses under the `CACHE_DIR`, if it's set, so that a re-run doesn't hit the APIs again.
+/// The `CACHE_TTLS` override the time the responses of an endpoint are fresh for, e.g.
+/// `https://blockstream.info/api=3600,https://api.coingecko.com=60` in seconds.
+fn cached(transport: SharedTransport, offline: bool) -> Result<SharedTransport, String> {
+    let Ok(dir) = env::var("CACHE_DIR") else {
+        return Ok(transport);
+    };
+    let mut caching = CachingTransport::new(transport, dir.into());
+    if let Ok(ttls) = env::var("CACHE_TTLS") {
+        for prefix_ttl in ttls.split(',').map(str::trim) {
+            let ttl = prefix_ttl
+                .rsplit_once('=')
+                .and_then(|(prefix, secs)| Some((prefix, secs.parse().ok()?)));
+            let (prefix, secs) = ttl.ok_or_else(|| {
+                format!("Invalid 'CACHE_TTLS': expected 'url=seconds', got '{prefix_ttl}'")
+            })?;
+            caching = caching.with_ttl(prefix, Duration::from_secs(secs));
+        }
     }
+    if offline {
+        caching = caching.offline();
+    }
+    Ok(Arc::new(caching))
+}
+
+/// Sends the requests of the `chain` over separate connections, and over a separate Tor circuit through
+/// a SOCKS5 proxy, so that the addresses of the different chains can't be linked by the connections.
+fn isolated(transport: &SharedTransport, chain: &str) -> SharedTransport {
+    IsolatedTransport::shared(transport.clone(), chain)
+}
 
-    print_cosmos_activity(&cosmos_address, &transport).await;
+/// Returns the `name` as a suffix of an environment variable name, e.g. `ETH_ARBITRUM` for `ETH (Arbitrum)`.
+fn env_suffix(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_uppercase()
 }
 
-async fn print_btc_activity<T>(bitcoin_address: &str, transport: &T)
+/// Returns the number set by the `name` environment variable, or the `default` one if it's not set or invalid.
+fn env_number<N>(name: &str, default: N) -> N
 where
-    T: rpc::http::HttpTransport + Sync,
+    N: FromStr,
 {
-    let bitcoin_rpc = rpc::blockstream::BlockstreamRpc::with_default_url(transport);
-    match bitcoin_rpc.transaction_count(bitcoin_address).await {
-        Ok(tx_count) => println!("  {tx_count} transactions on {bitcoin_address} (BTC)"),
-        Err(e) => eprintln!("Error on getting Bitcoin address info: {e}"),
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid '{name}': '{value}', expected a number");
+            default
+        }),
+        Err(_) => default,
     }
 }
 
-async fn print_eth_activity<T>(eth_address: &str, transport: &T, api_key: String)
-where
-    T: rpc::http::HttpTransport + Sync,
+async fn print_portfolio<S>(
+    portfolio: &Portfolio,
+    price_source: &S,
+    currency: &str,
+    date: Option<PriceDate>,
+) where
+    S: PriceSource + Sync,
 {
-    let eth_rpc = rpc::etherscan::EtherscanRpc::with_default_url(transport, api_key);
-    match eth_rpc.transaction_count(eth_address).await {
-        Ok(tx_count) => println!("  {tx_count} transactions on {eth_address} (ETH)"),
-        Err(e) => eprintln!("Error on getting Ethereum address info: {e}"),
+    let valuation = match portfolio.value(price_source, currency, date).await {
+        Ok(valuation) => valuation,
+        Err(e) => {
+            eprintln!("Error on getting the {currency} prices: {e}");
+            return;
+        }
+    };
+    match date {
+        Some(date) => println!("Portfolio on {date}:"),
+        None => println!("Portfolio:"),
+    }
+    for (balance, value) in valuation.balances {
+        match value {
+            Some(value) => println!("  {balance} = {value:.2}"),
+            None => println!("  {balance} (no price)"),
+        }
+    }
+    println!("  Total: {:.2}", valuation.total);
+}
+
+/// Returns the token contract `address` on the chain of the `coin_type`, or `None` if the chain
+/// has no asset platform to look the token price up on.
+fn token_contract(coin_type: u32, address: &str) -> Option<TokenContract> {
+    price::asset_platform(coin_type).map(|platform| TokenContract::new(platform, address))
+}
+
+/// Queries the first of the Esplora instances, failing over to the others.
+/// With `quorum`, compares the answers of the first two instances instead, and reports if they disagree.
+async fn btc_activity(
+    bitcoin_address: String,
+    esplora_urls: Vec<String>,
+    quorum: bool,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let endpoints: Result<Vec<_>, _> = esplora_urls.into_iter().map(Endpoint::new).collect();
+    let mut endpoints = match endpoints {
+        Ok(endpoints) if !endpoints.is_empty() => endpoints,
+        Ok(_) => {
+            report.error("'ESPLORA_URLS' is empty".to_string());
+            return report;
+        }
+        Err(e) => {
+            report.error(format!("Invalid 'ESPLORA_URLS': {e}"));
+            return report;
+        }
+    };
+    let info = if quorum && endpoints.len() >= 2 {
+        let (first, second) = (endpoints[0].url(), endpoints[1].url());
+        let rpc = |url: &str| BlockstreamRpc::with_url(transport.clone(), url.to_string());
+        let (first_rpc, second_rpc) = match (rpc(first), rpc(second)) {
+            (Ok(first_rpc), Ok(second_rpc)) => (first_rpc, second_rpc),
+            (Err(e), _) | (_, Err(e)) => {
+                report.error(format!("Invalid 'ESPLORA_URLS': {e}"));
+                return report;
+            }
+        };
+        // The mempools of the instances may differ, so only the confirmed stats are compared.
+        let answers = rpc::failover::quorum_by(
+            first_rpc.address_info(&bitcoin_address),
+            second_rpc.address_info(&bitcoin_address),
+            |first, second| first.chain_stats == second.chain_stats,
+        );
+        match answers.await {
+            Ok(Quorum::Agreed(info)) => Ok(info),
+            Ok(Quorum::Unconfirmed(info)) => {
+                report.line(format!(
+                    "Only one of {first} and {second} answered on {bitcoin_address} (BTC)"
+                ));
+                Ok(info)
+            }
+            Ok(Quorum::Disagreed {
+                first: first_info,
+                second: second_info,
+            }) => {
+                let (first_stats, second_stats) =
+                    (&first_info.chain_stats, &second_info.chain_stats);
+                let (first_txs, second_txs) = (first_stats.tx_count, second_stats.tx_count);
+                let (first_balance, second_balance) =
+                    (first_stats.balance(), second_stats.balance());
+                report.line(format!(
+                    "{first} and {second} disagree on {bitcoin_address} (BTC): \
+                    {first_txs} and {second_txs} confirmed transactions, {first_balance} and {second_balance} confirmed"
+                ));
+                Ok(first_info)
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        let primary = endpoints.remove(0);
+        let failover = FailoverTransport::new(transport, primary, endpoints);
+        let url = failover.url().to_string();
+        match BlockstreamRpc::with_url(Arc::new(failover), url) {
+            Ok(bitcoin_rpc) => bitcoin_rpc.address_info(&bitcoin_address).await,
+            Err(e) => {
+                report.error(format!("Invalid 'ESPLORA_URLS': {e}"));
+                return report;
+            }
+        }
+    };
+
+    match info {
+        Ok(info) => {
+            let tx_count = btc_tx_count(&info);
+            report.line(format!(
+                "{tx_count} transactions on {bitcoin_address} (BTC)"
+            ));
+            let (confirmed, unconfirmed) =
+                (info.chain_stats.balance(), info.mempool_stats.balance());
+            report.line(format!(
+                "{confirmed} confirmed, {unconfirmed} unconfirmed on {bitcoin_address} (BTC)"
+            ));
+            let coin_type = CoinType::TWCoinTypeBitcoin.as_raw();
+            report.balance(coin_type, &confirmed);
+            report.balance(coin_type, &unconfirmed);
+        }
+        Err(e) => report.error(format!("Error on getting Bitcoin address info: {e}")),
+    }
+    report
+}
+
+fn btc_tx_count(info: &AddressInfo) -> usize {
+    info.chain_stats.tx_count + info.mempool_stats.tx_count
+}
+
+/// Scans the receive and the change addresses of the default BTC account over one connection,
+/// as the default address is only the first one of the account.
+async fn electrum_activity(
+    hd_wallet: &HDWallet,
+    electrum_url: String,
+    config: &TransportConfig,
+) -> Report {
+    let mut report = Report::default();
+    let electrum_rpc = match ElectrumRpc::connect_with_config(&electrum_url, config).await {
+        Ok(electrum_rpc) => electrum_rpc,
+        Err(e) => {
+            report.error(format!("Error on connecting to the Electrum server: {e}"));
+            return report;
+        }
+    };
+
+    let mut used = Vec::new();
+    for change in BIP44_CHAINS {
+        let script_hash_at = |index| {
+            let lock_script = hd_wallet
+                .derive_lock_script(CoinType::TWCoinTypeBitcoin, change, index)
+                .expect("Bitcoin address is expected to have a lock script");
+            rpc::electrum::script_hash(&lock_script)
+        };
+        match electrum_rpc.scan_chain(script_hash_at).await {
+            Ok(chain) => used.extend(chain),
+            Err(e) => {
+                report.error(format!(
+                    "Error on getting Bitcoin history from the Electrum server: {e}"
+                ));
+                return report;
+            }
+        }
+    }
+    // A transaction that sends the change back is in the histories of both addresses.
+    let tx_count = used
+        .iter()
+        .flat_map(|(_, history)| history)
+        .map(|item| &item.tx_hash)
+        .collect::<HashSet<_>>()
+        .len();
+    let script_hashes: Vec<_> = used
+        .into_iter()
+        .map(|(script_hash, _)| script_hash)
+        .collect();
+
+    match electrum_rpc.get_balance_batch(&script_hashes).await {
+        Ok(balances) => {
+            let zero = Amount::zero(Coin::BTC);
+            let confirmed = balances.iter().try_fold(zero.clone(), |sum, balance| {
+                sum.checked_add(&balance.confirmed)
+            });
+            let unconfirmed = balances
+                .iter()
+                .try_fold(zero, |sum, balance| sum.checked_add(&balance.unconfirmed));
+            match (confirmed, unconfirmed) {
+                (Ok(confirmed), Ok(unconfirmed)) => report.line(format!(
+                    "{tx_count} transactions, {confirmed} confirmed, {unconfirmed} unconfirmed on {} used addresses of the BTC account (Electrum)",
+                    script_hashes.len()
+                )),
+                (Err(e), _) | (_, Err(e)) => {
+                    report.error(format!("Error on summing up Bitcoin balances: {e}"))
+                }
+            }
+        }
+        Err(e) => report.error(format!(
+            "Error on getting Bitcoin balance from the Electrum server: {e}"
+        )),
     }
+    report
 }
 
-async fn print_cosmos_activity<T>(cosmos_address: &str, transport: &T)
+async fn blockbook_activity(
+    hd_wallet: &HDWallet,
+    coin: CoinType,
+    instance: &BlockbookInstance,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let symbol = instance.symbol;
+    let units = instance.coin();
+    let blockbook_rpc = match rpc::blockbook::BlockbookRpc::with_instance(transport, instance) {
+        Ok(blockbook_rpc) => blockbook_rpc,
+        Err(e) => {
+            report.error(format!("Invalid '{}': {e}", instance.url_env));
+            return report;
+        }
+    };
+
+    // The account balance includes the default address one, so only the account balance is added
+    // to the portfolio if there is one.
+    let mut portfolio_balance = None;
+    let address = hd_wallet.derive_default_address(coin);
+    match blockbook_rpc.address_info(&address).await {
+        Ok(info) => {
+            let tx_count = info.txs + info.unconfirmed_txs;
+            let balance = info.to_balance(&units);
+            report.line(format!("{tx_count} transactions on {address} ({symbol})"));
+            report.line(format!(
+                "{} confirmed, {} unconfirmed on {address} ({symbol})",
+                balance.confirmed, balance.unconfirmed
+            ));
+            portfolio_balance = Some(balance);
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} address info: {e}")),
+    }
+
+    // The default address is only the first one of the account, so scan the whole account too.
+    if let Some(xpub) = hd_wallet.derive_default_xpub(coin) {
+        match blockbook_rpc.xpub_info(&xpub).await {
+            Ok(info) => {
+                let balance = info.to_balance(&units);
+                report.line(format!(
+                    "{} transactions, {} confirmed, {} unconfirmed on {} used addresses of the {symbol} account",
+                    info.txs + info.unconfirmed_txs,
+                    balance.confirmed,
+                    balance.unconfirmed,
+                    info.used_tokens
+                ));
+                portfolio_balance = Some(balance);
+            }
+            Err(e) => report.error(format!("Error on getting {symbol} account info: {e}")),
+        }
+    }
+
+    if let Some(balance) = portfolio_balance {
+        report.balance(coin.as_raw(), &balance.confirmed);
+        report.balance(coin.as_raw(), &balance.unconfirmed);
+    }
+    report
+}
+
+/// Queries the `explorer`, or the Etherscan-compatible APIs of the `urls` with a failover if they are set.
+async fn eth_activity(
+    eth_address: String,
+    explorer: &EtherscanExplorer,
+    urls: Option<Vec<String>>,
+    transport: SharedTransport,
+    api_key: String,
+) -> Report {
+    let mut report = Report::default();
+    let symbol = explorer.symbol;
+    let eth_rpc = match urls {
+        Some(urls) => match EtherscanRpc::with_explorer_urls(transport, api_key, explorer, &urls) {
+            Ok(eth_rpc) => eth_rpc,
+            Err(e) => {
+                report.error(format!("Invalid '{}': {e}", explorer.urls_env));
+                return report;
+            }
+        },
+        None => EtherscanRpc::with_explorer(transport, api_key, explorer),
+    };
+    // Both the transaction count and the token balances take the token transfers.
+    let transfers = eth_rpc.all_token_transfers(&eth_address).await;
+    match &transfers {
+        Ok(transfers) => match eth_rpc
+            .transaction_count_with_transfers(&eth_address, transfers)
+            .await
+        {
+            Ok(tx_count) => report.line(format!(
+                "{tx_count} transactions on {eth_address} ({symbol})"
+            )),
+            Err(e) => report.error(format!("Error on getting {symbol} address info: {e}")),
+        },
+        Err(e) => report.error(format!("Error on getting {symbol} token transfers: {e}")),
+    }
+    match eth_rpc.balance(&eth_address).await {
+        Ok(balance) => {
+            report.line(format!("{balance} on {eth_address} ({symbol})"));
+            report.balance(explorer.coin_type, &balance);
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} balance: {e}")),
+    }
+    if let Ok(transfers) = transfers {
+        for token in TokenBalance::from_transfers(&eth_address, &transfers) {
+            let token_id = token
+                .token_id
+                .map(|id| format!(" #{id}"))
+                .unwrap_or_default();
+            report.line(format!(
+                "{}{token_id} ({}) on {eth_address} ({symbol})",
+                token.balance, token.contract_address
+            ));
+            // The NFTs aren't valued.
+            let contract = token_contract(explorer.coin_type, &token.contract_address);
+            if let (None, Some(contract)) = (token.token_id, contract) {
+                let balance = match token.token_decimal {
+                    Some(_) => TokenAmount::Units(token.balance),
+                    None => TokenAmount::BaseUnits(token.balance),
+                };
+                report.token_balance(contract, balance);
+            }
+        }
+    }
+    report
+}
+
+async fn erc20_activity(
+    eth_address: String,
+    evm_rpc_url: String,
+    token_list: &TokenList,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let evm_rpc = match rpc::evm::EvmRpc::with_url(transport, evm_rpc_url) {
+        Ok(evm_rpc) => evm_rpc,
+        Err(e) => {
+            report.error(format!("Invalid 'EVM_RPC_URL': {e}"));
+            return report;
+        }
+    };
+    let chain_id = match evm_rpc.chain_id().await {
+        Ok(chain_id) => chain_id,
+        Err(e) => {
+            report.error(format!("Error on getting the EVM chain id: {e}"));
+            return report;
+        }
+    };
+    let tokens: Vec<_> = token_list.tokens_for_chain(chain_id).cloned().collect();
+    match evm_rpc.token_balances(&eth_address, &tokens).await {
+        Ok(balances) => {
+            for token in balances {
+                report.line(format!(
+                    "{} ({}) on {eth_address} (chain {chain_id})",
+                    token.balance, token.contract_address
+                ));
+                if let Some(platform) = price::evm_asset_platform(chain_id) {
+                    let contract = TokenContract::new(platform, &token.contract_address);
+                    report.token_balance(contract, TokenAmount::Units(token.balance));
+                }
+            }
+        }
+        Err(e) => report.error(format!(
+            "Error on getting ERC-20 balances on chain {chain_id}: {e}"
+        )),
+    }
+    report
+}
+
+async fn solana_activity(
+    solana_address: String,
+    solana_rpc_url: Option<String>,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let solana_rpc = match solana_rpc_url {
+        Some(url) => match rpc::solana::SolanaRpc::with_url(transport, url) {
+            Ok(solana_rpc) => solana_rpc,
+            Err(e) => {
+                report.error(format!("Invalid 'SOLANA_RPC_URL': {e}"));
+                return report;
+            }
+        },
+        None => rpc::solana::SolanaRpc::with_default_url(transport),
+    };
+    let coin_type = CoinType::TWCoinTypeSolana.as_raw();
+    match solana_rpc.transaction_count(&solana_address).await {
+        Ok(tx_count) => report.line(format!("{tx_count} transactions on {solana_address} (SOL)")),
+        Err(e) => report.error(format!("Error on getting SOL address info: {e}")),
+    }
+    match solana_rpc.balance(&solana_address).await {
+        Ok(balance) => {
+            report.line(format!("{balance} on {solana_address} (SOL)"));
+            report.balance(coin_type, &balance);
+        }
+        Err(e) => report.error(format!("Error on getting SOL balance: {e}")),
+    }
+    match solana_rpc.token_balances(&solana_address).await {
+        Ok(balances) => {
+            for token in balances {
+                report.line(format!("{} on {solana_address} (SOL)", token.amount));
+                if let Some(contract) = token_contract(coin_type, &token.mint) {
+                    report.token_balance(contract, TokenAmount::Units(token.amount));
+                }
+            }
+        }
+        Err(e) => report.error(format!("Error on getting SOL token balances: {e}")),
+    }
+    report
+}
+
+async fn xrp_activity(xrp_address: String, transport: SharedTransport) -> Report {
+    let mut report = Report::default();
+    let xrpl_rpc = rpc::xrpl::XrplRpc::with_default_url(transport);
+    match xrpl_rpc.account_info(&xrp_address).await {
+        Ok(AccountState::Active(account)) => {
+            report.line(format!("{} on {xrp_address} (XRP)", account.balance));
+            report.balance(CoinType::TWCoinTypeXRP.as_raw(), &account.balance);
+        }
+        Ok(AccountState::NotActivated) => {
+            report.line(format!("{xrp_address} is not activated (XRP)"))
+        }
+        Err(e) => report.error(format!("Error on getting XRP account info: {e}")),
+    }
+    match xrpl_rpc.transaction_count(&xrp_address).await {
+        Ok(tx_count) => report.line(format!("{tx_count} transactions on {xrp_address} (XRP)")),
+        Err(e) => report.error(format!("Error on getting XRP transactions: {e}")),
+    }
+    report
+}
+
+async fn stellar_activity(stellar_address: String, transport: SharedTransport) -> Report {
+    let mut report = Report::default();
+    let horizon_rpc = rpc::horizon::HorizonRpc::with_default_url(transport);
+    let coin_type = CoinType::TWCoinTypeStellar.as_raw();
+    match horizon_rpc.account(&stellar_address).await {
+        Ok(AccountState::Active(account)) => {
+            if let Some(balance) = account.native_balance() {
+                report.balance(coin_type, balance);
+            }
+            for balance in account.balances {
+                report.line(format!("{} on {stellar_address} (XLM)", balance.balance));
+                // The issued assets are listed as `CODE-ISSUER`.
+                if let (Some(code), Some(issuer)) = (&balance.asset_code, &balance.asset_issuer) {
+                    if let Some(contract) = token_contract(coin_type, &format!("{code}-{issuer}")) {
+                        report.token_balance(contract, TokenAmount::Units(balance.balance));
+                    }
+                }
+            }
+        }
+        Ok(AccountState::NotActivated) => {
+            report.line(format!("{stellar_address} is not activated (XLM)"))
+        }
+        Err(e) => report.error(format!("Error on getting XLM account info: {e}")),
+    }
+    match horizon_rpc.payments(&stellar_address).await {
+        Ok(payments) => report.line(format!(
+            "{} payments on {stellar_address} (XLM)",
+            payments.len()
+        )),
+        Err(e) => report.error(format!("Error on getting XLM payments: {e}")),
+    }
+    report
+}
+
+async fn tron_activity(
+    tron_address: String,
+    api_key: Option<String>,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let tron_rpc = rpc::tron::TronRpc::with_default_url(transport, api_key);
+    let is_activated = match tron_rpc.account(&tron_address).await {
+        Ok(AccountState::Active(account)) => {
+            report.line(format!("{} on {tron_address} (TRX)", account.balance));
+            report.balance(CoinType::TWCoinTypeTron.as_raw(), &account.balance);
+            true
+        }
+        // Receiving TRC-20 tokens doesn't activate an account, so it may still have transfers.
+        Ok(AccountState::NotActivated) => {
+            report.line(format!("{tron_address} is not activated (TRX)"));
+            false
+        }
+        Err(e) => {
+            report.error(format!("Error on getting TRX account info: {e}"));
+            true
+        }
+    };
+    if is_activated {
+        match tron_rpc.account_resource(&tron_address).await {
+            Ok(resource) => report.line(format!(
+                "{} bandwidth, {} energy on {tron_address} (TRX)",
+                resource.bandwidth(),
+                resource.energy()
+            )),
+            Err(e) => report.error(format!("Error on getting TRX account resources: {e}")),
+        }
+    }
+    match tron_rpc.transaction_count(&tron_address).await {
+        Ok(tx_count) => report.line(format!("{tx_count} transactions on {tron_address} (TRX)")),
+        Err(e) => report.error(format!("Error on getting TRX transactions: {e}")),
+    }
+    match tron_rpc.trc20_balances(&tron_address).await {
+        Ok(balances) => {
+            for token in balances {
+                report.line(format!(
+                    "{} ({}) on {tron_address} (TRX)",
+                    token.balance, token.contract_address
+                ));
+                let coin_type = CoinType::TWCoinTypeTron.as_raw();
+                if let Some(contract) = token_contract(coin_type, &token.contract_address) {
+                    let balance = match token.token_info {
+                        Some(_) => TokenAmount::Units(token.balance),
+                        None => TokenAmount::BaseUnits(token.balance),
+                    };
+                    report.token_balance(contract, balance);
+                }
+            }
+        }
+        Err(e) => report.error(format!("Error on getting TRC-20 balances: {e}")),
+    }
+    report
+}
+
+async fn account_activity<R>(
+    coin_type: u32,
+    symbol: &str,
+    address: String,
+    activity_rpc: R,
+) -> Report
 where
-    T: rpc::http::HttpTransport + Sync,
+    R: ActivityRpc + Sync,
 {
-    let cosmos_rpc = rpc::cosmos::CosmosRpc::with_default_url(transport);
-    match cosmos_rpc.transaction_count(cosmos_address).await {
-        Ok(tx_count) => println!("  {tx_count} transactions on {cosmos_address} (ADA)"),
-        Err(e) => eprintln!("Error on getting Cosmos address info: {e}"),
+    let mut report = Report::default();
+    match activity_rpc.activity(&address).await {
+        Ok(AccountState::Active(activity)) => {
+            report.line(format!("{} on {address} ({symbol})", activity.balance));
+            report.balance(coin_type, &activity.balance);
+            if let Some(tx_count) = activity.transaction_count {
+                report.line(format!("{tx_count} transactions on {address} ({symbol})"));
+            }
+        }
+        Ok(AccountState::NotActivated) => {
+            report.line(format!("{address} is not activated ({symbol})"))
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} activity: {e}")),
+    }
+    report
+}
+
+async fn cardano_activity(
+    hd_wallet: &HDWallet,
+    project_id: String,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let blockfrost_url = env::var("BLOCKFROST_URL").ok();
+    let blockfrost_rpc = match blockfrost_url {
+        Some(url) => match rpc::blockfrost::BlockfrostRpc::with_url(transport, project_id, url) {
+            Ok(blockfrost_rpc) => blockfrost_rpc,
+            Err(e) => {
+                report.error(format!("Invalid 'BLOCKFROST_URL': {e}"));
+                return report;
+            }
+        },
+        None => rpc::blockfrost::BlockfrostRpc::with_default_url(transport, project_id),
+    };
+    let (cardano_address, reward_address) = hd_wallet.derive_cardano_addresses();
+    let coin_type = CoinType::TWCoinTypeCardano.as_raw();
+
+    // The stake account controls the base address funds, so the address balance is only added
+    // to the portfolio if there is no stake account.
+    let mut address_balance = None;
+    match blockfrost_rpc.address(&cardano_address).await {
+        Ok(Some(info)) => {
+            let ada = info.ada();
+            report.line(format!("{ada} on {cardano_address} (ADA)"));
+            address_balance = Some(ada);
+            for asset in info.native_assets() {
+                let amount = asset.to_amount();
+                report.line(format!("{amount} on {cardano_address} (ADA)"));
+                if let Some(contract) = token_contract(coin_type, &asset.unit) {
+                    report.token_balance(contract, TokenAmount::BaseUnits(amount));
+                }
+            }
+        }
+        Ok(None) => report.line(format!("{cardano_address} has never been used (ADA)")),
+        Err(e) => report.error(format!("Error on getting ADA address info: {e}")),
+    }
+    match blockfrost_rpc.address_transactions(&cardano_address).await {
+        Ok(txs) => report.line(format!(
+            "{} transactions on {cardano_address} (ADA)",
+            txs.len()
+        )),
+        Err(e) => report.error(format!("Error on getting ADA transactions: {e}")),
+    }
+
+    if let Some(reward_address) = reward_address {
+        match blockfrost_rpc.account(&reward_address).await {
+            Ok(Some(account)) => {
+                report.line(format!(
+                    "{} controlled by {reward_address} (ADA)",
+                    account.controlled_amount
+                ));
+                report.balance(coin_type, &account.controlled_amount);
+                address_balance = None;
+                let rewards = &account.withdrawable_amount;
+                if !rewards.is_zero() {
+                    report.line(format!("{rewards} rewards on {reward_address} (ADA)"));
+                }
+                match account.delegated_pool() {
+                    Some(pool_id) => {
+                        report.line(format!("{reward_address} is delegated to {pool_id} (ADA)"))
+                    }
+                    None => report.line(format!("{reward_address} is not delegated (ADA)")),
+                }
+            }
+            Ok(None) => report.line(format!("{reward_address} has never been used (ADA)")),
+            Err(e) => report.error(format!("Error on getting ADA stake account: {e}")),
+        }
+        match blockfrost_rpc.account_addresses(&reward_address).await {
+            Ok(addresses) => report.line(format!(
+                "{} addresses of {reward_address} (ADA)",
+                addresses.len()
+            )),
+            Err(e) => report.error(format!("Error on getting ADA stake account addresses: {e}")),
+        }
+    }
+
+    if let Some(balance) = address_balance {
+        report.balance(coin_type, &balance);
+    }
+    report
+}
+
+async fn substrate_activity(
+    substrate_address: String,
+    network: &SubstrateNetwork,
+    subscan_api_key: Option<String>,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let symbol = network.symbol;
+    match rpc::sidecar::SidecarRpc::with_network(transport.clone(), network) {
+        Ok(sidecar_rpc) => match sidecar_rpc.balance_info(&substrate_address).await {
+            Ok(info) => {
+                let balance = info.to_balance(&network.coin());
+                report.line(format!(
+                    "{} on {substrate_address} ({symbol})",
+                    balance.free
+                ));
+                report.balance(network.coin_type, &balance.free);
+                report.balance(network.coin_type, &balance.reserved);
+                if !balance.reserved.is_zero() {
+                    let reserved = balance.reserved;
+                    report.line(format!(
+                        "{reserved} reserved on {substrate_address} ({symbol})"
+                    ));
+                }
+                if !balance.locked.is_zero() {
+                    let locked = balance.locked;
+                    report.line(format!("{locked} locked on {substrate_address} ({symbol})"));
+                }
+            }
+            Err(e) => report.error(format!("Error on getting {symbol} balance: {e}")),
+        },
+        Err(e) => report.error(format!("Invalid '{}' URL: {e}", network.sidecar_url_env)),
+    }
+
+    let subscan_rpc = rpc::subscan::SubscanRpc::with_network(transport, subscan_api_key, network);
+    match subscan_rpc.transfer_count(&substrate_address).await {
+        Ok(count) => report.line(format!(
+            "{count} transfers on {substrate_address} ({symbol})"
+        )),
+        Err(e) => report.error(format!("Error on getting {symbol} transfers: {e}")),
+    }
+    report
+}
+
+async fn cosmos_activity(
+    cosmos_address: String,
+    chain: &CosmosChain,
+    transport: SharedTransport,
+) -> Report {
+    let mut report = Report::default();
+    let symbol = &chain.symbol;
+    // A registry entry of another chain would report the activity of another account.
+    if !chain.is_chain_address(&cosmos_address) {
+        report.error(format!(
+            "{cosmos_address} ({symbol}) doesn't have the '{}' prefix of '{}'",
+            chain.bech32_prefix, chain.chain_name
+        ));
+        return report;
+    }
+    let cosmos_rpc = match rpc::cosmos::CosmosRpc::with_chain(transport, chain) {
+        Ok(cosmos_rpc) => cosmos_rpc,
+        Err(e) => {
+            report.error(format!("Invalid '{}' REST endpoint: {e}", chain.chain_name));
+            return report;
+        }
+    };
+    match cosmos_rpc.transaction_count(&cosmos_address).await {
+        Ok(tx_count) => report.line(format!(
+            "{tx_count} transactions on {cosmos_address} ({symbol})"
+        )),
+        Err(e) => report.error(format!("Error on getting {symbol} address info: {e}")),
+    }
+
+    match cosmos_rpc.balances(&cosmos_address).await {
+        Ok(balances) => {
+            report_cosmos_coins(&mut report, "available", &cosmos_address, chain, &balances)
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} balances: {e}")),
+    }
+    match cosmos_rpc.delegations(&cosmos_address).await {
+        Ok(delegations) => {
+            let coins: Vec<_> = delegations.into_iter().map(|d| d.balance).collect();
+            report_cosmos_coins(&mut report, "delegated", &cosmos_address, chain, &coins);
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} delegations: {e}")),
+    }
+    match cosmos_rpc.unbonding_delegations(&cosmos_address).await {
+        Ok(unbonding) => {
+            let amount: u128 = unbonding
+                .iter()
+                .flat_map(|delegation| delegation.entries.iter())
+                .map(|entry| entry.balance)
+                .sum();
+            if amount > 0 {
+                let amount = Amount::from_base_units(amount, chain.staking_coin());
+                report.line(format!("{amount} unbonding on {cosmos_address} ({symbol})"));
+                if let Some(coin_type) = chain.coin_type {
+                    report.balance(coin_type, &amount);
+                }
+            }
+        }
+        Err(e) => report.error(format!(
+            "Error on getting {symbol} unbonding delegations: {e}"
+        )),
+    }
+    match cosmos_rpc.rewards(&cosmos_address).await {
+        Ok(rewards) => {
+            let kind = "pending rewards";
+            report_cosmos_coins(&mut report, kind, &cosmos_address, chain, &rewards.total)
+        }
+        Err(e) => report.error(format!("Error on getting {symbol} rewards: {e}")),
+    }
+    report
+}
+
+fn report_cosmos_coins(
+    report: &mut Report,
+    kind: &str,
+    cosmos_address: &str,
+    chain: &CosmosChain,
+    coins: &[rpc::cosmos::Coin],
+) {
+    let symbol = &chain.symbol;
+    for (denom, amount) in rpc::cosmos::Coin::sum_by_denom(coins) {
+        let is_staking_denom = denom == chain.staking_denom;
+        let amount = rpc::cosmos::Coin {
+            denom: denom.clone(),
+            amount,
+        }
+        .to_amount(chain);
+        report.line(format!("{amount} {kind} on {cosmos_address} ({symbol})"));
+        let Some(coin_type) = chain.coin_type else {
+            continue;
+        };
+        if is_staking_denom {
+            report.balance(coin_type, &amount);
+        } else if let Some(contract) = token_contract(coin_type, &denom) {
+            // The other denoms are IBC and factory tokens, looked up by the denom.
+            report.token_balance(contract, TokenAmount::BaseUnits(amount));
+        }
     }
 }
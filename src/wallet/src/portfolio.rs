@@ -0,0 +1,185 @@
+use rpc::amount::{Amount, Coin};
+use rpc::http::HttpError;
+use rpc::price::{self, PriceDate, PriceSource, TokenContract};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The coin and token balances of the wallet, summed up per asset to value them in a fiat currency.
+#[derive(Default)]
+pub struct Portfolio {
+    /// The native balances by the `trustwallet/wallet-core` coin type.
+    balances: BTreeMap<u32, Amount>,
+    tokens: BTreeMap<TokenContract, TokenAmount>,
+}
+
+/// A token balance of a [`Portfolio`].
+#[derive(Clone)]
+pub enum TokenAmount {
+    /// In the token units, e.g. of an ERC-20 token with the on-chain decimals.
+    Units(Amount),
+    /// In the base units of a token which decimals are off-chain, e.g. a Cardano native asset.
+    /// Converted with the decimals of the price source, and not valued if it doesn't know them.
+    BaseUnits(Amount),
+}
+
+impl TokenAmount {
+    fn amount(&self) -> &Amount {
+        match self {
+            TokenAmount::Units(amount) | TokenAmount::BaseUnits(amount) => amount,
+        }
+    }
+
+    fn checked_add(&self, other: &TokenAmount) -> Result<TokenAmount, String> {
+        match (self, other) {
+            (TokenAmount::Units(a), TokenAmount::Units(b)) => {
+                a.checked_add(b).map(TokenAmount::Units)
+            }
+            (TokenAmount::BaseUnits(a), TokenAmount::BaseUnits(b)) => {
+                a.checked_add(b).map(TokenAmount::BaseUnits)
+            }
+            _ => return Err("the token decimals are known for one balance only".to_string()),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// The balances of a [`Portfolio`] valued in a fiat currency.
+pub struct Valuation {
+    /// The balances with their fiat values, `None` if there is no price of the coin.
+    pub balances: Vec<(Amount, Option<Amount>)>,
+    /// The sum of the fiat values.
+    pub total: Amount,
+}
+
+impl Portfolio {
+    /// Adds the `amount` to the balance of the coin.
+    pub fn add(&mut self, coin_type: u32, amount: &Amount) {
+        let balance = match self.balances.get(&coin_type) {
+            Some(balance) => match balance.checked_add(amount) {
+                Ok(balance) => balance,
+                Err(e) => {
+                    eprintln!("Error on adding {amount} to the portfolio: {e}");
+                    return;
+                }
+            },
+            None => amount.clone(),
+        };
+        self.balances.insert(coin_type, balance);
+    }
+
+    /// Adds the `amount` to the balance of the token.
+    pub fn add_token(&mut self, token: &TokenContract, amount: &TokenAmount) {
+        let balance = match self.tokens.get(token) {
+            Some(balance) => match balance.checked_add(amount) {
+                Ok(balance) => balance,
+                Err(e) => {
+                    let amount = amount.amount();
+                    eprintln!("Error on adding {amount} to the portfolio: {e}");
+                    return;
+                }
+            },
+            None => amount.clone(),
+        };
+        self.tokens.insert(token.clone(), balance);
+    }
+
+    /// Values the non-zero balances with the latest prices, or with the prices on the `date` if given.
+    /// The tokens are valued by the price ids of their contracts, the tokens without one have no price.
+    pub async fn value<S>(
+        &self,
+        price_source: &S,
+        currency: &str,
+        date: Option<PriceDate>,
+    ) -> Result<Valuation, HttpError>
+    where
+        S: PriceSource + Sync,
+    {
+        let mut balances: Vec<_> = self
+            .balances
+            .iter()
+            .filter(|(_, balance)| !balance.is_zero())
+            .map(|(coin_type, balance)| {
+                (
+                    price::price_id(*coin_type).map(String::from),
+                    balance.clone(),
+                )
+            })
+            .collect();
+        for (token, balance) in &self.tokens {
+            if !balance.amount().is_zero() {
+                balances.push(token_price_id(price_source, token, balance).await);
+            }
+        }
+
+        // Different coin types may share a price id, e.g. ETH on the L2 chains.
+        let ids: BTreeSet<_> = balances
+            .iter()
+            .filter_map(|(id, _)| id.as_deref())
+            .collect();
+        let prices = match date {
+            Some(date) => {
+                let mut prices = BTreeMap::new();
+                for id in ids {
+                    if let Some(price) = price_source.historical_price(id, currency, date).await? {
+                        prices.insert(id.to_string(), price);
+                    }
+                }
+                prices
+            }
+            None => {
+                let ids: Vec<_> = ids.into_iter().collect();
+                price_source.prices(&ids, currency).await?
+            }
+        };
+
+        let mut valuation = Valuation {
+            balances: Vec::with_capacity(balances.len()),
+            total: Amount::zero(price::fiat_coin(currency)),
+        };
+        for (id, balance) in balances {
+            let value = id
+                .and_then(|id| prices.get(&id))
+                .map(|price| balance.value_in(price))
+                .transpose()
+                .map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+            if let Some(value) = &value {
+                valuation.total = valuation
+                    .total
+                    .checked_add(value)
+                    .map_err(|e| HttpError::InvalidPayload(e.to_string()))?;
+            }
+            valuation.balances.push((balance, value));
+        }
+        Ok(valuation)
+    }
+}
+
+/// Returns the price id of the `token`, and its `balance` in the token units if the decimals are known.
+/// A failed lookup leaves the token without a price, e.g. if the price source rate-limits the lookups.
+async fn token_price_id<S>(
+    price_source: &S,
+    token: &TokenContract,
+    balance: &TokenAmount,
+) -> (Option<String>, Amount)
+where
+    S: PriceSource + Sync,
+{
+    let info = match price_source.token_info(token).await {
+        Ok(info) => info,
+        Err(e) => {
+            let TokenContract { platform, address } = token;
+            eprintln!("Error on getting the price id of {address} on {platform}: {e}");
+            None
+        }
+    };
+    match (info, balance) {
+        (Some(info), TokenAmount::Units(amount)) => (Some(info.id), amount.clone()),
+        (Some(info), TokenAmount::BaseUnits(amount)) => match info.decimals {
+            Some(decimals) => {
+                let coin = Coin::token(amount.coin().symbol.clone(), decimals);
+                (Some(info.id), Amount::from_base_units(amount.raw(), coin))
+            }
+            None => (None, amount.clone()),
+        },
+        (None, balance) => (None, balance.amount().clone()),
+    }
+}
@@ -0,0 +1,111 @@
+use crate::portfolio::TokenAmount;
+use futures::future::LocalBoxFuture;
+use futures::stream::{self, Stream, StreamExt};
+use rpc::amount::Amount;
+use rpc::price::TokenContract;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// The output of one activity query, printed at once when the query is finished,
+/// so that the output of the concurrent queries isn't interleaved.
+#[derive(Default)]
+pub struct Report {
+    lines: Vec<String>,
+    errors: Vec<String>,
+    /// The native balances by the `trustwallet/wallet-core` coin type, to be added to the portfolio.
+    balances: Vec<(u32, Amount)>,
+    /// The token balances, to be added to the portfolio.
+    token_balances: Vec<(TokenContract, TokenAmount)>,
+}
+
+impl Report {
+    pub fn line(&mut self, line: String) { self.lines.push(line); }
+
+    pub fn error(&mut self, error: String) { self.errors.push(error); }
+
+    pub fn balance(&mut self, coin_type: u32, amount: &Amount) {
+        self.balances.push((coin_type, amount.clone()));
+    }
+
+    pub fn balances(&self) -> &[(u32, Amount)] { &self.balances }
+
+    pub fn token_balance(&mut self, token: TokenContract, amount: TokenAmount) {
+        self.token_balances.push((token, amount));
+    }
+
+    pub fn token_balances(&self) -> &[(TokenContract, TokenAmount)] { &self.token_balances }
+
+    /// Prints the lines to stdout and the errors to stderr.
+    pub fn print(&self) {
+        for line in &self.lines {
+            println!("  {line}");
+        }
+        for error in &self.errors {
+            eprintln!("{error}");
+        }
+    }
+}
+
+/// The activity query of a chain that didn't finish within the timeout.
+pub struct QueryTimeout {
+    pub chain: String,
+    pub timeout: Duration,
+}
+
+/// Runs the activity queries concurrently.
+pub struct Scheduler<'a> {
+    queries: Vec<(String, LocalBoxFuture<'a, Report>)>,
+    max_concurrent: usize,
+    timeout: Duration,
+    /// The timeouts of the chains that override the `timeout`.
+    chain_timeouts: HashMap<String, Duration>,
+}
+
+impl<'a> Scheduler<'a> {
+    pub fn new(max_concurrent: usize, timeout: Duration) -> Self {
+        Scheduler {
+            queries: Vec::new(),
+            max_concurrent,
+            timeout,
+            chain_timeouts: HashMap::new(),
+        }
+    }
+
+    /// Returns the chains of the added queries.
+    pub fn chains(&self) -> impl Iterator<Item = &str> {
+        self.queries.iter().map(|(chain, _)| chain.as_str())
+    }
+
+    /// Overrides the timeout of the `chain` queries, e.g. of a chain with a slow or rate-limited API.
+    pub fn set_timeout(&mut self, chain: &str, timeout: Duration) {
+        self.chain_timeouts.insert(chain.to_string(), timeout);
+    }
+
+    /// Adds the `query` of the `chain` activity, e.g. `BTC`.
+    pub fn add<F>(&mut self, chain: impl Into<String>, query: F)
+    where
+        F: Future<Output = Report> + 'a,
+    {
+        self.queries.push((chain.into(), Box::pin(query)));
+    }
+
+    /// Runs at most `max_concurrent` queries at once, each one within the timeout of its chain from its start.
+    /// The reports are yielded in the order the queries finish.
+    pub fn run(self) -> impl Stream<Item = Result<Report, QueryTimeout>> + 'a {
+        let (default_timeout, chain_timeouts) = (self.timeout, self.chain_timeouts);
+        stream::iter(self.queries)
+            .map(move |(chain, query)| {
+                let timeout = chain_timeouts
+                    .get(&chain)
+                    .copied()
+                    .unwrap_or(default_timeout);
+                async move {
+                    tokio::time::timeout(timeout, query)
+                        .await
+                        .map_err(|_| QueryTimeout { chain, timeout })
+                }
+            })
+            .buffer_unordered(self.max_concurrent.max(1))
+    }
+}
@@ -1,10 +1,15 @@
 use std::{fmt, io};
 
+const PROXY_ONLY_OPTION: &str = "--proxy-only";
+const QUORUM_OPTION: &str = "--quorum";
+const OFFLINE_OPTION: &str = "--offline";
+
 #[derive(Debug)]
 pub enum InvalidInput {
     ExpectedUtf8Args,
     Empty,
     UnknownInputFormat,
+    UnknownOption(String),
 }
 
 impl fmt::Display for InvalidInput {
@@ -13,6 +18,7 @@ impl fmt::Display for InvalidInput {
             InvalidInput::ExpectedUtf8Args => write!(f, "Expected a UTF-8 arguments"),
             InvalidInput::Empty => write!(f, "Expected either a mnemonic or a mnemonic entropy"),
             InvalidInput::UnknownInputFormat => write!(f, "Unknown input format"),
+            InvalidInput::UnknownOption(option) => write!(f, "Unknown option: '{option}'"),
         }
     }
 }
@@ -29,19 +35,53 @@ pub enum WalletInput {
 }
 
 impl WalletInput {
-    /// Takes command line arguments and tries to determine the input format:
-    /// either a mnemonic or a mnemonic entropy.
-    pub fn parse_cmd_args() -> Result<WalletInput, InvalidInput> {
+    /// Tries to determine the input format of the `words`: either a mnemonic or a mnemonic entropy.
+    fn from_words(words: &[String]) -> Result<WalletInput, InvalidInput> {
+        match words.len() {
+            0 => Err(InvalidInput::Empty),
+            1 => Ok(WalletInput::Entropy(words[0].clone())),
+            // Compile the words into a single string.
+            _ => Ok(WalletInput::Mnemonic(words.join(" "))),
+        }
+    }
+}
+
+/// The command line arguments: the options followed by the wallet input.
+pub struct CmdArgs {
+    pub input: WalletInput,
+    /// Connect through the proxy only, refusing any direct connection.
+    pub proxy_only: bool,
+    /// Compare the answers of two providers, where a chain has a few.
+    pub quorum: bool,
+    /// Answer from the response cache only, without sending any request.
+    pub offline: bool,
+}
+
+impl CmdArgs {
+    /// Takes command line arguments, the options start with `--`.
+    pub fn parse() -> Result<CmdArgs, InvalidInput> {
         let args: Vec<_> = std::env::args_os()
+            .skip(1)
             .map(|arg| arg.into_string())
             .collect::<Result<_, _>>()
             .map_err(|_| InvalidInput::ExpectedUtf8Args)?;
 
-        match args.len() {
-            0 | 1 => Err(InvalidInput::Empty),
-            2 => Ok(WalletInput::Entropy(args[1].clone())),
-            // Compile the words into a single string.
-            _ => Ok(WalletInput::Mnemonic(args[1..].join(" "))),
+        let (mut proxy_only, mut quorum, mut offline) = (false, false, false);
+        let options_len = args.iter().take_while(|arg| arg.starts_with("--")).count();
+        for option in &args[..options_len] {
+            match option.as_str() {
+                PROXY_ONLY_OPTION => proxy_only = true,
+                QUORUM_OPTION => quorum = true,
+                OFFLINE_OPTION => offline = true,
+                _ => return Err(InvalidInput::UnknownOption(option.clone())),
+            }
         }
+        let input = WalletInput::from_words(&args[options_len..])?;
+        Ok(CmdArgs {
+            input,
+            proxy_only,
+            quorum,
+            offline,
+        })
     }
 }